@@ -0,0 +1,304 @@
+//! Benchmarks comparing alternative solvers for the same problem. The group
+//! names are prefixed with the year and padded day so that they show up in
+//! `aoc bench-history` alongside the regular benchmarks.
+use a_long_walk::ALongWalk;
+use aoc_plumbing::Problem;
+use aoc_testing::{day05::Almanac, Generator};
+use clumsy_crucible::ClumsyCrucible;
+use cosmic_expansion::CosmicExpansion;
+use criterion::{criterion_group, criterion_main, Criterion};
+use gear_ratios::GearRatios;
+use if_you_give_a_seed_a_fertilizer::IfYouGiveASeedAFertilizer;
+use parabolic_reflector_dish::{ParabolicReflectorDish, TiltStrategy};
+use pulse_propagation::PulsePropagation;
+use sand_slabs::SandSlabs;
+use snowverload::Snowverload;
+use trebuchet::Trebuchet;
+
+#[cfg(feature = "track-allocations")]
+#[global_allocator]
+static ALLOCATOR: aoc_benchmarking::alloc::TrackingAllocator =
+    aoc_benchmarking::alloc::TrackingAllocator;
+
+/// Repeats the day 1 input to get ~100MB.
+fn day_001_large(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("{} large", Trebuchet::problem_label()));
+    group.sample_size(10);
+    let input =
+        std::fs::read_to_string("../day-001-trebuchet/input.txt").expect("Could not load input");
+    let input = input.repeat(100_000_000 / input.len());
+
+    group.bench_function("Combined (including parsing)", |b| {
+        #[cfg(feature = "track-allocations")]
+        {
+            static REPORT: std::sync::Once = std::sync::Once::new();
+            REPORT.call_once(|| aoc_benchmarking::alloc::report::<Trebuchet>(&input));
+        }
+
+        b.iter(|| Trebuchet::solve(&input).expect("Failed to solve"))
+    });
+    group.finish();
+}
+
+/// Tiles the day 3 input horizontally to get ~100k character lines.
+fn day_003_wide(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("{} wide", GearRatios::problem_label()));
+    group.sample_size(10);
+    let input =
+        std::fs::read_to_string("../day-003-gear-ratios/input.txt").expect("Could not load input");
+    let width = input.lines().next().map(|l| l.len() + 1).unwrap_or(1);
+    let copies = 100_000 / width;
+    let input = input
+        .lines()
+        .map(|l| format!("{}.", l).repeat(copies))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    group.bench_function("Combined (including parsing)", |b| {
+        b.iter(|| GearRatios::solve(&input).expect("Failed to solve"))
+    });
+    group.finish();
+}
+
+fn day_005_generated(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!(
+        "{} generated",
+        IfYouGiveASeedAFertilizer::problem_label()
+    ));
+    group.sample_size(10);
+
+    // values as large as the real input's, with up to a few hundred mappings
+    // per stage
+    let mut almanac = Almanac {
+        max_mappings: 300,
+        max_value: 1 << 32,
+        max_range: 10_000_000,
+        ..Default::default()
+    };
+    for n in [1_000, 10_000, 100_000] {
+        almanac.seed_ranges = n;
+        let input = almanac.generate_seeded(0);
+        let problem = IfYouGiveASeedAFertilizer::instance(&input).expect("Could not parse input");
+
+        group.bench_function(format!("{} seed ranges (sequential)", n), |b| {
+            b.iter(|| problem.min_location_witness_sequential())
+        });
+        group.bench_function(format!("{} seed ranges (parallel)", n), |b| {
+            b.iter(|| problem.min_location_witness())
+        });
+    }
+    group.finish();
+}
+
+fn day_011(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("{} solvers", CosmicExpansion::problem_label()));
+    let input = std::fs::read_to_string("../day-011-cosmic-expansion/input.txt")
+        .expect("Could not load input");
+    let problem = CosmicExpansion::instance(&input).expect("Could not parse input");
+
+    group.bench_function("Distances (pairwise)", |b| {
+        b.iter(|| problem.total_distances_formula())
+    });
+    group.bench_function("Distances (sorted)", |b| {
+        b.iter(|| problem.total_distances_formula_sorted())
+    });
+    group.finish();
+}
+
+fn day_014(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!(
+        "{} solvers",
+        ParabolicReflectorDish::problem_label()
+    ));
+    let input = std::fs::read_to_string("../day-014-parabolic-reflector-dish/input.txt")
+        .expect("Could not load input");
+    let problem = ParabolicReflectorDish::instance(&input).expect("Could not parse input");
+
+    for (name, strategy) in [
+        ("grid walk", TiltStrategy::GridWalk),
+        ("segments", TiltStrategy::Segments),
+    ] {
+        let problem = problem.clone().with_strategy(strategy);
+        group.bench_function(format!("Part 2 ({})", name), |b| {
+            b.iter(|| {
+                problem
+                    .clone()
+                    .part_two()
+                    .expect("Failed to solve part two")
+            })
+        });
+    }
+    group.finish();
+}
+
+fn day_017(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("{} solvers", ClumsyCrucible::problem_label()));
+    let input = std::fs::read_to_string("../day-017-clumsy-crucible/input.txt")
+        .expect("Could not load input");
+    let problem = ClumsyCrucible::instance(&input).expect("Could not parse input");
+
+    group.bench_function("Part 2 (bucket queue)", |b| {
+        let mut problem = problem.clone();
+        b.iter(|| problem.part_two().expect("Failed to solve part two"))
+    });
+    group.bench_function("Part 2 (binary heap)", |b| {
+        b.iter(|| problem.dijkstra_heap(4, 10))
+    });
+    group.finish();
+}
+
+fn day_020(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("{} solvers", PulsePropagation::problem_label()));
+    let input = std::fs::read_to_string("../day-020-pulse-propagation/input.txt")
+        .expect("Could not load input");
+    let problem = PulsePropagation::instance(&input).expect("Could not parse input");
+
+    group.bench_function("Part 1 (simulation)", |b| {
+        b.iter(|| {
+            problem
+                .clone()
+                .simulate(1000)
+                .expect("Failed to solve part one")
+        })
+    });
+    group.bench_function("Part 1 (counters)", |b| {
+        let mut problem = problem.clone();
+        b.iter(|| problem.pulses(1000).expect("Failed to solve part one"))
+    });
+    group.finish();
+}
+
+/// Stacks `n` random slabs over a footprint that grows with `n`, so the
+/// stack stays roughly as tall as the real input's relative to its area.
+fn day_022_input(n: usize) -> String {
+    // xorshift, so the inputs are the same on every run
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut rand = |bound: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % bound
+    };
+
+    let side = ((n as f64).sqrt() as u64 / 3).max(10);
+    let mut z = 1;
+    let mut lines = Vec::with_capacity(n);
+    for _ in 0..n {
+        let (x, y) = (rand(side), rand(side));
+        let len = rand(5);
+        let (dx, dy, dz) = match rand(3) {
+            0 => (len.min(side - 1 - x), 0, 0),
+            1 => (0, len.min(side - 1 - y), 0),
+            _ => (0, 0, len),
+        };
+        lines.push(format!(
+            "{},{},{}~{},{},{}",
+            x,
+            y,
+            z,
+            x + dx,
+            y + dy,
+            z + dz
+        ));
+        z += dz + 1;
+    }
+
+    lines.join("\n")
+}
+
+fn day_022_generated(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("{} generated", SandSlabs::problem_label()));
+    group.sample_size(10);
+
+    for n in [1_000, 10_000, 100_000] {
+        let input = day_022_input(n);
+        let problem = SandSlabs::instance(&input).expect("Could not parse input");
+
+        group.bench_function(format!("{} slabs (parsing)", n), |b| {
+            b.iter(|| SandSlabs::instance(&input).expect("Could not parse input"))
+        });
+        group.bench_function(format!("{} slabs (heightmap)", n), |b| {
+            b.iter(|| {
+                let mut problem = problem.clone();
+                problem.part_one().expect("Failed to solve part one");
+                problem.part_two().expect("Failed to solve part two")
+            })
+        });
+        group.bench_function(format!("{} slabs (sweep line)", n), |b| {
+            b.iter(|| {
+                let mut problem = problem.clone();
+                problem.settle_sweepline();
+                problem.part_one().expect("Failed to solve part one");
+                problem.part_two().expect("Failed to solve part two")
+            })
+        });
+    }
+    group.finish();
+}
+
+fn day_023(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("{} solvers", ALongWalk::problem_label()));
+    let input =
+        std::fs::read_to_string("../day-023-a-long-walk/input.txt").expect("Could not load input");
+    let problem = ALongWalk::instance(&input).expect("Could not parse input");
+
+    group.bench_function("Part 2 (neighbour lists)", |b| {
+        let mut problem = problem.clone();
+        b.iter(|| problem.part_two().expect("Failed to solve part two"))
+    });
+    group.bench_function("Part 2 (adjacency matrix)", |b| {
+        b.iter(|| {
+            problem
+                .longest_path_matrix(10)
+                .expect("Failed to solve part two")
+        })
+    });
+    group.finish();
+}
+
+fn day_025(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("{} solvers", Snowverload::problem_label()));
+    // Stoer-Wagner takes around half a second
+    group.sample_size(10);
+    let input =
+        std::fs::read_to_string("../day-025-snowverload/input.txt").expect("Could not load input");
+    let problem = Snowverload::instance(&input).expect("Could not parse input");
+
+    group.bench_function("Part 1 (max flow)", |b| {
+        let mut problem = problem.clone();
+        b.iter(|| problem.part_one().expect("Failed to solve part one"))
+    });
+    group.bench_function("Part 1 (betweenness)", |b| {
+        b.iter(|| {
+            problem
+                .min_cut_betweenness(20, 25)
+                .expect("Failed to solve part one")
+        })
+    });
+    group.bench_function("Part 1 (Karger-Stein)", |b| {
+        b.iter(|| {
+            problem
+                .min_cut_contraction(2, 0)
+                .expect("Failed to solve part one")
+        })
+    });
+    group.bench_function("Part 1 (Stoer-Wagner)", |b| {
+        b.iter(|| problem.min_cut_exact().expect("Failed to solve part one"))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    experimental,
+    day_001_large,
+    day_003_wide,
+    day_005_generated,
+    day_011,
+    day_014,
+    day_017,
+    day_020,
+    day_022_generated,
+    day_023,
+    day_025
+);
+criterion_main!(experimental);
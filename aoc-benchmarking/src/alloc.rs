@@ -0,0 +1,168 @@
+//! Optional allocation tracking for benchmarks.
+//!
+//! The bench binary installs [`TrackingAllocator`] as its global allocator
+//! when built with the `track-allocations` feature, and each day then reports
+//! how much it allocated while parsing and solving. Without the allocator
+//! installed, every measurement comes back as zero.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use aoc_plumbing::Problem;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Wraps the system allocator, counting allocations and tracking the peak
+/// number of live bytes.
+pub struct TrackingAllocator;
+
+impl TrackingAllocator {
+    fn record_alloc(size: usize) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(size, Ordering::Relaxed);
+        let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn record_dealloc(size: usize) {
+        CURRENT.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            Self::record_dealloc(layout.size());
+            Self::record_alloc(new_size);
+        }
+        new
+    }
+}
+
+/// The allocations made during a measurement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// The number of allocations, counting reallocations.
+    pub allocations: usize,
+    /// The total number of bytes allocated.
+    pub bytes: usize,
+    /// The most bytes live at once, above what was live at the start.
+    pub peak: usize,
+}
+
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>10} allocs {:>12} total {:>12} peak",
+            self.allocations,
+            format_bytes(self.bytes),
+            format_bytes(self.peak)
+        )
+    }
+}
+
+/// Runs `f`, returning its result along with what it allocated.
+///
+/// The counters are global, so this is only meaningful when nothing else is
+/// allocating at the same time.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, AllocStats) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED.load(Ordering::Relaxed);
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+
+    let ret = f();
+
+    let stats = AllocStats {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        bytes: ALLOCATED.load(Ordering::Relaxed) - bytes,
+        peak: PEAK.load(Ordering::Relaxed).saturating_sub(baseline),
+    };
+
+    (ret, stats)
+}
+
+/// Measures parsing and each part of the given problem, printing the results
+/// under the problem's label.
+pub fn report<T>(input: &str)
+where
+    T: Problem,
+    <T as FromStr>::Err: fmt::Debug,
+    <T as Problem>::ProblemError: fmt::Debug,
+{
+    let (problem, parse) = measure(|| T::instance(input).expect("Could not parse input"));
+    let mut problem = problem;
+    let (_, part_one) = measure(|| problem.part_one().expect("Failed to solve part one"));
+    let (_, part_two) = measure(|| problem.part_two().expect("Failed to solve part two"));
+
+    println!("{} allocations", T::problem_label());
+    println!("  {:<8} {}", "Parse", parse);
+    println!("  {:<8} {}", "Part 1", part_one);
+    println!("  {:<8} {}", "Part 2", part_two);
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_bytes_test() {
+        assert_eq!(format_bytes(12), "12 B");
+        assert_eq!(format_bytes(2048), "2.0 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024 + 512 * 1024), "5.5 MiB");
+    }
+
+    #[test]
+    fn stats_without_allocator() {
+        // the test binary uses the system allocator, so nothing is counted
+        let (v, stats) = measure(|| vec![0u8; 1024]);
+        assert_eq!(v.len(), 1024);
+        assert_eq!(stats, AllocStats::default());
+    }
+}
@@ -0,0 +1,74 @@
+//! Restricting the benchmarks to a subset of days.
+//!
+//! Setting `AOC_BENCH_DAYS` to a comma separated list of days (e.g.
+//! `AOC_BENCH_DAYS=17,23`) skips every other day's benchmarks, as well as the
+//! total runtime, which only makes sense for every day. Unlike criterion's
+//! name filter, the days don't need to be padded and `1` doesn't also match
+//! day 10-19.
+//!
+//! When several years share the benchmarks, `AOC_BENCH_YEARS` does the same
+//! for years, so `AOC_BENCH_YEARS=2023 AOC_BENCH_DAYS=17` runs only 2023's
+//! day 17.
+use std::num::ParseIntError;
+
+pub const DAYS_VAR: &str = "AOC_BENCH_DAYS";
+pub const YEARS_VAR: &str = "AOC_BENCH_YEARS";
+
+/// Parses a comma separated list of days (or years), ignoring whitespace and
+/// empty entries.
+pub fn parse_days(s: &str) -> Result<Vec<usize>, ParseIntError> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Returns the days listed in `AOC_BENCH_DAYS`, or `None` if it's unset or
+/// empty. Panics if it can't be parsed, as silently running every day would
+/// be surprising.
+pub fn selected_days() -> Option<Vec<usize>> {
+    selected(DAYS_VAR)
+}
+
+/// Returns the years listed in `AOC_BENCH_YEARS`, like [selected_days].
+pub fn selected_years() -> Option<Vec<usize>> {
+    selected(YEARS_VAR)
+}
+
+fn selected(var: &str) -> Option<Vec<usize>> {
+    let value = std::env::var(var).ok()?;
+    let values = parse_days(&value).unwrap_or_else(|e| {
+        panic!(
+            "{} should be a comma separated list of numbers, got {:?}: {}",
+            var, value, e
+        )
+    });
+
+    (!values.is_empty()).then_some(values)
+}
+
+/// Whether the benchmarks for `day` of `year` should run.
+pub fn is_selected(year: usize, day: usize) -> bool {
+    selected_years().is_none_or(|years| years.contains(&year))
+        && selected_days().is_none_or(|days| days.contains(&day))
+}
+
+/// Whether every day is selected, i.e. neither `AOC_BENCH_DAYS` nor
+/// `AOC_BENCH_YEARS` is set.
+pub fn all_selected() -> bool {
+    selected_days().is_none() && selected_years().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_days("17,23").unwrap(), vec![17, 23]);
+        assert_eq!(parse_days(" 1, 2 ,,3, ").unwrap(), vec![1, 2, 3]);
+        assert_eq!(parse_days("").unwrap(), Vec::<usize>::new());
+        assert!(parse_days("17,x").is_err());
+    }
+}
@@ -1,12 +1,58 @@
 #[macro_export]
 macro_rules! aoc_bench {
+    // "standard" solution with two distinct parts, also timing the parsing
+    // on its own
+    ($name:ident, $input:literal, $problem:ty, $parse_desc:literal, $part1_desc:literal, $part2_desc:literal) => {
+        pub fn $name(c: &mut Criterion) {
+            if !aoc_benchmarking::days::is_selected(<$problem>::YEAR, <$problem>::DAY) {
+                return;
+            }
+
+            let mut group = c.benchmark_group(<$problem>::problem_label());
+            let input = std::fs::read_to_string($input).expect("Could not load input");
+
+            group.bench_function($parse_desc, |b| {
+                #[cfg(feature = "track-allocations")]
+                {
+                    static REPORT: std::sync::Once = std::sync::Once::new();
+                    REPORT.call_once(|| aoc_benchmarking::alloc::report::<$problem>(&input));
+                }
+
+                b.iter(|| <$problem>::instance(&input).expect("Could not parse input"))
+            });
+            group.bench_function($part1_desc, |b| {
+                let mut problem = <$problem>::instance(&input).expect("Could not parse input");
+                b.iter(|| problem.part_one().expect("Failed to solve part one"))
+            });
+            group.bench_function($part2_desc, |b| {
+                let mut problem = <$problem>::instance(&input).expect("Could not parse input");
+                b.iter(|| problem.part_two().expect("Failed to solve part two"))
+            });
+            group.bench_function("Combined", |b| {
+                b.iter(|| <$problem>::solve(&input).expect("Failed to solve"))
+            });
+            group.finish();
+        }
+    };
     // "standard" solution with two distinct parts
     ($name:ident, $input:literal, $problem:ty, $part1_desc:literal, $part2_desc:literal) => {
         pub fn $name(c: &mut Criterion) {
+            if !aoc_benchmarking::days::is_selected(<$problem>::YEAR, <$problem>::DAY) {
+                return;
+            }
+
             let mut group = c.benchmark_group(<$problem>::problem_label());
             let input = std::fs::read_to_string($input).expect("Could not load input");
 
             group.bench_function($part1_desc, |b| {
+                // report inside the closure so that it respects the filter,
+                // but only once, as criterion calls this repeatedly
+                #[cfg(feature = "track-allocations")]
+                {
+                    static REPORT: std::sync::Once = std::sync::Once::new();
+                    REPORT.call_once(|| aoc_benchmarking::alloc::report::<$problem>(&input));
+                }
+
                 let mut problem = <$problem>::instance(&input).expect("Could not parse input");
                 b.iter(|| problem.part_one().expect("Failed to solve part one"))
             });
@@ -23,10 +69,20 @@ macro_rules! aoc_bench {
     // combined solution
     ($name:ident, $input:literal, $problem:ty, $combined_desc:literal) => {
         pub fn $name(c: &mut Criterion) {
+            if !aoc_benchmarking::days::is_selected(<$problem>::YEAR, <$problem>::DAY) {
+                return;
+            }
+
             let mut group = c.benchmark_group(<$problem>::problem_label());
             let input = std::fs::read_to_string($input).expect("Could not load input");
 
             group.bench_function($combined_desc, |b| {
+                #[cfg(feature = "track-allocations")]
+                {
+                    static REPORT: std::sync::Once = std::sync::Once::new();
+                    REPORT.call_once(|| aoc_benchmarking::alloc::report::<$problem>(&input));
+                }
+
                 b.iter(|| <$problem>::solve(&input).expect("Failed to solve"))
             });
             group.finish();
@@ -47,6 +103,10 @@ macro_rules! aoc_benches {
         )*
 
         pub fn aoc_combined(c: &mut Criterion) {
+            if !aoc_benchmarking::days::all_selected() {
+                return;
+            }
+
             let mut group = c.benchmark_group("Advent of Code");
             group.measurement_time(Duration::new($comb_seconds, 0));
             group.bench_function("Total runtime for all solutions, including parsing", |b| {
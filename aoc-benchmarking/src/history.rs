@@ -0,0 +1,329 @@
+//! Tracks benchmark results over time.
+//!
+//! After a `cargo bench` run, criterion leaves its latest estimates under
+//! `target/criterion`. [`HistoryEntry::collect`] gathers those, along with some
+//! metadata about the build, into a single entry that can be appended to an
+//! NDJSON history file and later displayed as a trend table.
+use std::{
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The mean runtime of a single benchmark.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Timing {
+    /// The year of the day's event. Missing for timings recorded before
+    /// groups were labeled with the year.
+    #[serde(default)]
+    pub year: Option<usize>,
+    /// The day, if the benchmark group belongs to one.
+    pub day: Option<usize>,
+    pub group: String,
+    pub bench: String,
+    pub mean_ns: f64,
+}
+
+/// A single benchmark run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Seconds since the unix epoch.
+    pub timestamp: u64,
+    pub commit: String,
+    pub dirty: bool,
+    pub rustc: String,
+    pub timings: Vec<Timing>,
+}
+
+#[derive(Deserialize)]
+struct CriterionBenchmark {
+    group_id: String,
+    function_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CriterionEstimate {
+    point_estimate: f64,
+}
+
+#[derive(Deserialize)]
+struct CriterionEstimates {
+    mean: CriterionEstimate,
+}
+
+impl HistoryEntry {
+    /// Gathers the latest estimates from the given criterion output directory
+    /// (usually `target/criterion`), stamped with the current time, commit,
+    /// and rustc version.
+    pub fn collect(criterion_dir: &Path) -> io::Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let commit = command_output("git", &["rev-parse", "HEAD"]);
+        let dirty = !command_output("git", &["status", "--porcelain"]).is_empty();
+        let rustc = command_output("rustc", &["--version"]);
+
+        Ok(Self {
+            timestamp,
+            commit: if commit.is_empty() {
+                "unknown".to_string()
+            } else {
+                commit
+            },
+            dirty,
+            rustc,
+            timings: collect_timings(criterion_dir)?,
+        })
+    }
+
+    /// Returns the timings for the given day. Timings without a year count
+    /// for any year.
+    pub fn day(&self, year: usize, day: usize) -> impl Iterator<Item = &Timing> {
+        self.timings
+            .iter()
+            .filter(move |t| t.day == Some(day) && t.year.is_none_or(|y| y == year))
+    }
+}
+
+fn command_output(cmd: &str, args: &[&str]) -> String {
+    Command::new(cmd)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Reads every `<group>/<bench>/new` estimate under the criterion directory.
+fn collect_timings(criterion_dir: &Path) -> io::Result<Vec<Timing>> {
+    let mut timings = Vec::default();
+
+    for group in fs::read_dir(criterion_dir)? {
+        let group = group?.path();
+        if !group.is_dir() {
+            continue;
+        }
+
+        for bench in fs::read_dir(&group)? {
+            let new = bench?.path().join("new");
+            let (Ok(benchmark), Ok(estimates)) = (
+                fs::read_to_string(new.join("benchmark.json")),
+                fs::read_to_string(new.join("estimates.json")),
+            ) else {
+                continue;
+            };
+
+            let benchmark: CriterionBenchmark = serde_json::from_str(&benchmark)?;
+            let estimates: CriterionEstimates = serde_json::from_str(&estimates)?;
+
+            let (year, day) = parse_day(&benchmark.group_id);
+            timings.push(Timing {
+                year,
+                day,
+                group: benchmark.group_id,
+                bench: benchmark.function_id.unwrap_or_default(),
+                mean_ns: estimates.mean.point_estimate,
+            });
+        }
+    }
+
+    timings.sort_by(|a, b| {
+        (a.year, a.day, &a.group, &a.bench).cmp(&(b.year, b.day, &b.group, &b.bench))
+    });
+    Ok(timings)
+}
+
+/// Groups are labeled with the year and zero-padded day, e.g.
+/// `2023 017 clumsy crucible`, or just the day in older runs.
+fn parse_day(group: &str) -> (Option<usize>, Option<usize>) {
+    let mut words = group.split_whitespace().map(|w| w.parse::<usize>().ok());
+    match (words.next().flatten(), words.next().flatten()) {
+        (Some(year), Some(day)) => (Some(year), Some(day)),
+        (day, _) => (None, day),
+    }
+}
+
+/// Appends the entry as a single line to the NDJSON history file, creating it
+/// if necessary.
+pub fn append(path: &Path, entry: &HistoryEntry) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)
+}
+
+/// Loads every entry from the NDJSON history file, oldest first.
+pub fn load(path: &Path) -> io::Result<Vec<HistoryEntry>> {
+    let file = fs::File::open(path)?;
+    let mut entries = Vec::default();
+
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(serde_json::from_str(&line)?);
+    }
+
+    Ok(entries)
+}
+
+/// Formats a duration in nanoseconds with a sensible unit.
+pub fn format_ns(ns: f64) -> String {
+    if ns < 1e3 {
+        format!("{:.1} ns", ns)
+    } else if ns < 1e6 {
+        format!("{:.1} µs", ns / 1e3)
+    } else if ns < 1e9 {
+        format!("{:.1} ms", ns / 1e6)
+    } else {
+        format!("{:.2} s", ns / 1e9)
+    }
+}
+
+/// Renders a table with one row per run and one column per benchmark of the
+/// given day.
+pub fn trend_table(entries: &[HistoryEntry], year: usize, day: usize) -> String {
+    let mut benches: Vec<&str> = Vec::default();
+    for entry in entries {
+        for timing in entry.day(year, day) {
+            if !benches.contains(&timing.bench.as_str()) {
+                benches.push(&timing.bench);
+            }
+        }
+    }
+
+    let mut out = String::new();
+    let _ = write!(out, "{:<12} {:<10} {:<6}", "timestamp", "commit", "dirty");
+    for bench in &benches {
+        let _ = write!(out, " {:>30}", bench);
+    }
+    out.push('\n');
+
+    for entry in entries {
+        if entry.day(year, day).next().is_none() {
+            continue;
+        }
+
+        let commit: String = entry.commit.chars().take(10).collect();
+        let _ = write!(
+            out,
+            "{:<12} {:<10} {:<6}",
+            entry.timestamp,
+            commit,
+            if entry.dirty { "yes" } else { "no" }
+        );
+        for bench in &benches {
+            let cell = entry
+                .day(year, day)
+                .find(|t| t.bench == *bench)
+                .map(|t| format_ns(t.mean_ns))
+                .unwrap_or_else(|| "-".to_string());
+            let _ = write!(out, " {:>30}", cell);
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_bench(dir: &Path, group: &str, bench: &str, mean: f64) {
+        let new = dir.join(group).join(bench).join("new");
+        fs::create_dir_all(&new).unwrap();
+        fs::write(
+            new.join("benchmark.json"),
+            format!(
+                "{{\"group_id\":\"{}\",\"function_id\":\"{}\",\"full_id\":\"{}/{}\"}}",
+                group, bench, group, bench
+            ),
+        )
+        .unwrap();
+        fs::write(
+            new.join("estimates.json"),
+            format!("{{\"mean\":{{\"point_estimate\":{}}}}}", mean),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn collect_and_round_trip() {
+        let dir = std::env::temp_dir().join(format!("aoc-bench-history-{}", std::process::id()));
+        let criterion = dir.join("criterion");
+        write_bench(&criterion, "2023 017 clumsy crucible", "Part 2", 2500.0);
+        write_bench(&criterion, "2023 017 clumsy crucible", "Part 1", 1500.0);
+        write_bench(&criterion, "2022 017 pyroclastic flow", "Part 1", 10.0);
+        write_bench(&criterion, "Advent of Code", "Total", 9e9);
+
+        let timings = collect_timings(&criterion).unwrap();
+        assert_eq!(
+            timings
+                .iter()
+                .map(|t| (t.year, t.day, t.bench.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (None, None, "Total"),
+                (Some(2022), Some(17), "Part 1"),
+                (Some(2023), Some(17), "Part 1"),
+                (Some(2023), Some(17), "Part 2")
+            ]
+        );
+
+        let entry = HistoryEntry {
+            timestamp: 1,
+            commit: "abcdef1234567".to_string(),
+            dirty: true,
+            rustc: "rustc 1.74.0".to_string(),
+            timings,
+        };
+        let history = dir.join("history.ndjson");
+        append(&history, &entry).unwrap();
+        append(&history, &entry).unwrap();
+        let loaded = load(&history).unwrap();
+        assert_eq!(loaded, vec![entry.clone(), entry]);
+
+        let table = trend_table(&loaded, 2023, 17);
+        assert_eq!(table.lines().count(), 3);
+        assert!(table.contains("abcdef1234 yes"));
+        assert!(table.contains("1.5 µs"));
+        assert!(!table.contains("10.0 ns"));
+        assert!(trend_table(&loaded, 2023, 3).lines().count() == 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn parse_day_test() {
+        assert_eq!(
+            parse_day("2023 017 clumsy crucible"),
+            (Some(2023), Some(17))
+        );
+        // recorded before groups had the year
+        assert_eq!(parse_day("017 clumsy crucible"), (None, Some(17)));
+        assert_eq!(parse_day("017 large"), (None, Some(17)));
+        assert_eq!(parse_day("Advent of Code"), (None, None));
+
+        let old: Timing = serde_json::from_str(
+            r#"{"day":17,"group":"017 clumsy crucible","bench":"Part 1","mean_ns":1.0}"#,
+        )
+        .unwrap();
+        assert_eq!(old.year, None);
+    }
+
+    #[test]
+    fn format_ns_test() {
+        assert_eq!(format_ns(12.0), "12.0 ns");
+        assert_eq!(format_ns(1234.0), "1.2 µs");
+        assert_eq!(format_ns(12_345_678.0), "12.3 ms");
+        assert_eq!(format_ns(2.5e9), "2.50 s");
+    }
+}
@@ -1 +1,4 @@
+pub mod alloc;
+pub mod days;
 pub mod helper_macros;
+pub mod history;
@@ -4,8 +4,9 @@ use std::{
 };
 
 use a_long_walk::ALongWalk;
-use anyhow::{Context, Result};
-use aoc_plumbing::Problem;
+use anyhow::{bail, Context, Result};
+use aoc_benchmarking::history::{self, HistoryEntry};
+use aoc_plumbing::{Answers, Problem};
 use aplenty::Aplenty;
 use camel_cards::CamelCards;
 use clap::{Args, CommandFactory, Parser, Subcommand};
@@ -34,9 +35,22 @@ use trebuchet::Trebuchet;
 use wait_for_it::WaitForIt;
 // import_marker
 
+/// The year `run`, `describe` and `bench-history` default to.
+const DEFAULT_YEAR: usize = 2023;
+
+/// The subcommand for a day: the generic [Solver] unless the day has its own.
+macro_rules! solver {
+    ($name:ident) => {
+        Solver<$name>
+    };
+    ($name:ident, $solver:ty) => {
+        $solver
+    };
+}
+
 // I'm not proud
 macro_rules! generate_cli {
-    ($(($name:ident, $day:literal)),* $(,)?) => {
+    ($(($name:ident, $day:literal $(, $solver:ty)?)),* $(,)?) => {
         /// Advent of Code solutions for 2023
         #[derive(Parser)]
         #[command(name = "aoc", author, version)]
@@ -59,7 +73,7 @@ macro_rules! generate_cli {
         pub(crate) enum Commands {
             $(
             #[command(about = $name::problem_label(), long_about = $name::long_description(), display_order = $day)]
-            $name(Solver<$name>),
+            $name(solver!($name $(, $solver)?)),
             )*
 
             #[command(display_order = 30)]
@@ -67,12 +81,24 @@ macro_rules! generate_cli {
 
             #[command(display_order = 31)]
             GenerateCompletions(GenerateCompletions),
+
+            #[command(display_order = 32)]
+            BenchHistory(BenchHistory),
+
+            #[command(display_order = 33)]
+            Verify(Verify),
+
+            #[command(display_order = 34)]
+            Describe(Describe),
         }
 
         impl Commands {
             pub fn run(&self) -> Result<()> {
                 match self {
                     Self::GenerateCompletions(cmd) => cmd.run(),
+                    Self::BenchHistory(cmd) => cmd.run(),
+                    Self::Verify(cmd) => cmd.run(),
+                    Self::Describe(cmd) => cmd.run(),
                     Self::Run(cmd) => cmd.run(),
                     $(
                     Self::$name(cmd) => cmd.run(),
@@ -94,6 +120,13 @@ macro_rules! generate_cli {
             #[clap(env = "AOC_DAY")]
             day: usize,
 
+            /// The year the day is from.
+            ///
+            /// This may be specified instead by setting the `AOC_YEAR` env
+            /// var.
+            #[clap(short, long, env = "AOC_YEAR", default_value_t = DEFAULT_YEAR)]
+            year: usize,
+
             /// The path to the input for this solution.
             ///
             /// This may be specified instead by setting the `AOC_INPUT` env
@@ -113,9 +146,11 @@ macro_rules! generate_cli {
 
         impl Run {
             pub fn run(&self) -> Result<()> {
-                match self.day {
+                match (self.year, self.day) {
                     $(
-                    $day => _run::<$name>(&self.input, self.json),
+                    (year, day) if year == $name::YEAR && day == $name::DAY => {
+                        _run::<$name>(&self.input, self.json)
+                    }
                     )*
                     _ => {
                         if self.json {
@@ -128,6 +163,84 @@ macro_rules! generate_cli {
                 }
             }
         }
+
+        /// Print statistics about an input for a specified day, such as line
+        /// counts, grid dimensions, graph sizes and value ranges.
+        ///
+        /// Useful for sanity checking a custom input before debugging a wrong
+        /// answer. The input is parsed, but not solved.
+        #[derive(Args)]
+        pub(crate) struct Describe {
+            /// The day the input is for.
+            day: usize,
+
+            /// The year the day is from.
+            #[clap(short, long, default_value_t = DEFAULT_YEAR)]
+            year: usize,
+
+            /// The path to the input.
+            input: PathBuf,
+
+            /// Display the output as json.
+            #[clap(short, long)]
+            json: bool,
+        }
+
+        impl Describe {
+            pub fn run(&self) -> Result<()> {
+                match (self.year, self.day) {
+                    $(
+                    (year, day) if year == $name::YEAR && day == $name::DAY => {
+                        _describe::<$name>(&self.input, self.json)
+                    }
+                    )*
+                    _ => bail!("day {} of {} is not implemented", self.day, self.year),
+                }
+            }
+        }
+
+        /// Solve every day with its personal input and compare the results
+        /// against the stored answers.
+        ///
+        /// Each day's input is read from `<root>/day-NNN-<title>/input.txt`.
+        /// Days without stored answers are reported but do not count as
+        /// failures.
+        #[derive(Args)]
+        pub(crate) struct Verify {
+            /// The TOML file containing the expected answers.
+            #[clap(short, long, default_value = "answers.toml")]
+            answers: PathBuf,
+
+            /// The workspace root containing the day crates.
+            #[clap(short, long, default_value = ".")]
+            root: PathBuf,
+
+            /// Only verify the days from this year.
+            #[clap(short, long)]
+            year: Option<usize>,
+        }
+
+        impl Verify {
+            pub fn run(&self) -> Result<()> {
+                let answers = Answers::load(&self.answers)
+                    .with_context(|| format!("Could not load {}", self.answers.display()))?;
+
+                let mut failed = 0;
+                $(
+                if self.year.is_none_or(|year| year == $name::YEAR)
+                    && !_verify::<$name>(&self.root, &answers)
+                {
+                    failed += 1;
+                }
+                )*
+
+                if failed > 0 {
+                    bail!("{} day(s) failed verification", failed);
+                }
+
+                Ok(())
+            }
+        }
     };
 }
 
@@ -157,6 +270,90 @@ where
     }
 }
 
+/// Day 19, which can also sort further lists of parts with the same
+/// workflows.
+#[derive(Args)]
+pub(crate) struct AplentySolver {
+    #[command(flatten)]
+    solver: Solver<Aplenty>,
+
+    /// A file of parts, one per line, to sort with the workflows from the
+    /// input. The sum of the ratings of the accepted parts is printed for
+    /// each. May be given more than once.
+    #[clap(long)]
+    extra_parts: Vec<PathBuf>,
+}
+
+impl AplentySolver {
+    pub fn run(&self) -> Result<()> {
+        self.solver.run()?;
+
+        if self.extra_parts.is_empty() {
+            return Ok(());
+        }
+
+        let input =
+            std::fs::read_to_string(&self.solver.input).context("Could not read input file")?;
+        let problem = Aplenty::instance(&input).context("Failed to parse input")?;
+        for file in &self.extra_parts {
+            let parts = std::fs::read_to_string(file)
+                .map_err(anyhow::Error::from)
+                .and_then(|s| aplenty::parse_parts(&s))
+                .with_context(|| format!("Could not read parts from {}", file.display()))?;
+            let sum = problem.sort_parts(&parts);
+            if self.solver.json {
+                let value = serde_json::json!({ "parts": file, "part_one": sum });
+                println!("{}", value);
+            } else {
+                println!("{}: {}", file.display(), sum);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Day 23, which can also write out the junction graph.
+#[derive(Args)]
+pub(crate) struct ALongWalkSolver {
+    #[command(flatten)]
+    solver: Solver<ALongWalk>,
+
+    /// Also write the junction graph to this file in Graphviz DOT format.
+    #[clap(long)]
+    dot: Option<PathBuf>,
+
+    /// Colour the longest path (without slopes) in the DOT output. This
+    /// searches for the path again, so it takes a while.
+    #[clap(long, requires = "dot")]
+    best_path: bool,
+}
+
+impl ALongWalkSolver {
+    pub fn run(&self) -> Result<()> {
+        self.solver.run()?;
+
+        if let Some(dot) = &self.dot {
+            let input =
+                std::fs::read_to_string(&self.solver.input).context("Could not read input file")?;
+            let problem = ALongWalk::instance(&input).context("Failed to parse input")?;
+            let path = if self.best_path {
+                let (_, path) = problem
+                    .longest_path_junctions()
+                    .context("Could not find a path")?;
+                Some(path)
+            } else {
+                None
+            };
+
+            std::fs::write(dot, problem.to_dot(path.as_deref()))
+                .with_context(|| format!("Could not write {}", dot.display()))?;
+        }
+
+        Ok(())
+    }
+}
+
 fn _run<T>(input_file: &Path, json: bool) -> Result<()>
 where
     T: Problem,
@@ -177,6 +374,75 @@ where
     Ok(())
 }
 
+fn _describe<T>(input_file: &Path, json: bool) -> Result<()>
+where
+    T: Problem,
+    <T as Problem>::ProblemError: Into<anyhow::Error>,
+{
+    let input = std::fs::read_to_string(input_file).context("Could not read input file")?;
+
+    let description = T::describe_input(&input)
+        .map_err(Into::<anyhow::Error>::into)
+        .context("Failed to parse input")?;
+
+    if json {
+        println!("{}", serde_json::to_string(&description)?);
+    } else {
+        println!("{}", description);
+    }
+
+    Ok(())
+}
+
+/// Solves a single day for `aoc verify`, printing the outcome. Returns false
+/// if the day could not be solved or any stored answer did not match.
+fn _verify<T>(root: &Path, answers: &Answers) -> bool
+where
+    T: Problem,
+    <T as Problem>::ProblemError: Into<anyhow::Error>,
+{
+    let label = T::problem_label();
+    let Some(answer) = answers.get(T::YEAR, T::DAY) else {
+        println!("{}: no stored answers", label);
+        return true;
+    };
+
+    let input_file = root
+        .join(format!(
+            "day-{}-{}",
+            T::padded_day(),
+            T::TITLE.replace(' ', "-")
+        ))
+        .join("input.txt");
+
+    let solution = std::fs::read_to_string(&input_file)
+        .context("Could not read input file")
+        .and_then(|input| {
+            T::solve(&input)
+                .map_err(Into::<anyhow::Error>::into)
+                .context("Failed to solve")
+        });
+
+    match solution {
+        Ok(solution) => {
+            let mismatches = answer.check(&solution);
+            if mismatches.is_empty() {
+                println!("{}: ok", label);
+                true
+            } else {
+                for mismatch in mismatches {
+                    println!("{}: {}", label, mismatch);
+                }
+                false
+            }
+        }
+        Err(e) => {
+            println!("{}: {:#}", label, e);
+            false
+        }
+    }
+}
+
 /// Generate zsh completions
 #[derive(Debug, Args)]
 pub struct GenerateCompletions;
@@ -188,6 +454,56 @@ impl GenerateCompletions {
     }
 }
 
+/// Record and display benchmark timings over time.
+///
+/// With `--record`, the latest criterion results (from a prior
+/// `cargo bench -p aoc-benchmarking`) are appended to the history file along
+/// with the current commit, dirty flag, and rustc version.
+#[derive(Debug, Args)]
+pub struct BenchHistory {
+    /// Print the trend table for this day.
+    #[clap(short, long)]
+    day: Option<usize>,
+
+    /// The year of the day to print.
+    #[clap(short, long, default_value_t = DEFAULT_YEAR)]
+    year: usize,
+
+    /// Append the latest criterion results to the history before printing.
+    #[clap(short, long)]
+    record: bool,
+
+    /// The NDJSON history file.
+    #[clap(short, long, default_value = "bench_history.ndjson")]
+    file: PathBuf,
+
+    /// The criterion output directory.
+    #[clap(long, default_value = "target/criterion")]
+    criterion_dir: PathBuf,
+}
+
+impl BenchHistory {
+    fn run(&self) -> Result<()> {
+        if self.record {
+            let entry = HistoryEntry::collect(&self.criterion_dir)
+                .context("Could not read criterion results")?;
+            history::append(&self.file, &entry).context("Could not write history file")?;
+            println!(
+                "recorded {} timings for {}",
+                entry.timings.len(),
+                entry.commit
+            );
+        }
+
+        if let Some(day) = self.day {
+            let entries = history::load(&self.file).context("Could not read history file")?;
+            print!("{}", history::trend_table(&entries, self.year, day));
+        }
+
+        Ok(())
+    }
+}
+
 generate_cli! {
     (Trebuchet, 1),
     (CubeConundrum, 2),
@@ -207,11 +523,11 @@ generate_cli! {
     (TheFloorWillBeLava, 16),
     (ClumsyCrucible, 17),
     (LavaductLagoon, 18),
-    (Aplenty, 19),
+    (Aplenty, 19, AplentySolver),
     (PulsePropagation, 20),
     (StepCounter, 21),
     (SandSlabs, 22),
-    (ALongWalk, 23),
+    (ALongWalk, 23, ALongWalkSolver),
     (NeverTellMeTheOdds, 24),
     (Snowverload, 25),
     // command_marker
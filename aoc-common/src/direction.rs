@@ -1,3 +1,6 @@
+//! The four compass directions, shared by every day that moves around a
+//! grid. Use [Coordinate::neighbour](crate::grid::Coordinate::neighbour) and
+//! [Coordinate::steps](crate::grid::Coordinate::steps) to move in one.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum Cardinal {
     North,
@@ -33,3 +36,21 @@ impl Cardinal {
         self.left().opposite()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns() {
+        for dir in Cardinal::all() {
+            assert_eq!(dir.left().right(), dir);
+            assert_eq!(dir.left().left(), dir.opposite());
+            assert_eq!(dir.right().right(), dir.opposite());
+            assert_ne!(dir.left(), dir.opposite());
+        }
+
+        assert_eq!(Cardinal::North.right(), Cardinal::East);
+        assert_eq!(Cardinal::East.right(), Cardinal::South);
+    }
+}
@@ -0,0 +1,40 @@
+//! The ways the helpers in this crate can fail, so that callers can match on
+//! what went wrong rather than on messages.
+//!
+//! ```
+//! use aoc_common::{error::Error, grid::{Coordinate, Grid}};
+//!
+//! let grid: Grid<char> = Grid::parse("#.\n.#").unwrap();
+//! assert_eq!(grid.try_get(Coordinate::new(1, 1)), Ok(&'#'));
+//! assert!(matches!(
+//!     grid.try_get(Coordinate::new(2, 0)),
+//!     Err(Error::OutOfBounds { .. })
+//! ));
+//! ```
+use thiserror::Error;
+
+use crate::grid::{Coordinate, ShapeError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum Error {
+    /// The text isn't a non-empty rectangle.
+    #[error(transparent)]
+    Shape(#[from] ShapeError),
+    /// A character isn't one the grid's cells can be made from. The `row`
+    /// and `col` are 0-based.
+    #[error("invalid character {ch:?} at row {}, column {}", row + 1, col + 1)]
+    Parse { row: usize, col: usize, ch: char },
+    /// `coord` isn't in the `n` by `m` grid.
+    #[error("{coord:?} is outside the {n}x{m} grid")]
+    OutOfBounds {
+        coord: Coordinate,
+        n: usize,
+        m: usize,
+    },
+    /// The system of equations has no unique solution, e.g. because the
+    /// lines involved are parallel.
+    #[error("degenerate matrix")]
+    DegenerateMatrix,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,191 @@
+use num::PrimInt;
+
+use crate::interval::Interval;
+
+/// An axis-aligned box in `N` dimensions, with a half-open interval along each
+/// axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HyperRect<T, const N: usize> {
+    intervals: [Interval<T>; N],
+}
+
+impl<T: PrimInt, const N: usize> Default for HyperRect<T, N> {
+    fn default() -> Self {
+        Self {
+            intervals: [Interval::new(T::zero(), T::zero()); N],
+        }
+    }
+}
+
+impl<T: PrimInt, const N: usize> HyperRect<T, N> {
+    pub fn new(intervals: [Interval<T>; N]) -> Self {
+        Self { intervals }
+    }
+
+    /// Returns the box spanning `start..end` along every axis.
+    pub fn cube(start: T, end: T) -> Self {
+        Self::new([Interval::new(start, end); N])
+    }
+
+    pub fn get(&self, dim: usize) -> &Interval<T> {
+        &self.intervals[dim]
+    }
+
+    pub fn set(&mut self, dim: usize, interval: Interval<T>) {
+        self.intervals[dim] = interval;
+    }
+
+    /// The number of points in the box. Panics if it doesn't fit in a `u64`.
+    pub fn volume(&self) -> u64 {
+        self.intervals
+            .iter()
+            .map(|x| x.len().to_u64().expect("length should fit in a u64"))
+            .try_fold(1_u64, |acc, x| acc.checked_mul(x))
+            .expect("volume should fit in a u64")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.iter().any(|x| x.is_empty())
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let mut ret = *self;
+        for dim in 0..N {
+            ret.intervals[dim] = self.intervals[dim].intersection(&other.intervals[dim])?;
+        }
+        Some(ret)
+    }
+
+    /// Splits the box along the given axis into the parts below and at or
+    /// above `x`. Parts that would be empty are returned as `None`.
+    pub fn split(&self, dim: usize, x: T) -> (Option<Self>, Option<Self>) {
+        let interval = self.intervals[dim];
+
+        if interval.end() <= x {
+            (Some(*self), None)
+        } else if interval.start() >= x {
+            (None, Some(*self))
+        } else {
+            let mut lower = *self;
+            let mut upper = *self;
+            lower.intervals[dim] = Interval::new(interval.start(), x);
+            upper.intervals[dim] = Interval::new(x, interval.end());
+            (Some(lower), Some(upper))
+        }
+    }
+
+    /// Returns a set of disjoint boxes covering the space in `self` that is not
+    /// in `other`.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let inter = match self.intersection(other) {
+            Some(inter) => inter,
+            None => return vec![*self],
+        };
+
+        let mut ret = Vec::default();
+        let mut cur = *self;
+        for dim in 0..N {
+            let (below, rest) = cur.split(dim, inter.intervals[dim].start());
+            ret.extend(below);
+
+            // rest can't be empty since the intersection is non-empty
+            let (middle, above) = rest
+                .expect("empty remainder")
+                .split(dim, inter.intervals[dim].end());
+            ret.extend(above);
+
+            cur = middle.expect("empty remainder");
+        }
+
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume() {
+        let rect = HyperRect::new([
+            Interval::new(0, 2),
+            Interval::new(1, 4),
+            Interval::new(-1, 4),
+        ]);
+        assert_eq!(rect.volume(), 30);
+        assert!(!rect.is_empty());
+        assert_eq!(
+            HyperRect::<isize, 4>::cube(1, 4001).volume(),
+            4000_u64.pow(4)
+        );
+        assert!(HyperRect::<isize, 2>::default().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "volume should fit in a u64")]
+    fn volume_overflow() {
+        HyperRect::<i64, 4>::cube(0, 1 << 20).volume();
+    }
+
+    #[test]
+    fn intersection() {
+        let a = HyperRect::new([Interval::new(0, 10), Interval::new(0, 10)]);
+        let b = HyperRect::new([Interval::new(5, 15), Interval::new(-5, 3)]);
+        let c = HyperRect::new([Interval::new(10, 15), Interval::new(0, 10)]);
+        assert_eq!(
+            a.intersection(&b),
+            Some(HyperRect::new([Interval::new(5, 10), Interval::new(0, 3)]))
+        );
+        assert_eq!(a.intersection(&c), None);
+    }
+
+    #[test]
+    fn split() {
+        let a = HyperRect::new([Interval::new(0, 10), Interval::new(0, 10)]);
+        assert_eq!(a.split(0, 0), (None, Some(a)));
+        assert_eq!(a.split(0, 10), (Some(a), None));
+        assert_eq!(
+            a.split(1, 4),
+            (
+                Some(HyperRect::new([Interval::new(0, 10), Interval::new(0, 4)])),
+                Some(HyperRect::new([Interval::new(0, 10), Interval::new(4, 10)])),
+            )
+        );
+    }
+
+    #[test]
+    fn subtract() {
+        let a = HyperRect::new([
+            Interval::new(0, 10),
+            Interval::new(0, 10),
+            Interval::new(0, 10),
+        ]);
+        let b = HyperRect::new([
+            Interval::new(2, 5),
+            Interval::new(-3, 4),
+            Interval::new(5, 20),
+        ]);
+
+        let pieces = a.subtract(&b);
+        let inter = a.intersection(&b).unwrap();
+        assert_eq!(
+            pieces.iter().map(|x| x.volume()).sum::<u64>(),
+            a.volume() - inter.volume()
+        );
+
+        for (i, x) in pieces.iter().enumerate() {
+            assert!(x.intersection(&b).is_none());
+            for y in &pieces[i + 1..] {
+                assert!(x.intersection(y).is_none());
+            }
+        }
+
+        let c = HyperRect::new([
+            Interval::new(20, 25),
+            Interval::new(0, 1),
+            Interval::new(0, 1),
+        ]);
+        assert_eq!(a.subtract(&c), vec![a]);
+        assert_eq!(a.subtract(&a), vec![]);
+    }
+}
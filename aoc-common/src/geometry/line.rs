@@ -0,0 +1,133 @@
+use num::{Float, Num, NumCast};
+
+use crate::{
+    algebra::{Point3, Ray, EPSILON},
+    error::Error,
+};
+
+/// Where two lines cross, with the parameters along each of the rays that
+/// describe them, such that `position` is `a.origin + a.dir * t` and
+/// `b.origin + b.dir * s`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineIntersection<T>
+where
+    T: Copy + Num,
+{
+    pub t: T,
+    pub s: T,
+    pub position: Point3<T>,
+}
+
+impl<T> LineIntersection<T>
+where
+    T: Copy + Num + Float,
+{
+    /// True if the crossing is at or ahead of both rays' origins.
+    pub fn is_forward(&self) -> bool {
+        self.t >= T::zero() && self.s >= T::zero()
+    }
+}
+
+fn epsilon<T: Float>() -> T {
+    <T as NumCast>::from(EPSILON).expect("EPSILON fits in any float")
+}
+
+/// Returns the parameters `(t, s)` of the points of closest approach between
+/// the lines described by rays `a` and `b`, such that `a.origin + a.dir * t`
+/// and `b.origin + b.dir * s` are the closest points. Fails with
+/// [Error::DegenerateMatrix] if the lines are parallel, as then every point
+/// is as close as any other.
+pub fn closest_points<T>(a: &Ray<T>, b: &Ray<T>) -> Result<(T, T), Error>
+where
+    T: Copy + Num + Float,
+{
+    let w = a.origin - b.origin;
+    let aa = a.dir.dot(&a.dir);
+    let ab = a.dir.dot(&b.dir);
+    let bb = b.dir.dot(&b.dir);
+    let aw = a.dir.dot(&w);
+    let bw = b.dir.dot(&w);
+
+    let denom = aa * bb - ab * ab;
+    if denom.abs() < epsilon() {
+        return Err(Error::DegenerateMatrix);
+    }
+
+    Ok(((ab * bw - bb * aw) / denom, (aa * bw - ab * aw) / denom))
+}
+
+/// Returns where the lines described by rays `a` and `b` cross, regardless of
+/// whether that is ahead of or behind either origin. Returns `None` if the
+/// lines are parallel or skew.
+pub fn line_intersection<T>(a: &Ray<T>, b: &Ray<T>) -> Option<LineIntersection<T>>
+where
+    T: Copy + Num + Float,
+{
+    let (t, s) = closest_points(a, b).ok()?;
+    let pa = a.origin + a.dir * t;
+    let pb = b.origin + b.dir * s;
+
+    // coordinates can be large, so the tolerance scales with them
+    let scale = T::one().max(pa.x.abs()).max(pa.y.abs()).max(pa.z.abs());
+    if (pa - pb).magnitude() > epsilon::<T>() * scale {
+        return None;
+    }
+
+    Some(LineIntersection { t, s, position: pa })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algebra::{Point3, Vector3};
+
+    use super::*;
+
+    #[test]
+    fn closest_points_skew() {
+        let a = Ray::new(Point3::origin(), Vector3::i());
+        let b = Ray::new(Point3::new(3.0, -2.0, 1.0), Vector3::j());
+        assert_eq!(closest_points(&a, &b), Ok((3.0, 2.0)));
+    }
+
+    #[test]
+    fn closest_points_intersecting() {
+        let a = Ray::new(Point3::new(19.0, 13.0, 0.0), Vector3::new(-2.0, 1.0, 0.0));
+        let b = Ray::new(Point3::new(18.0, 19.0, 0.0), Vector3::new(-1.0, -1.0, 0.0));
+        let (t, s) = closest_points(&a, &b).unwrap();
+        assert!((t - 7.0 / 3.0).abs() < EPSILON);
+        assert!((s - 11.0 / 3.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn line_intersection_params() {
+        let a = Ray::new(Point3::new(19.0, 13.0, 0.0), Vector3::new(-2.0, 1.0, 0.0));
+        let b = Ray::new(Point3::new(18.0, 19.0, 0.0), Vector3::new(-1.0, -1.0, 0.0));
+        let x = line_intersection(&a, &b).unwrap();
+        assert!((x.t - 7.0 / 3.0).abs() < EPSILON);
+        assert!((x.s - 11.0 / 3.0).abs() < EPSILON);
+        assert!((x.position.x - 14.333333).abs() < 1e-5);
+        assert!((x.position.y - 15.333333).abs() < 1e-5);
+        assert!(x.is_forward());
+
+        // crossing in the past for a only
+        let a = Ray::new(Point3::new(19.0, 13.0, 0.0), Vector3::new(-2.0, 1.0, 0.0));
+        let b = Ray::new(Point3::new(20.0, 19.0, 0.0), Vector3::new(1.0, -5.0, 0.0));
+        let x = line_intersection(&a, &b).unwrap();
+        assert!((x.t + 11.0 / 9.0).abs() < EPSILON);
+        assert!((x.s - 13.0 / 9.0).abs() < EPSILON);
+        assert!(!x.is_forward());
+
+        // skew lines don't intersect
+        let a = Ray::new(Point3::origin(), Vector3::i());
+        let b = Ray::new(Point3::new(3.0, -2.0, 1.0), Vector3::j());
+        assert_eq!(line_intersection(&a, &b), None);
+    }
+
+    #[test]
+    fn closest_points_parallel() {
+        let a = Ray::new(Point3::origin(), Vector3::i());
+        let b = Ray::new(Point3::new(0.0, 1.0, 0.0), Vector3::i() * 2.0);
+        assert_eq!(closest_points(&a, &b), Err(Error::DegenerateMatrix));
+        assert_eq!(line_intersection(&a, &b), None);
+    }
+}
@@ -1,3 +1,13 @@
+mod hyperrect;
+mod line;
+mod plane;
+mod segment;
+
+pub use hyperrect::HyperRect;
+pub use line::{closest_points, line_intersection, LineIntersection};
+pub use plane::Plane;
+pub use segment::Segment;
+
 use crate::algebra::{Point3, Ray, EPSILON};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
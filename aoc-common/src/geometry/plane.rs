@@ -0,0 +1,141 @@
+use num::Num;
+
+use crate::algebra::{Point3, Ray, Vector3, EPSILON};
+use crate::geometry::{IntersectRay, Intersection};
+
+/// A plane described by a point on it and its (not necessarily unit) normal.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Plane<T>
+where
+    T: Copy + Num,
+{
+    pub point: Point3<T>,
+    pub normal: Vector3<T>,
+}
+
+impl<T> Plane<T>
+where
+    T: Copy + Num,
+{
+    pub fn new(point: Point3<T>, normal: Vector3<T>) -> Self {
+        Self { point, normal }
+    }
+
+    /// Returns the plane passing through the three given points. The normal
+    /// will be zero if the points are collinear.
+    pub fn from_points(a: Point3<T>, b: Point3<T>, c: Point3<T>) -> Self {
+        Self::new(a, (b - a).cross(&(c - a)))
+    }
+
+    /// Returns the plane containing the line of the given ray and the given
+    /// point. The normal will be zero if the point lies on the line.
+    pub fn from_ray_and_point(ray: &Ray<T>, point: Point3<T>) -> Self {
+        Self::new(ray.origin, ray.dir.cross(&(point - ray.origin)))
+    }
+
+    pub fn is_degenerate(&self) -> bool {
+        self.normal == Vector3::zero()
+    }
+
+    /// Returns the distance from the plane to the point, scaled by the
+    /// magnitude of the normal. The sign indicates which side of the plane
+    /// the point is on.
+    pub fn scaled_distance(&self, point: Point3<T>) -> T {
+        (point - self.point).dot(&self.normal)
+    }
+
+    /// Returns the ray parameter `t` at which the line of the ray crosses this
+    /// plane, or `None` if the ray is parallel to the plane.
+    ///
+    /// For integral types, the result is truncated, so it is only exact if
+    /// the crossing happens at an integral `t`.
+    pub fn intersect_time(&self, ray: &Ray<T>) -> Option<T> {
+        let denom = ray.dir.dot(&self.normal);
+        if denom == T::zero() {
+            return None;
+        }
+
+        Some((self.point - ray.origin).dot(&self.normal) / denom)
+    }
+}
+
+impl Plane<i128> {
+    pub fn contains(&self, point: Point3<i128>) -> bool {
+        self.scaled_distance(point) == 0
+    }
+}
+
+impl IntersectRay for Plane<f64> {
+    fn intersect(&self, ray: &Ray<f64>) -> Option<Intersection> {
+        let denom = ray.dir.dot(&self.normal);
+        if denom.abs() < EPSILON {
+            return None;
+        }
+
+        let t = (self.point - ray.origin).dot(&self.normal) / denom;
+        if t > EPSILON {
+            Some(Intersection::new(t, ray.origin + ray.dir * t))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_points() {
+        let plane = Plane::from_points(
+            Point3::new(0, 0, 0),
+            Point3::new(1, 0, 0),
+            Point3::new(0, 1, 0),
+        );
+        assert_eq!(plane.normal, Vector3::new(0, 0, 1));
+        assert!(!plane.is_degenerate());
+
+        let plane = Plane::from_points(
+            Point3::new(0, 0, 0),
+            Point3::new(1, 1, 1),
+            Point3::new(2, 2, 2),
+        );
+        assert!(plane.is_degenerate());
+    }
+
+    #[test]
+    fn from_ray_and_point() {
+        let ray = Ray::new(Point3::new(1_i128, 2, 3), Vector3::new(1, 1, 0));
+        let plane = Plane::from_ray_and_point(&ray, Point3::origin());
+        assert!(plane.contains(Point3::origin()));
+        assert!(plane.contains(Point3::new(1, 2, 3)));
+        assert!(plane.contains(Point3::new(6, 7, 3)));
+        assert!(!plane.contains(Point3::new(0, 0, 1)));
+    }
+
+    #[test]
+    fn intersect_time() {
+        let plane = Plane::new(Point3::new(0, 0, 5), Vector3::k());
+        let ray = Ray::new(Point3::new(3, 4, -1), Vector3::new(1, 0, 2));
+        assert_eq!(plane.intersect_time(&ray), Some(3));
+
+        let parallel = Ray::new(Point3::new(3, 4, -1), Vector3::new(1, 1, 0));
+        assert_eq!(plane.intersect_time(&parallel), None);
+    }
+
+    #[test]
+    fn intersection_test() {
+        let plane = Plane::new(Point3::new(0.0, 0.0, 5.0), Vector3::k());
+
+        let ray = Ray::new(Point3::new(3.0, 4.0, -1.0), Vector3::new(1.0, 0.0, 2.0));
+        let expected = Intersection::new(3.0, Point3::new(6.0, 4.0, 5.0));
+        assert!(plane
+            .intersect(&ray)
+            .unwrap()
+            .approximately_equals(&expected));
+
+        // pointing away from the plane
+        let ray = Ray::new(Point3::new(3.0, 4.0, -1.0), Vector3::new(1.0, 0.0, -2.0));
+        assert_eq!(plane.intersect(&ray), None);
+    }
+}
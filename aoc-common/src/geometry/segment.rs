@@ -0,0 +1,95 @@
+use num::{Float, Num};
+
+use crate::algebra::{Point3, Ray, Vector3};
+
+/// A line segment between two points.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Segment<T>
+where
+    T: Copy + Num,
+{
+    pub start: Point3<T>,
+    pub end: Point3<T>,
+}
+
+impl<T> Segment<T>
+where
+    T: Copy + Num,
+{
+    pub fn new(start: Point3<T>, end: Point3<T>) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns the vector from `start` to `end`.
+    pub fn dir(&self) -> Vector3<T> {
+        self.end - self.start
+    }
+
+    /// Returns the point at parameter `t`, where `0` is `start` and `1` is
+    /// `end`.
+    pub fn point_at(&self, t: T) -> Point3<T> {
+        self.start + self.dir() * t
+    }
+
+    /// Returns the ray starting at `start` in the direction of `end`.
+    pub fn to_ray(&self) -> Ray<T> {
+        Ray::new(self.start, self.dir())
+    }
+}
+
+impl<T> Segment<T>
+where
+    T: Copy + Num + Float,
+{
+    pub fn length(&self) -> T {
+        self.dir().magnitude()
+    }
+
+    /// Returns the point on the segment that is closest to the given point.
+    pub fn closest_point(&self, point: Point3<T>) -> Point3<T> {
+        let dir = self.dir();
+        let norm = dir.norm();
+        if norm == T::zero() {
+            return self.start;
+        }
+
+        let t = (point - self.start).dot(&dir) / norm;
+        self.point_at(t.max(T::zero()).min(T::one()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_at() {
+        let segment = Segment::new(Point3::new(1, 1, 1), Point3::new(3, 5, 1));
+        assert_eq!(segment.dir(), Vector3::new(2, 4, 0));
+        assert_eq!(segment.point_at(0), Point3::new(1, 1, 1));
+        assert_eq!(segment.point_at(1), Point3::new(3, 5, 1));
+    }
+
+    #[test]
+    fn length() {
+        let segment = Segment::new(Point3::new(1.0, 1.0, 1.0), Point3::new(4.0, 5.0, 1.0));
+        assert_eq!(segment.length(), 5.0);
+    }
+
+    #[test]
+    fn closest_point() {
+        let segment = Segment::new(Point3::origin(), Point3::new(10.0, 0.0, 0.0));
+        assert_eq!(
+            segment.closest_point(Point3::new(4.0, 3.0, -2.0)),
+            Point3::new(4.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            segment.closest_point(Point3::new(-4.0, 3.0, -2.0)),
+            Point3::origin()
+        );
+        assert_eq!(
+            segment.closest_point(Point3::new(14.0, 3.0, -2.0)),
+            Point3::new(10.0, 0.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::{hash::Hash, ops::Range};
+
+use rustc_hash::FxHashMap;
+
+/// An undirected graph with labeled vertices. Labels are interned to dense
+/// indices, which are what all of the adjacency queries operate on.
+#[derive(Debug, Clone)]
+pub struct UnGraph<L> {
+    labels: Vec<L>,
+    indices: FxHashMap<L, usize>,
+    adjacency: Vec<Vec<usize>>,
+    // the id of the edge behind each entry in `adjacency`
+    incident: Vec<Vec<usize>>,
+    edge_count: usize,
+}
+
+impl<L> Default for UnGraph<L> {
+    fn default() -> Self {
+        Self {
+            labels: Vec::default(),
+            indices: FxHashMap::default(),
+            adjacency: Vec::default(),
+            incident: Vec::default(),
+            edge_count: 0,
+        }
+    }
+}
+
+impl<L> UnGraph<L>
+where
+    L: Clone + Eq + Hash,
+{
+    /// Returns the index of the vertex with the given label, adding it if it
+    /// does not exist yet.
+    pub fn add_vertex(&mut self, label: L) -> usize {
+        if let Some(&idx) = self.indices.get(&label) {
+            return idx;
+        }
+
+        let idx = self.labels.len();
+        self.labels.push(label.clone());
+        self.indices.insert(label, idx);
+        self.adjacency.push(Vec::default());
+        self.incident.push(Vec::default());
+        idx
+    }
+
+    /// Adds an edge between the vertices with the given labels, adding the
+    /// vertices if necessary. Returns the indices of the two vertices.
+    pub fn add_edge(&mut self, a: L, b: L) -> (usize, usize) {
+        let u = self.add_vertex(a);
+        let v = self.add_vertex(b);
+        self.add_edge_by_index(u, v);
+        (u, v)
+    }
+
+    pub fn index_of(&self, label: &L) -> Option<usize> {
+        self.indices.get(label).copied()
+    }
+}
+
+impl<L> UnGraph<L> {
+    /// Adds an edge between two vertices. Edges are given ids in the order
+    /// they're added, starting from 0.
+    pub fn add_edge_by_index(&mut self, u: usize, v: usize) {
+        self.adjacency[u].push(v);
+        self.adjacency[v].push(u);
+        self.incident[u].push(self.edge_count);
+        self.incident[v].push(self.edge_count);
+        self.edge_count += 1;
+    }
+
+    pub fn label(&self, idx: usize) -> &L {
+        &self.labels[idx]
+    }
+
+    pub fn neighbours(&self, idx: usize) -> &[usize] {
+        &self.adjacency[idx]
+    }
+
+    /// Returns the ids of the edges to each of [Self::neighbours], in the
+    /// same order. This tells parallel edges apart, and lets per-edge state
+    /// live in a `Vec` indexed by id rather than a map keyed by vertices.
+    pub fn incident_edges(&self, idx: usize) -> &[usize] {
+        &self.incident[idx]
+    }
+
+    pub fn degree(&self, idx: usize) -> usize {
+        self.adjacency[idx].len()
+    }
+
+    /// Returns the indices of all of the vertices.
+    pub fn vertices(&self) -> Range<usize> {
+        0..self.labels.len()
+    }
+
+    /// Returns the number of vertices.
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interning() {
+        let mut graph = UnGraph::default();
+        let (a, b) = graph.add_edge("abc", "def");
+        let (c, d) = graph.add_edge("def", "ghi");
+
+        assert_eq!((a, b, c, d), (0, 1, 1, 2));
+        assert_eq!(graph.len(), 3);
+        assert_eq!(graph.edge_count(), 2);
+        assert_eq!(graph.index_of(&"ghi"), Some(2));
+        assert_eq!(graph.index_of(&"xyz"), None);
+        assert_eq!(*graph.label(1), "def");
+        assert_eq!(graph.neighbours(1), &[0, 2]);
+        assert_eq!(graph.incident_edges(1), &[0, 1]);
+        assert_eq!(graph.incident_edges(2), &[1]);
+        assert_eq!(graph.degree(0), 1);
+    }
+
+    #[test]
+    fn distinct_labels_do_not_collide() {
+        // these would all collide (or overflow) if labels were parsed as
+        // radix-36 numbers
+        let mut graph = UnGraph::default();
+        graph.add_edge("a".to_string(), "0a".to_string());
+        graph.add_edge("00a".to_string(), "abcdefghij".to_string());
+        graph.add_edge("abcdefghij".to_string(), "abcdefghik".to_string());
+
+        assert_eq!(graph.len(), 5);
+        assert_eq!(graph.vertices(), 0..5);
+        assert_eq!(graph.index_of(&"abcdefghik".to_string()), Some(4));
+    }
+}
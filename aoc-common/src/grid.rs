@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::fmt::{self, Debug};
 use std::str::FromStr;
 use std::{
@@ -5,7 +6,46 @@ use std::{
     ops::{Index, IndexMut},
 };
 
-use crate::direction::Cardinal;
+use crate::{direction::Cardinal, error::Error};
+
+/// A transformation that maps a grid onto itself without changing its
+/// dimensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    /// Mirrored left to right.
+    Horizontal,
+    /// Mirrored top to bottom.
+    Vertical,
+    /// Rotated a half turn.
+    HalfTurn,
+}
+
+impl Symmetry {
+    pub fn all() -> [Self; 3] {
+        [Self::Horizontal, Self::Vertical, Self::HalfTurn]
+    }
+
+    /// Maps a coordinate in an `n` by `m` grid to where it ends up.
+    pub fn apply(&self, coord: Coordinate, n: usize, m: usize) -> Coordinate {
+        let row = n as isize - 1 - coord.row();
+        let col = m as isize - 1 - coord.col();
+        match self {
+            Self::Horizontal => Coordinate(coord.row(), col),
+            Self::Vertical => Coordinate(row, coord.col()),
+            Self::HalfTurn => Coordinate(row, col),
+        }
+    }
+
+    /// Maps a direction to the one it points in after the transformation.
+    pub fn apply_cardinal(&self, dir: &Cardinal) -> Cardinal {
+        match (self, dir) {
+            (Self::Horizontal, Cardinal::East | Cardinal::West)
+            | (Self::Vertical, Cardinal::North | Cardinal::South)
+            | (Self::HalfTurn, _) => dir.opposite(),
+            _ => *dir,
+        }
+    }
+}
 
 #[derive(PartialEq, Eq, Clone, Copy, Hash, Default)]
 pub struct Coordinate(pub isize, pub isize);
@@ -128,9 +168,84 @@ impl Coordinate {
         ret
     }
 
+    /// Returns the direction to head in to get from this coordinate to
+    /// `other`, if `other` is a different coordinate on the same row or
+    /// column.
+    pub fn direction_to(&self, other: &Self) -> Option<Cardinal> {
+        match (other.0.cmp(&self.0), other.1.cmp(&self.1)) {
+            (Ordering::Less, Ordering::Equal) => Some(Cardinal::North),
+            (Ordering::Greater, Ordering::Equal) => Some(Cardinal::South),
+            (Ordering::Equal, Ordering::Less) => Some(Cardinal::West),
+            (Ordering::Equal, Ordering::Greater) => Some(Cardinal::East),
+            _ => None,
+        }
+    }
+
     pub fn manhattan_distance(&self, other: &Self) -> usize {
         other.0.abs_diff(self.0) + other.1.abs_diff(self.1)
     }
+
+    /// Wraps this coordinate into an `n` by `m` grid, as if the grid were
+    /// tiled infinitely in every direction.
+    pub fn rem_euclid(&self, n: usize, m: usize) -> Self {
+        Self(self.0.rem_euclid(n as isize), self.1.rem_euclid(m as isize))
+    }
+}
+
+/// Why some text can't be read as a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeError {
+    /// There are no rows, or the first row is empty.
+    Empty,
+    /// The 0-based `row` has `len` characters instead of `expected`.
+    Ragged {
+        row: usize,
+        len: usize,
+        expected: usize,
+    },
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty grid"),
+            Self::Ragged { row, len, expected } => write!(
+                f,
+                "row {} has {} characters, expected {}",
+                row + 1,
+                len,
+                expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+/// Checks that `s` is a non-empty rectangle of characters, one row per line,
+/// returning its dimensions. [Grid]'s `FromStr` doesn't check this itself,
+/// and most of the methods on a grid assume it.
+pub fn check_shape(s: &str) -> Result<(usize, usize), ShapeError> {
+    let mut lines = s.lines();
+    let expected = lines.next().map(|l| l.chars().count()).unwrap_or_default();
+    if expected == 0 {
+        return Err(ShapeError::Empty);
+    }
+
+    let mut n = 1;
+    for line in lines {
+        let len = line.chars().count();
+        if len != expected {
+            return Err(ShapeError::Ragged {
+                row: n,
+                len,
+                expected,
+            });
+        }
+        n += 1;
+    }
+
+    Ok((n, expected))
 }
 
 #[derive(Debug, Clone)]
@@ -218,6 +333,80 @@ where
     }
 }
 
+impl<T> Grid<T> {
+    /// Parses a grid with one row per line, checking that it is a non-empty
+    /// rectangle and reporting where any invalid character is. Unlike
+    /// `from_str`, the error doesn't depend on `T`.
+    pub fn parse(s: &str) -> Result<Self, Error>
+    where
+        T: TryFrom<char>,
+    {
+        check_shape(s)?;
+        let grid = s
+            .lines()
+            .enumerate()
+            .map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(col, ch)| T::try_from(ch).map_err(|_| Error::Parse { row, col, ch }))
+                    .collect::<Result<Vec<T>, Error>>()
+            })
+            .collect::<Result<Vec<Vec<T>>, Error>>()?;
+
+        Ok(grid.into())
+    }
+
+    pub fn is_in_bounds(&self, coord: Coordinate) -> bool {
+        (0..self.n as isize).contains(&coord.0) && (0..self.m as isize).contains(&coord.1)
+    }
+
+    /// Like indexing, but returns an error rather than panicking if `coord`
+    /// is out of bounds.
+    pub fn try_get(&self, coord: Coordinate) -> Result<&T, Error> {
+        if self.is_in_bounds(coord) {
+            Ok(&self[coord])
+        } else {
+            Err(Error::OutOfBounds {
+                coord,
+                n: self.n,
+                m: self.m,
+            })
+        }
+    }
+
+    pub fn find_coordinate(&self, pred: impl Fn(&T) -> bool) -> Option<Coordinate> {
+        for i in 0..self.n {
+            for j in 0..self.m {
+                if pred(&self.grid[i][j]) {
+                    return Some(Coordinate(i as isize, j as isize));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the coordinates of every cell matching `pred`, in row-major
+    /// order.
+    pub fn find_all(&self, pred: impl Fn(&T) -> bool) -> Vec<Coordinate> {
+        self.grid
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, x)| pred(x))
+                    .map(move |(j, _)| (i, j).into())
+            })
+            .collect()
+    }
+
+    /// Counts the cells matching `pred`.
+    pub fn count(&self, pred: impl Fn(&T) -> bool) -> usize {
+        self.grid.iter().flatten().filter(|x| pred(x)).count()
+    }
+}
+
 impl<T> Grid<T>
 where
     T: Copy + PartialEq,
@@ -238,8 +427,10 @@ where
         }
     }
 
-    pub fn is_in_bounds(&self, coord: Coordinate) -> bool {
-        (0..self.n as isize).contains(&coord.0) && (0..self.m as isize).contains(&coord.1)
+    /// Like `get`, but treats the grid as infinitely tiled, so any coordinate
+    /// is valid.
+    pub fn get_wrapping(&self, coord: Coordinate) -> T {
+        self[coord.rem_euclid(self.n, self.m)]
     }
 
     pub fn is_on_edge(&self, coord: Coordinate) -> bool {
@@ -252,15 +443,250 @@ where
         }
     }
 
-    pub fn find_coordinate(&self, pred: impl Fn(&T) -> bool) -> Option<Coordinate> {
-        for i in 0..self.n {
-            for j in 0..self.m {
-                if pred(&self.grid[i][j]) {
-                    return Some(Coordinate(i as isize, j as isize));
+    /// Returns a new grid with rows and columns swapped.
+    pub fn transpose(&self) -> Self {
+        (0..self.m)
+            .map(|j| (0..self.n).map(|i| self.grid[i][j]).collect())
+            .collect::<Vec<Vec<T>>>()
+            .into()
+    }
+
+    /// Returns a new grid rotated a quarter turn clockwise.
+    pub fn rotate_cw(&self) -> Self {
+        (0..self.m)
+            .map(|j| (0..self.n).rev().map(|i| self.grid[i][j]).collect())
+            .collect::<Vec<Vec<T>>>()
+            .into()
+    }
+
+    /// Returns a new grid rotated a quarter turn counterclockwise.
+    pub fn rotate_ccw(&self) -> Self {
+        (0..self.m)
+            .rev()
+            .map(|j| (0..self.n).map(|i| self.grid[i][j]).collect())
+            .collect::<Vec<Vec<T>>>()
+            .into()
+    }
+
+    /// Returns the symmetries the grid has.
+    ///
+    /// `map` gives what a cell looks like after being transformed, for cells
+    /// that have an orientation of their own (e.g. a `/` mirrored left to
+    /// right becomes a `\`). Cells without one can just be copied.
+    pub fn symmetries(&self, map: impl Fn(Symmetry, &T) -> T) -> Vec<Symmetry> {
+        Symmetry::all()
+            .into_iter()
+            .filter(|sym| {
+                (0..self.n).all(|i| {
+                    (0..self.m).all(|j| {
+                        let coord = Coordinate(i as isize, j as isize);
+                        self[sym.apply(coord, self.n, self.m)] == map(*sym, &self[coord])
+                    })
+                })
+            })
+            .collect()
+    }
+
+    /// Returns a new grid mirrored left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        self.grid
+            .iter()
+            .map(|row| row.iter().rev().copied().collect())
+            .collect::<Vec<Vec<T>>>()
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rem_euclid() {
+        assert_eq!(Coordinate(3, 4).rem_euclid(5, 7), Coordinate(3, 4));
+        assert_eq!(Coordinate(5, 7).rem_euclid(5, 7), Coordinate(0, 0));
+        assert_eq!(Coordinate(-1, -8).rem_euclid(5, 7), Coordinate(4, 6));
+        assert_eq!(Coordinate(12, -14).rem_euclid(5, 7), Coordinate(2, 0));
+    }
+
+    #[test]
+    fn direction_to() {
+        let origin = Coordinate(2, 2);
+        for dir in Cardinal::all() {
+            assert_eq!(origin.direction_to(&origin.steps(&dir, 3)), Some(dir));
+        }
+        assert_eq!(origin.direction_to(&origin), None);
+        assert_eq!(origin.direction_to(&origin.northeast()), None);
+    }
+
+    #[test]
+    fn shapes() {
+        assert_eq!(check_shape("#.#\n...\n"), Ok((2, 3)));
+        assert_eq!(check_shape(""), Err(ShapeError::Empty));
+        assert_eq!(check_shape("\n#"), Err(ShapeError::Empty));
+        assert_eq!(
+            check_shape("#.#\n..\n"),
+            Err(ShapeError::Ragged {
+                row: 1,
+                len: 2,
+                expected: 3
+            })
+        );
+        assert_eq!(
+            check_shape("ab\ncd\né").unwrap_err().to_string(),
+            "row 3 has 1 characters, expected 2"
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let grid: Grid<char> = Grid::parse("#.#\n...\n").unwrap();
+        assert_eq!((grid.n, grid.m), (2, 3));
+        assert_eq!(
+            Grid::<char>::parse("#.#\n..").unwrap_err(),
+            Error::Shape(ShapeError::Ragged {
+                row: 1,
+                len: 2,
+                expected: 3
+            })
+        );
+
+        #[derive(Debug, Clone, Copy)]
+        struct Wall(bool);
+
+        impl TryFrom<char> for Wall {
+            type Error = ();
+
+            fn try_from(value: char) -> Result<Self, Self::Error> {
+                match value {
+                    '#' => Ok(Self(true)),
+                    '.' => Ok(Self(false)),
+                    _ => Err(()),
                 }
             }
         }
 
-        None
+        let grid: Grid<Wall> = Grid::parse("#.\n.#").unwrap();
+        assert!(grid.grid[0][0].0 && !grid.grid[0][1].0);
+
+        let err = Grid::<Wall>::parse("#.\n.x").unwrap_err();
+        assert_eq!(
+            err,
+            Error::Parse {
+                row: 1,
+                col: 1,
+                ch: 'x'
+            }
+        );
+        assert_eq!(err.to_string(), "invalid character 'x' at row 2, column 2");
+    }
+
+    #[test]
+    fn try_get() {
+        let grid: Grid<usize> = vec![vec![1, 2, 3], vec![4, 5, 6]].into();
+        assert_eq!(grid.try_get(Coordinate(1, 2)), Ok(&6));
+        for coord in [Coordinate(2, 0), Coordinate(0, 3), Coordinate(-1, 0)] {
+            assert_eq!(
+                grid.try_get(coord),
+                Err(Error::OutOfBounds { coord, n: 2, m: 3 })
+            );
+        }
+        assert_eq!(
+            grid.try_get(Coordinate(0, -1)).unwrap_err().to_string(),
+            "(0, -1) is outside the 2x3 grid"
+        );
+    }
+
+    #[test]
+    fn get_wrapping() {
+        let grid: Grid<usize> = vec![vec![1, 2, 3], vec![4, 5, 6]].into();
+        assert_eq!(grid.get_wrapping(Coordinate(1, 1)), 5);
+        assert_eq!(grid.get_wrapping(Coordinate(-1, -1)), 6);
+        assert_eq!(grid.get_wrapping(Coordinate(2, 3)), 1);
+        assert_eq!(grid.get_wrapping(Coordinate(-4, 7)), 2);
+    }
+
+    #[test]
+    fn finding() {
+        let grid: Grid<usize> = vec![vec![1, 2, 3], vec![4, 5, 6]].into();
+        let even = |x: &usize| x.is_multiple_of(2);
+        assert_eq!(grid.find_coordinate(even), Some(Coordinate(0, 1)));
+        assert_eq!(
+            grid.find_all(even),
+            vec![Coordinate(0, 1), Coordinate(1, 0), Coordinate(1, 2)]
+        );
+        assert_eq!(grid.count(even), 3);
+        assert!(grid.find_all(|x| *x > 6).is_empty());
+        assert_eq!(grid.count(|_| true), grid.n * grid.m);
+    }
+
+    #[test]
+    fn transformations() {
+        let grid: Grid<usize> = vec![vec![1, 2, 3], vec![4, 5, 6]].into();
+
+        let transposed = grid.transpose();
+        assert_eq!(transposed.grid, vec![vec![1, 4], vec![2, 5], vec![3, 6]]);
+        assert_eq!((transposed.n, transposed.m), (3, 2));
+
+        let cw = grid.rotate_cw();
+        assert_eq!(cw.grid, vec![vec![4, 1], vec![5, 2], vec![6, 3]]);
+        assert_eq!((cw.n, cw.m), (3, 2));
+
+        let ccw = grid.rotate_ccw();
+        assert_eq!(ccw.grid, vec![vec![3, 6], vec![2, 5], vec![1, 4]]);
+
+        let flipped = grid.flip_horizontal();
+        assert_eq!(flipped.grid, vec![vec![3, 2, 1], vec![6, 5, 4]]);
+
+        assert_eq!(cw.rotate_ccw().grid, grid.grid);
+        assert_eq!(
+            grid.rotate_cw().rotate_cw().rotate_cw().grid,
+            grid.rotate_ccw().grid
+        );
+        assert_eq!(grid.transpose().transpose().grid, grid.grid);
+        // a transpose is a clockwise rotation followed by a mirror
+        assert_eq!(grid.rotate_cw().flip_horizontal().grid, transposed.grid);
+    }
+
+    #[test]
+    fn symmetries() {
+        let copy = |_, c: &char| *c;
+
+        let grid: Grid<char> = Grid::from_str("#.#\n...\n#.#").unwrap();
+        assert_eq!(grid.symmetries(copy), Symmetry::all().to_vec());
+
+        let grid: Grid<char> = Grid::from_str("#..\n...\n..#").unwrap();
+        assert_eq!(grid.symmetries(copy), vec![Symmetry::HalfTurn]);
+
+        let grid: Grid<char> = Grid::from_str("#.#\n...").unwrap();
+        assert_eq!(grid.symmetries(copy), vec![Symmetry::Horizontal]);
+
+        // a mirror is only symmetric with its reflection
+        let grid: Grid<char> = Grid::from_str("/\\\n\\/").unwrap();
+        assert_eq!(grid.symmetries(copy), vec![Symmetry::HalfTurn]);
+        let reflect = |sym, c: &char| match (sym, c) {
+            (Symmetry::HalfTurn, _) => *c,
+            (_, '/') => '\\',
+            (_, '\\') => '/',
+            _ => *c,
+        };
+        assert_eq!(grid.symmetries(reflect), Symmetry::all().to_vec());
+
+        let coord = Coordinate(0, 1);
+        assert_eq!(Symmetry::Horizontal.apply(coord, 2, 3), Coordinate(0, 1));
+        assert_eq!(Symmetry::Vertical.apply(coord, 2, 3), Coordinate(1, 1));
+        assert_eq!(Symmetry::HalfTurn.apply(coord, 2, 3), Coordinate(1, 1));
+        assert_eq!(
+            Symmetry::Horizontal.apply_cardinal(&Cardinal::East),
+            Cardinal::West
+        );
+        assert_eq!(
+            Symmetry::Horizontal.apply_cardinal(&Cardinal::North),
+            Cardinal::North
+        );
+        assert_eq!(
+            Symmetry::HalfTurn.apply_cardinal(&Cardinal::North),
+            Cardinal::South
+        );
     }
 }
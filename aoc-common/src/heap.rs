@@ -0,0 +1,74 @@
+//! Priority queue helpers.
+//!
+//! [std::collections::BinaryHeap] is a max-heap, so shortest path searches
+//! have to flip the ordering of whatever they push. Getting that flip wrong
+//! (or comparing on more than the distance) silently turns Dijkstra into
+//! something slower or incorrect, so it lives here instead of being
+//! reimplemented per day.
+use std::cmp::Ordering;
+
+/// A value paired with a score, ordered so that the *lowest* score is the
+/// greatest. Pushing these into a [BinaryHeap](std::collections::BinaryHeap)
+/// pops them in ascending order of score.
+///
+/// Only the score takes part in comparisons, so `T` doesn't need to be `Ord`.
+///
+/// ```
+/// use std::collections::BinaryHeap;
+///
+/// use aoc_common::heap::MinScored;
+///
+/// let mut heap = BinaryHeap::new();
+/// heap.push(MinScored(3, "c"));
+/// heap.push(MinScored(1, "a"));
+/// heap.push(MinScored(2, "b"));
+/// assert_eq!(heap.pop(), Some(MinScored(1, "a")));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinScored<K, T>(pub K, pub T);
+
+impl<K: Ord, T> PartialEq for MinScored<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K: Ord, T> Eq for MinScored<K, T> {}
+
+impl<K: Ord, T> PartialOrd for MinScored<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, T> Ord for MinScored<K, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.cmp(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BinaryHeap;
+
+    use super::*;
+
+    #[test]
+    fn pops_lowest_score_first() {
+        let mut heap: BinaryHeap<_> = [5, 1, 4, 1, 3]
+            .into_iter()
+            .enumerate()
+            .map(|(i, score)| MinScored(score, i))
+            .collect();
+
+        let mut scores = Vec::new();
+        while let Some(MinScored(score, _)) = heap.pop() {
+            scores.push(score);
+        }
+        assert_eq!(scores, vec![1, 1, 3, 4, 5]);
+
+        // values don't take part in comparisons
+        assert_eq!(MinScored(1, 'a'), MinScored(1, 'b'));
+        assert!(MinScored(1, 'a') > MinScored(2, 'a'));
+    }
+}
@@ -1,15 +1,32 @@
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd)]
-pub struct Interval {
-    start: isize,
-    end: isize,
+//! Half-open intervals of integers, and sorted lists of disjoint ones.
+//!
+//! Both are generic over the primitive integers, so e.g. day 19's ratings
+//! can use `u16` while day 5's seed ranges use `usize`.
+use std::ops::Range;
+
+use num::{NumCast, PrimInt};
+
+/// The half-open interval `start..end`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Interval<T> {
+    start: T,
+    end: T,
 }
 
-impl Interval {
-    pub fn new(start: isize, end: isize) -> Self {
+impl<T: PrimInt> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
         Self { start, end }
     }
 
-    pub fn split(&self, x: isize) -> Option<(Interval, Interval)> {
+    pub fn start(&self) -> T {
+        self.start
+    }
+
+    pub fn end(&self) -> T {
+        self.end
+    }
+
+    pub fn split(&self, x: T) -> Option<(Self, Self)> {
         if self.contains(x) {
             Some((Self::new(self.start, x), Self::new(x, self.end)))
         } else {
@@ -17,57 +34,93 @@ impl Interval {
         }
     }
 
-    pub fn less_than(&self, x: isize) -> bool {
+    pub fn less_than(&self, x: T) -> bool {
         self.end <= x
     }
 
-    pub fn greater_than(&self, x: isize) -> bool {
+    pub fn greater_than(&self, x: T) -> bool {
         self.start > x
     }
 
-    pub fn len(&self) -> usize {
-        (self.end - self.start) as usize
+    /// The number of values in the interval, which is 0 if `end <= start`.
+    pub fn len(&self) -> T {
+        if self.is_empty() {
+            T::zero()
+        } else {
+            self.end - self.start
+        }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.len() == 0
+        self.end <= self.start
     }
 
-    pub fn contains(&self, x: isize) -> bool {
+    pub fn contains(&self, x: T) -> bool {
         (self.start..self.end).contains(&x)
     }
 
-    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
         if self.end <= other.start || other.end <= self.start {
             None
         } else {
-            Some(Interval::new(
+            Some(Self::new(
                 self.start.max(other.start),
                 self.end.min(other.end),
             ))
         }
     }
+
+    /// Converts the bounds to another integer type, or returns `None` if
+    /// either doesn't fit.
+    pub fn cast<U: PrimInt>(&self) -> Option<Interval<U>> {
+        Some(Interval::new(
+            <U as NumCast>::from(self.start)?,
+            <U as NumCast>::from(self.end)?,
+        ))
+    }
+}
+
+impl<T: PrimInt> From<Range<T>> for Interval<T> {
+    fn from(value: Range<T>) -> Self {
+        Self::new(value.start, value.end)
+    }
+}
+
+impl<T: PrimInt> From<Interval<T>> for Range<T> {
+    fn from(value: Interval<T>) -> Self {
+        value.start..value.end
+    }
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
-pub struct Intervals {
-    intervals: Vec<Interval>,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Intervals<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T> Default for Intervals<T> {
+    fn default() -> Self {
+        Self {
+            intervals: Vec::default(),
+        }
+    }
 }
 
-impl Intervals {
-    pub fn new(intervals: Vec<Interval>) -> Self {
+impl<T: PrimInt> Intervals<T> {
+    pub fn new(intervals: Vec<Interval<T>>) -> Self {
         Self { intervals }
     }
 
-    pub fn len(&self) -> usize {
-        self.intervals.iter().map(|x| x.len()).sum()
+    pub fn len(&self) -> T {
+        self.intervals
+            .iter()
+            .fold(T::zero(), |acc, x| acc + x.len())
     }
 
     pub fn is_empty(&self) -> bool {
-        self.len() == 0
+        self.intervals.iter().all(|x| x.is_empty())
     }
 
-    pub fn split(&self, x: isize) -> (Self, Self) {
+    pub fn split(&self, x: T) -> (Self, Self) {
         let index = self.intervals.partition_point(|i| i.end <= x);
 
         if index == self.intervals.len() {
@@ -94,7 +147,7 @@ impl Intervals {
         }
     }
 
-    pub fn add(&mut self, interval: Interval) {
+    pub fn add(&mut self, interval: Interval<T>) {
         if self.intervals.is_empty()
             || self.intervals[self.intervals.len() - 1].end < interval.start
         {
@@ -131,6 +184,8 @@ impl Intervals {
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -293,6 +348,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unsigned_and_conversions() {
+        let interval = Interval::new(1_u16, 4001);
+        assert_eq!(interval.len(), 4000);
+        assert_eq!(interval.split(2000).map(|(a, _)| a.len()), Some(1999));
+        assert!(Interval::new(5_u16, 3).is_empty());
+        assert_eq!(Interval::new(5_u16, 3).len(), 0);
+
+        assert_eq!(Interval::from(3_usize..7), Interval::new(3, 7));
+        assert_eq!(std::ops::Range::from(Interval::new(3_usize, 7)), 3..7);
+        assert_eq!(interval.cast::<i64>(), Some(Interval::new(1_i64, 4001)));
+        assert_eq!(Interval::new(-1_isize, 5).cast::<usize>(), None);
+        assert_eq!(Interval::new(0_u64, 1 << 20).cast::<u16>(), None);
+
+        let mut intervals = Intervals::new(vec![Interval::new(0_u8, 10)]);
+        intervals.add(Interval::new(20, 30));
+        assert_eq!(intervals.len(), 20);
+        let (below, above) = intervals.split(25);
+        assert_eq!((below.len(), above.len()), (15, 5));
+        assert!(Intervals::<u8>::default().is_empty());
+    }
+
     #[test]
     fn interval_intersection_test() {
         let interval = Interval::new(0, 10);
@@ -315,4 +392,73 @@ mod tests {
             Some(Interval::new(5, 7))
         );
     }
+
+    /// Small, possibly empty intervals, so that every value can be checked.
+    fn interval() -> impl Strategy<Value = Interval<i32>> {
+        (-50..50, -50..50).prop_map(|(a, b)| Interval::new(a, b))
+    }
+
+    proptest! {
+        #[test]
+        fn split_partitions(interval in interval(), x in -60..60) {
+            match interval.split(x) {
+                Some((below, above)) => {
+                    prop_assert!(interval.contains(x));
+                    prop_assert_eq!(below.end(), x);
+                    prop_assert_eq!(above.start(), x);
+                    prop_assert_eq!(below.len() + above.len(), interval.len());
+                    for v in -60..60 {
+                        // every value is in exactly one half, and only if
+                        // it's in the original
+                        prop_assert_eq!(
+                            below.contains(v) as u8 + above.contains(v) as u8,
+                            interval.contains(v) as u8
+                        );
+                        prop_assert!(!below.contains(v) || v < x);
+                        prop_assert!(!above.contains(v) || v >= x);
+                    }
+                }
+                None => prop_assert!(!interval.contains(x)),
+            }
+        }
+
+        #[test]
+        fn comparisons(interval in interval(), x in -60..60) {
+            prop_assume!(!interval.is_empty());
+            let values: Range<i32> = interval.into();
+            prop_assert_eq!(interval.less_than(x), values.clone().all(|v| v < x));
+            prop_assert_eq!(interval.greater_than(x), values.clone().all(|v| v > x));
+            prop_assert!(!(interval.less_than(x) && interval.greater_than(x)));
+            // the off-by-one in day 19: being above x is the same as nothing
+            // being left below x + 1
+            let nothing_below = match interval.split(x + 1) {
+                Some((below, _)) => below.is_empty(),
+                None => !interval.less_than(x + 1),
+            };
+            prop_assert_eq!(interval.greater_than(x), nothing_below);
+        }
+
+        #[test]
+        fn intervals_split_partitions(
+            added in prop::collection::vec(interval(), 0..6),
+            x in -60..60,
+        ) {
+            let mut intervals = Intervals::default();
+            for i in added.iter().filter(|i| !i.is_empty()) {
+                intervals.add(*i);
+            }
+
+            let (below, above) = intervals.split(x);
+            prop_assert_eq!(below.len() + above.len(), intervals.len());
+            let contains = |is: &Intervals<i32>, v| is.intervals.iter().any(|i| i.contains(v));
+            for v in -60..60 {
+                prop_assert_eq!(
+                    contains(&below, v) as u8 + contains(&above, v) as u8,
+                    contains(&intervals, v) as u8
+                );
+                prop_assert!(!contains(&below, v) || v < x);
+                prop_assert!(!contains(&above, v) || v >= x);
+            }
+        }
+    }
 }
@@ -1,5 +1,12 @@
 pub mod algebra;
 pub mod direction;
+pub mod error;
 pub mod geometry;
+pub mod graph;
 pub mod grid;
+pub mod heap;
 pub mod interval;
+pub mod math;
+pub mod union_find;
+#[cfg(feature = "visualize")]
+pub mod visualize;
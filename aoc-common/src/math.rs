@@ -0,0 +1,240 @@
+//! Number theory helpers: gcd, lcm, Bézout coefficients and the Chinese
+//! remainder theorem.
+//!
+//! These are generic over the primitive integers, so the same code works
+//! for `u64`, `i64`, `u128` and friends. Results are always non-negative.
+use num::{PrimInt, Signed};
+
+/// Returns the greatest common divisor of `a` and `b`. `gcd(0, 0)` is 0.
+pub fn gcd<T: PrimInt>(a: T, b: T) -> T {
+    let (mut a, mut b) = (abs(a), abs(b));
+    while !b.is_zero() {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+/// Returns the least common multiple of `a` and `b`, which is 0 if either
+/// is. Panics on overflow in debug builds, see [checked_lcm].
+pub fn lcm<T: PrimInt>(a: T, b: T) -> T {
+    if a.is_zero() || b.is_zero() {
+        return T::zero();
+    }
+
+    abs(a) / gcd(a, b) * abs(b)
+}
+
+/// Like [lcm], but returns `None` instead of overflowing.
+pub fn checked_lcm<T: PrimInt>(a: T, b: T) -> Option<T> {
+    if a.is_zero() || b.is_zero() {
+        return Some(T::zero());
+    }
+
+    (abs(a) / gcd(a, b)).checked_mul(&abs(b))
+}
+
+/// Returns `(g, x, y)` where `g` is the gcd of `a` and `b`, and
+/// `a * x + b * y == g`.
+pub fn egcd<T: PrimInt + Signed>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::one(), T::zero());
+    let (mut old_y, mut y) = (T::zero(), T::one());
+
+    while !r.is_zero() {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r < T::zero() {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Solves a system of congruences `x = residue (mod modulus)`, given as
+/// `(residue, modulus)` pairs. The moduli don't have to be coprime.
+///
+/// Returns `(x, m)` where `m` is the lcm of the moduli and `0 <= x < m` is
+/// the unique solution modulo `m`. Returns `None` if the congruences
+/// contradict each other, a modulus isn't positive, or `m` doesn't fit in
+/// `T`.
+pub fn crt<T: PrimInt>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    let mut x = T::zero();
+    let mut m = T::one();
+
+    for &(residue, modulus) in congruences {
+        if modulus <= T::zero() {
+            return None;
+        }
+        let residue = rem_euclid(residue, modulus);
+
+        // x + m * k = residue (mod modulus), so m * k = d (mod modulus)
+        let g = gcd(m, modulus);
+        let d = sub_mod(residue, x % modulus, modulus);
+        if !(d % g).is_zero() {
+            return None;
+        }
+
+        let reduced = modulus / g;
+        let k = if reduced.is_one() {
+            T::zero()
+        } else {
+            let inv = inverse_mod((m / g) % reduced, reduced)?;
+            mul_mod((d / g) % reduced, inv, reduced)
+        };
+
+        let next = m.checked_mul(&reduced)?;
+        // k < reduced, so m * k < next and this can't overflow
+        x = x + m * k;
+        m = next;
+    }
+
+    Some((x, m))
+}
+
+fn abs<T: PrimInt>(a: T) -> T {
+    if a < T::zero() {
+        T::zero() - a
+    } else {
+        a
+    }
+}
+
+fn rem_euclid<T: PrimInt>(a: T, m: T) -> T {
+    let r = a % m;
+    if r < T::zero() {
+        r + m
+    } else {
+        r
+    }
+}
+
+/// `a + b (mod m)` for `a, b < m`, without overflowing.
+fn add_mod<T: PrimInt>(a: T, b: T, m: T) -> T {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+/// `a - b (mod m)` for `a, b < m`, without going negative.
+fn sub_mod<T: PrimInt>(a: T, b: T, m: T) -> T {
+    if a >= b {
+        a - b
+    } else {
+        m - (b - a)
+    }
+}
+
+/// `a * b (mod m)` for `a, b < m`. Falls back to doubling and adding when
+/// the product doesn't fit in `T`.
+fn mul_mod<T: PrimInt>(a: T, b: T, m: T) -> T {
+    if let Some(product) = a.checked_mul(&b) {
+        return product % m;
+    }
+
+    let (mut a, mut b) = (a, b);
+    let mut ret = T::zero();
+    while !b.is_zero() {
+        if !(b & T::one()).is_zero() {
+            ret = add_mod(ret, a, m);
+        }
+        a = add_mod(a, a, m);
+        b = b >> 1;
+    }
+
+    ret
+}
+
+/// The inverse of `a` modulo `m`, for `a < m`. This is the extended
+/// Euclidean algorithm with the coefficient kept in `0..m`, so it also works
+/// for unsigned types.
+fn inverse_mod<T: PrimInt>(a: T, m: T) -> Option<T> {
+    let (mut old_r, mut r) = (a, m);
+    let (mut old_s, mut s) = (T::one() % m, T::zero());
+
+    while !r.is_zero() {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, sub_mod(old_s, mul_mod(q % m, s, m), m));
+    }
+
+    old_r.is_one().then_some(old_s)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn gcd_lcm() {
+        assert_eq!(gcd(48_u64, 18), 6);
+        assert_eq!(gcd(-48_i64, 18), 6);
+        assert_eq!(gcd(0_u64, 7), 7);
+        assert_eq!(gcd(0_u64, 0), 0);
+        assert_eq!(lcm(21_u64, 6), 42);
+        assert_eq!(lcm(-4_i64, 6), 12);
+        assert_eq!(lcm(0_u128, 6), 0);
+        assert_eq!(checked_lcm(u64::MAX, u64::MAX - 1), None);
+        assert_eq!(checked_lcm(1_u64 << 40, 1 << 50), Some(1 << 50));
+    }
+
+    #[test]
+    fn egcd_coefficients() {
+        assert_eq!(egcd(240_i64, 46), (2, -9, 47));
+        assert_eq!(egcd(-240_i64, 46), (2, 9, 47));
+        assert_eq!(egcd(7_i64, 0), (7, 1, 0));
+    }
+
+    #[test]
+    fn crt_systems() {
+        assert_eq!(crt(&[(2_u64, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // not coprime, but consistent
+        assert_eq!(crt(&[(3_u64, 4), (5, 6)]), Some((11, 12)));
+        assert_eq!(crt(&[(0_u64, 4), (1, 6)]), None);
+        assert_eq!(crt(&[(-1_i64, 3), (-1, 5)]), Some((14, 15)));
+        assert_eq!(crt::<u64>(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(1_i64, 0)]), None);
+        assert_eq!(crt(&[(1_u64, 1 << 40), (0, (1 << 40) + 1)]), None);
+
+        // the product of the moduli only fits in a u128, and so does every
+        // intermediate product
+        let p = 18_446_744_073_709_551_557_u128;
+        let q = 18_446_744_073_709_551_533_u128;
+        let x = p * q - 12345;
+        assert_eq!(crt(&[(x % p, p), (x % q, q)]), Some((x, p * q)));
+    }
+
+    proptest! {
+        #[test]
+        fn gcd_divides(a in 0..u32::MAX as u64, b in 0..u32::MAX as u64) {
+            let g = gcd(a, b);
+            if g != 0 {
+                prop_assert_eq!(a % g, 0);
+                prop_assert_eq!(b % g, 0);
+            }
+            prop_assert_eq!(g * lcm(a, b), a * b);
+        }
+
+        #[test]
+        fn bezout(a in -1_000_000_000_i64..1_000_000_000, b in -1_000_000_000_i64..1_000_000_000) {
+            let (g, x, y) = egcd(a, b);
+            prop_assert_eq!(g, gcd(a, b));
+            prop_assert_eq!(a * x + b * y, g);
+        }
+
+        #[test]
+        fn crt_recovers(x in 0..u64::MAX, moduli in prop::collection::vec(1..10_000_u64, 1..5)) {
+            let congruences: Vec<_> = moduli.iter().map(|&m| (x % m, m)).collect();
+            let m = moduli.iter().fold(1_u128, |acc, &m| lcm(acc, m as u128));
+            prop_assert_eq!(crt(&congruences), u64::try_from(m).ok().map(|m| (x % m, m)));
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Disjoint sets of the integers `0..n`.
+//!
+//! ```
+//! use aoc_common::union_find::UnionFind;
+//!
+//! let mut uf = UnionFind::new(5);
+//! uf.union(0, 3);
+//! uf.union(4, 3);
+//! assert!(uf.same_set(0, 4));
+//! assert_eq!(uf.set_size(4), 3);
+//! assert_eq!(uf.components(), vec![vec![0, 3, 4], vec![1], vec![2]]);
+//! ```
+
+/// A union-find over `0..n`, with path compression and union by rank.
+#[derive(Debug, Clone, Default)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    /// The size of each set, only kept up to date for representatives.
+    size: Vec<usize>,
+    sets: usize,
+}
+
+impl UnionFind {
+    /// Puts each of `0..n` in a set of its own.
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+            size: vec![1; n],
+            sets: n,
+        }
+    }
+
+    /// Returns the representative of the set containing `x`.
+    pub fn find(&mut self, mut x: usize) -> usize {
+        // path halving: point every other node on the way up at its
+        // grandparent
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+
+        x
+    }
+
+    /// Merges the sets containing `a` and `b`, returning false if they were
+    /// already the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+
+        if self.rank[a] < self.rank[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        if self.rank[a] == self.rank[b] {
+            self.rank[a] += 1;
+        }
+        self.sets -= 1;
+
+        true
+    }
+
+    /// Whether `a` and `b` are in the same set.
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The number of elements in the set containing `x`.
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// The number of disjoint sets.
+    pub fn sets(&self) -> usize {
+        self.sets
+    }
+
+    /// The number of elements across all the sets.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Returns the elements of each set, sorted, with the sets ordered by
+    /// their smallest element.
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut index = vec![usize::MAX; self.len()];
+        let mut components: Vec<Vec<usize>> = Vec::with_capacity(self.sets);
+        for x in 0..self.len() {
+            let root = self.find(x);
+            if index[root] == usize::MAX {
+                index[root] = components.len();
+                components.push(Vec::with_capacity(self.size[root]));
+            }
+            components[index[root]].push(x);
+        }
+
+        components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn union_and_find() {
+        let mut uf = UnionFind::new(5);
+        assert_eq!(uf.sets(), 5);
+        assert!(uf.union(0, 1));
+        assert!(uf.union(3, 4));
+        assert!(!uf.union(1, 0));
+        assert!(uf.union(1, 4));
+        assert_eq!(uf.sets(), 2);
+        assert_eq!(uf.find(0), uf.find(3));
+        assert_ne!(uf.find(0), uf.find(2));
+    }
+
+    #[test]
+    fn components_and_sizes() {
+        let mut uf = UnionFind::new(7);
+        assert_eq!(uf.len(), 7);
+        assert_eq!(uf.components(), (0..7).map(|x| vec![x]).collect::<Vec<_>>());
+
+        uf.union(6, 2);
+        uf.union(5, 1);
+        uf.union(2, 1);
+        assert!(uf.same_set(6, 5));
+        assert!(!uf.same_set(0, 6));
+        assert_eq!(uf.set_size(5), 4);
+        assert_eq!(uf.set_size(3), 1);
+        assert_eq!(
+            uf.components(),
+            vec![vec![0], vec![1, 2, 5, 6], vec![3], vec![4]]
+        );
+
+        let mut empty = UnionFind::new(0);
+        assert!(empty.is_empty());
+        assert_eq!(empty.sets(), 0);
+        assert!(empty.components().is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn matches_relabelling(n in 1_usize..40, unions in prop::collection::vec((0_usize..40, 0_usize..40), 0..60)) {
+            // the naive version: a label per element, relabelling a whole
+            // set on every union
+            let mut labels: Vec<usize> = (0..n).collect();
+            let mut uf = UnionFind::new(n);
+            for (a, b) in unions {
+                let (a, b) = (a % n, b % n);
+                let (la, lb) = (labels[a], labels[b]);
+                prop_assert_eq!(uf.union(a, b), la != lb);
+                labels.iter_mut().filter(|l| **l == lb).for_each(|l| *l = la);
+            }
+
+            let mut expected: Vec<Vec<usize>> = Vec::default();
+            for label in 0..n {
+                let members: Vec<usize> = (0..n).filter(|&x| labels[x] == label).collect();
+                if !members.is_empty() {
+                    expected.push(members);
+                }
+            }
+            expected.sort();
+
+            prop_assert_eq!(uf.sets(), expected.len());
+            for x in 0..n {
+                let size = labels.iter().filter(|l| **l == labels[x]).count();
+                prop_assert_eq!(uf.set_size(x), size);
+            }
+            prop_assert_eq!(uf.components(), expected);
+        }
+    }
+}
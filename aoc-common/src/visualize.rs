@@ -0,0 +1,237 @@
+//! Frame-based rendering of grid states, for watching an algorithm run.
+//!
+//! A frame is just a `Grid<char>`; problems build one per step (see
+//! [frame]) and hand it to a [Renderer], which can animate it in the
+//! terminal, dump it to a text file, or encode it as a GIF.
+use std::{
+    io::{self, Write},
+    thread,
+    time::Duration,
+};
+
+use gif::{Encoder, Frame, Repeat};
+
+use crate::grid::Grid;
+
+pub trait Renderer {
+    /// Renders a single frame. Every frame passed to a renderer is expected
+    /// to have the same dimensions.
+    fn render(&mut self, frame: &Grid<char>) -> io::Result<()>;
+}
+
+/// Builds a frame from a grid, using `glyph` to pick the character for each
+/// cell.
+pub fn frame<T>(grid: &Grid<T>, glyph: impl Fn(&T) -> char) -> Grid<char> {
+    grid.grid
+        .iter()
+        .map(|row| row.iter().map(&glyph).collect())
+        .collect::<Vec<Vec<char>>>()
+        .into()
+}
+
+/// Writes every frame one after another, each preceded by its index.
+#[derive(Debug)]
+pub struct TextRenderer<W: Write> {
+    writer: W,
+    frames: usize,
+}
+
+impl<W: Write> TextRenderer<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, frames: 0 }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Renderer for TextRenderer<W> {
+    fn render(&mut self, frame: &Grid<char>) -> io::Result<()> {
+        writeln!(self.writer, "frame {}", self.frames)?;
+        writeln!(self.writer, "{}", frame)?;
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+/// Redraws each frame in place, pausing for `delay` between frames.
+#[derive(Debug)]
+pub struct TerminalRenderer<W: Write> {
+    writer: W,
+    delay: Duration,
+}
+
+impl<W: Write> TerminalRenderer<W> {
+    pub fn new(writer: W, delay: Duration) -> Self {
+        Self { writer, delay }
+    }
+}
+
+impl<W: Write> Renderer for TerminalRenderer<W> {
+    fn render(&mut self, frame: &Grid<char>) -> io::Result<()> {
+        // clear the screen and move the cursor to the top left
+        write!(self.writer, "\x1b[2J\x1b[H{}", frame)?;
+        self.writer.flush()?;
+        thread::sleep(self.delay);
+        Ok(())
+    }
+}
+
+/// Encodes the frames as a looping GIF, drawing each cell as a `scale` by
+/// `scale` square.
+///
+/// Characters are coloured from the palette, with anything not in it drawn
+/// in magenta. The GIF is only complete once [GifRenderer::into_inner] is
+/// called (or the renderer is dropped).
+pub struct GifRenderer<W: Write> {
+    writer: Option<W>,
+    encoder: Option<Encoder<W>>,
+    palette: Vec<(char, [u8; 3])>,
+    scale: usize,
+    // in hundredths of a second
+    delay: u16,
+}
+
+/// The colours a [GifRenderer] uses unless given a palette.
+pub const DEFAULT_PALETTE: [(char, [u8; 3]); 12] = [
+    ('.', [0x10, 0x10, 0x18]),
+    ('#', [0x70, 0x70, 0x78]),
+    ('O', [0xf0, 0xf0, 0xf0]),
+    ('@', [0xf0, 0xa0, 0x20]),
+    ('|', [0x40, 0x80, 0xc0]),
+    ('-', [0x40, 0x80, 0xc0]),
+    ('/', [0x40, 0x80, 0xc0]),
+    ('\\', [0x40, 0x80, 0xc0]),
+    ('^', [0xf0, 0xa0, 0x20]),
+    ('>', [0xf0, 0xa0, 0x20]),
+    ('v', [0xf0, 0xa0, 0x20]),
+    ('<', [0xf0, 0xa0, 0x20]),
+];
+
+const FALLBACK: [u8; 3] = [0xff, 0x00, 0xff];
+
+impl<W: Write> GifRenderer<W> {
+    pub fn new(writer: W, scale: usize, delay: Duration) -> Self {
+        Self {
+            writer: Some(writer),
+            encoder: None,
+            palette: DEFAULT_PALETTE.to_vec(),
+            scale: scale.max(1),
+            delay: (delay.as_millis() / 10).min(u16::MAX as u128) as u16,
+        }
+    }
+
+    /// Replaces the palette. GIFs are limited to 256 colours, one of which
+    /// is used for characters missing from the palette.
+    pub fn with_palette(mut self, palette: &[(char, [u8; 3])]) -> Self {
+        self.palette = palette.iter().copied().take(255).collect();
+        self
+    }
+
+    /// Finishes the GIF and returns the underlying writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        match (self.encoder.take(), self.writer.take()) {
+            (Some(encoder), _) => encoder.into_inner(),
+            (None, Some(writer)) => Ok(writer),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    fn index(&self, c: char) -> u8 {
+        self.palette
+            .iter()
+            .position(|(p, _)| *p == c)
+            .map(|idx| idx as u8 + 1)
+            .unwrap_or(0)
+    }
+}
+
+impl<W: Write> Renderer for GifRenderer<W> {
+    fn render(&mut self, frame: &Grid<char>) -> io::Result<()> {
+        let too_large = |_| io::Error::new(io::ErrorKind::InvalidInput, "frame too large");
+        let width = u16::try_from(frame.m * self.scale).map_err(too_large)?;
+        let height = u16::try_from(frame.n * self.scale).map_err(too_large)?;
+
+        if self.encoder.is_none() {
+            let colours = std::iter::once(FALLBACK)
+                .chain(self.palette.iter().map(|(_, rgb)| *rgb))
+                .flatten()
+                .collect::<Vec<_>>();
+            let writer = self.writer.take().expect("writer is only taken once");
+            let mut encoder =
+                Encoder::new(writer, width, height, &colours).map_err(io::Error::other)?;
+            encoder
+                .set_repeat(Repeat::Infinite)
+                .map_err(io::Error::other)?;
+            self.encoder = Some(encoder);
+        }
+
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        for row in frame.grid.iter() {
+            let line = row
+                .iter()
+                .flat_map(|c| std::iter::repeat_n(self.index(*c), self.scale))
+                .collect::<Vec<_>>();
+            for _ in 0..self.scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+
+        let mut gif_frame = Frame::from_indexed_pixels(width, height, pixels, None);
+        gif_frame.delay = self.delay;
+
+        self.encoder
+            .as_mut()
+            .expect("encoder was created above")
+            .write_frame(&gif_frame)
+            .map_err(io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames() -> Vec<Grid<char>> {
+        vec![
+            vec![vec!['.', '#'], vec!['O', '.']].into(),
+            vec![vec!['O', '#'], vec!['.', 'x']].into(),
+        ]
+    }
+
+    #[test]
+    fn text() {
+        let mut renderer = TextRenderer::new(Vec::new());
+        for frame in frames() {
+            renderer.render(&frame).unwrap();
+        }
+
+        let out = String::from_utf8(renderer.into_inner()).unwrap();
+        assert_eq!(out, "frame 0\n.#\nO.\n\nframe 1\nO#\n.x\n\n");
+    }
+
+    #[test]
+    fn gif() {
+        let mut renderer = GifRenderer::new(Vec::new(), 3, Duration::from_millis(100));
+        for frame in frames() {
+            renderer.render(&frame).unwrap();
+        }
+
+        let out = renderer.into_inner().unwrap();
+        assert_eq!(&out[..6], b"GIF89a");
+        // logical screen width and height, little endian
+        assert_eq!(&out[6..10], &[6, 0, 6, 0]);
+        // trailer
+        assert_eq!(out.last(), Some(&0x3b));
+    }
+
+    #[test]
+    fn frame_from_grid() {
+        let grid: Grid<bool> = vec![vec![true, false], vec![false, false]].into();
+        assert_eq!(
+            frame(&grid, |b| if *b { '#' } else { '.' }).grid,
+            vec![vec!['#', '.'], vec!['.', '.']]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Expected answers for the personal inputs, stored in `answers.toml` at the
+//! workspace root so that every day can be checked at once without relying on
+//! the hardcoded values in the ignored `full_dataset` tests.
+//!
+//! The file has one table per year, with a table per day inside it keyed by
+//! the (unpadded) day number:
+//!
+//! ```toml
+//! [2023.1]
+//! part_one = 54390
+//! part_two = 54277
+//! ```
+//!
+//! Either part may be omitted, and string answers are also accepted.
+use std::{collections::BTreeMap, fmt::Display, fs, io, path::Path, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Solution;
+
+/// A single expected value, stored as the string the solution would display.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum Value {
+    Int(i64),
+    Str(String),
+}
+
+impl From<Value> for String {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Int(v) => v.to_string(),
+            Value::Str(v) => v,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+struct RawAnswer {
+    part_one: Option<Value>,
+    part_two: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answer {
+    pub part_one: Option<String>,
+    pub part_two: Option<String>,
+}
+
+impl From<RawAnswer> for Answer {
+    fn from(value: RawAnswer) -> Self {
+        Self {
+            part_one: value.part_one.map(String::from),
+            part_two: value.part_two.map(String::from),
+        }
+    }
+}
+
+/// A part whose answer did not match the stored one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub part: usize,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "part {}: expected {}, got {}",
+            self.part, self.expected, self.actual
+        )
+    }
+}
+
+impl Answer {
+    /// Compares the displayed values of `solution` against the stored
+    /// answers, skipping any part without one.
+    pub fn check<T, G>(&self, solution: &Solution<T, G>) -> Vec<Mismatch>
+    where
+        T: Display + Serialize + PartialEq,
+        G: Display + Serialize + PartialEq,
+    {
+        [
+            (1, &self.part_one, solution.part_one.to_string()),
+            (2, &self.part_two, solution.part_two.to_string()),
+        ]
+        .into_iter()
+        .filter_map(|(part, expected, actual)| match expected {
+            Some(expected) if *expected != actual => Some(Mismatch {
+                part,
+                expected: expected.clone(),
+                actual,
+            }),
+            _ => None,
+        })
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "BTreeMap<String, BTreeMap<String, RawAnswer>>")]
+pub struct Answers {
+    days: BTreeMap<(usize, usize), Answer>,
+}
+
+impl TryFrom<BTreeMap<String, BTreeMap<String, RawAnswer>>> for Answers {
+    type Error = String;
+
+    fn try_from(value: BTreeMap<String, BTreeMap<String, RawAnswer>>) -> Result<Self, Self::Error> {
+        let mut days = BTreeMap::default();
+        for (year_key, answers) in value {
+            let year = year_key
+                .parse()
+                .map_err(|_| format!("invalid year: {}", year_key))?;
+            for (key, answer) in answers {
+                let day = key.parse().map_err(|_| format!("invalid day: {}", key))?;
+                days.insert((year, day), answer.into());
+            }
+        }
+        Ok(Self { days })
+    }
+}
+
+impl FromStr for Answers {
+    type Err = toml::de::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s)
+    }
+}
+
+impl Answers {
+    /// Loads the answers from the given TOML file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn get(&self, year: usize, day: usize) -> Option<&Answer> {
+        self.days.get(&(year, day))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::OptionalAnswer;
+
+    use super::*;
+
+    #[test]
+    fn parse_and_check() {
+        let answers = Answers::from_str(
+            "[2023.1]\npart_one = 142\npart_two = \"abc\"\n\n[2023.25]\npart_one = -3\n\n\
+             [2022.1]\npart_one = 24000\n",
+        )
+        .unwrap();
+
+        let day_one = answers.get(2023, 1).unwrap();
+        assert_eq!(day_one.part_one.as_deref(), Some("142"));
+        assert_eq!(day_one.part_two.as_deref(), Some("abc"));
+        assert!(day_one.check(&Solution::new(142, "abc")).is_empty());
+        assert_eq!(
+            day_one.check(&Solution::new(141, "abc")),
+            vec![Mismatch {
+                part: 1,
+                expected: "142".into(),
+                actual: "141".into()
+            }]
+        );
+
+        let day_25 = answers.get(2023, 25).unwrap();
+        assert_eq!(day_25.part_two, None);
+        assert!(day_25.check(&Solution::new(-3, 99)).is_empty());
+        assert!(day_25
+            .check(&Solution::new(-3, OptionalAnswer::<u64>(None)))
+            .is_empty());
+
+        assert!(answers.get(2023, 2).is_none());
+        assert_eq!(
+            answers.get(2022, 1).unwrap().part_one.as_deref(),
+            Some("24000")
+        );
+        assert!(answers.get(2022, 25).is_none());
+    }
+
+    #[test]
+    fn invalid_day() {
+        assert!(Answers::from_str("[2023.one]\npart_one = 1\n").is_err());
+        assert!(Answers::from_str("[twenty.1]\npart_one = 1\n").is_err());
+        // days must be inside a year
+        assert!(Answers::from_str("[1]\npart_one = 1\n").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! Statistics about an input, for sanity checking custom inputs before
+//! digging into a wrong answer.
+//!
+//! Every day gets the raw line counts for free through
+//! [Problem::describe_input](crate::Problem::describe_input). Days can add
+//! whatever they parse into (grid sizes, graph sizes, value ranges) by
+//! overriding [Problem::describe](crate::Problem::describe).
+//!
+//! ```
+//! use aoc_plumbing::Description;
+//!
+//! let desc = Description::from_input("#.#\n...\n")
+//!     .grid(2, 3)
+//!     .range_of("value", [3, -1, 7]);
+//! assert_eq!(
+//!     desc.to_string(),
+//!     "lines: 2\nblank lines: 0\nlongest line: 3\ngrid: 2x3\nvalue range: -1..=7"
+//! );
+//! ```
+use std::fmt::Display;
+
+use serde::{ser::SerializeMap, Serialize};
+use serde_json::Value;
+
+/// An ordered list of named statistics.
+///
+/// Displays as one `name: value` line per statistic, and serializes to a
+/// JSON object with the values kept as numbers where possible.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Description {
+    stats: Vec<(String, Value)>,
+}
+
+impl Description {
+    /// The line counts of `raw_input`, before any parsing.
+    pub fn from_input(raw_input: &str) -> Self {
+        let lines = raw_input.lines();
+        Self::default()
+            .with("lines", lines.clone().count())
+            .with(
+                "blank lines",
+                lines.clone().filter(|l| l.trim().is_empty()).count(),
+            )
+            .with(
+                "longest line",
+                lines.map(|l| l.chars().count()).max().unwrap_or_default(),
+            )
+    }
+
+    /// Adds a statistic.
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.stats.push((name.into(), value.into()));
+        self
+    }
+
+    /// Adds the dimensions of a grid with `rows` rows and `cols` columns.
+    pub fn grid(self, rows: usize, cols: usize) -> Self {
+        self.with("grid", format!("{}x{}", rows, cols))
+    }
+
+    /// Adds the number of vertices and edges in a graph.
+    pub fn graph(self, vertices: usize, edges: usize) -> Self {
+        self.with("vertices", vertices).with("edges", edges)
+    }
+
+    /// Adds the smallest and largest of `values` as `<name> range`, or
+    /// nothing if there are no values.
+    pub fn range_of<T, I>(self, name: &str, values: I) -> Self
+    where
+        T: Ord + Copy + Display,
+        I: IntoIterator<Item = T>,
+    {
+        let mut values = values.into_iter();
+        let Some(first) = values.next() else {
+            return self;
+        };
+
+        let (min, max) = values.fold((first, first), |(min, max), x| (min.min(x), max.max(x)));
+        self.with(format!("{} range", name), format!("{}..={}", min, max))
+    }
+
+    /// Appends the statistics from `other`.
+    pub fn extend(mut self, other: Self) -> Self {
+        self.stats.extend(other.stats);
+        self
+    }
+
+    /// Looks up a statistic by name.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.stats.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.stats.iter().map(|(n, v)| (n.as_str(), v))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stats.is_empty()
+    }
+}
+
+impl Display for Description {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (name, value)) in self.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            match value {
+                Value::String(s) => write!(f, "{}: {}", name, s)?,
+                _ => write!(f, "{}: {}", name, value)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for Description {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.stats.len()))?;
+        for (name, value) in self.iter() {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let desc = Description::from_input("ab\n\n  \nabcdé\n")
+            .graph(3, 2)
+            .range_of::<i64, _>("empty", [])
+            .extend(Description::default().with("note", "custom"));
+
+        assert_eq!(desc.get("lines"), Some(&Value::from(4)));
+        assert_eq!(desc.get("blank lines"), Some(&Value::from(2)));
+        assert_eq!(desc.get("longest line"), Some(&Value::from(5)));
+        assert_eq!(desc.get("empty range"), None);
+        assert_eq!(
+            serde_json::to_string(&desc).unwrap(),
+            r#"{"lines":4,"blank lines":2,"longest line":5,"vertices":3,"edges":2,"note":"custom"}"#
+        );
+
+        assert!(Description::default().is_empty());
+        assert_eq!(
+            Description::from_input("").to_string(),
+            "lines: 0\nblank lines: 0\nlongest line: 0"
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Checking an optimized solution against a naive reference over many
+//! generated inputs.
+//!
+//! Inputs are produced from a seed (see the `aoc-testing` crate for
+//! generators), so a disagreement can be reproduced from the seed alone.
+//!
+//! ```
+//! use aoc_plumbing::differential::assert_agree;
+//!
+//! assert_agree(
+//!     0..100,
+//!     |seed| (0..seed).map(|x| x.to_string()).collect::<Vec<_>>().join(" "),
+//!     |input| input.split(' ').filter_map(|x| x.parse::<u64>().ok()).sum::<u64>(),
+//!     |input| {
+//!         let n = input.split(' ').filter(|x| !x.is_empty()).count() as u64;
+//!         n * n.saturating_sub(1) / 2
+//!     },
+//! );
+//! ```
+use std::fmt::{Debug, Display};
+
+/// The first input on which the reference and candidate disagreed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disagreement<T> {
+    pub seed: u64,
+    pub input: String,
+    pub reference: T,
+    pub candidate: T,
+}
+
+impl<T: Debug> Display for Disagreement<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "seed {}: reference gave {:?}, candidate gave {:?}",
+            self.seed, self.reference, self.candidate
+        )?;
+        write!(f, "input:\n{}", self.input)
+    }
+}
+
+/// Runs `reference` and `candidate` on the input generated for each seed,
+/// returning the first input they disagree on.
+pub fn find_disagreement<T, G, A, B>(
+    seeds: impl IntoIterator<Item = u64>,
+    generate: G,
+    reference: A,
+    candidate: B,
+) -> Option<Disagreement<T>>
+where
+    T: PartialEq,
+    G: Fn(u64) -> String,
+    A: Fn(&str) -> T,
+    B: Fn(&str) -> T,
+{
+    seeds.into_iter().find_map(|seed| {
+        let input = generate(seed);
+        let (expected, actual) = (reference(&input), candidate(&input));
+        (expected != actual).then_some(Disagreement {
+            seed,
+            input,
+            reference: expected,
+            candidate: actual,
+        })
+    })
+}
+
+/// Like [find_disagreement], but panics with the seed and input of the first
+/// disagreement.
+pub fn assert_agree<T, G, A, B>(
+    seeds: impl IntoIterator<Item = u64>,
+    generate: G,
+    reference: A,
+    candidate: B,
+) where
+    T: PartialEq + Debug,
+    G: Fn(u64) -> String,
+    A: Fn(&str) -> T,
+    B: Fn(&str) -> T,
+{
+    if let Some(disagreement) = find_disagreement(seeds, generate, reference, candidate) {
+        panic!("{}", disagreement);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_first_disagreement() {
+        let found = find_disagreement(
+            0..10,
+            |seed| "x".repeat(seed as usize),
+            |input| input.len(),
+            |input| input.len().min(3),
+        );
+        assert_eq!(
+            found,
+            Some(Disagreement {
+                seed: 4,
+                input: "xxxx".to_string(),
+                reference: 4,
+                candidate: 3,
+            })
+        );
+        assert_eq!(
+            found.unwrap().to_string(),
+            "seed 4: reference gave 4, candidate gave 3\ninput:\nxxxx"
+        );
+
+        assert_eq!(
+            find_disagreement(0..10, |seed| seed.to_string(), str::len, |s| s.len()),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "seed 0: reference gave 1, candidate gave 2")]
+    fn assert_agree_panics() {
+        assert_agree(0..1, |_| String::default(), |_| 1, |_| 2);
+    }
+}
@@ -0,0 +1,41 @@
+//! Fuzzing the parsers of every day with the same proptest.
+//!
+//! Call `aoc_plumbing::parsing_never_panics!(Day)` in a day's test module to
+//! check that parsing arbitrary input, or a mangled copy of `example.txt`,
+//! returns an error rather than panicking. Days without an `example.txt` pass
+//! the example as a second argument. The calling crate needs `proptest` as a
+//! dev-dependency.
+
+/// Defines a `parsing_never_panics` proptest for `$problem`, see the
+/// [module docs](crate::fuzz).
+#[macro_export]
+macro_rules! parsing_never_panics {
+    ($problem:ty) => {
+        $crate::parsing_never_panics!(
+            $problem,
+            std::fs::read_to_string("example.txt").expect("Unable to load input")
+        );
+    };
+    ($problem:ty, $example:expr) => {
+        ::proptest::proptest! {
+            #[test]
+            fn parsing_never_panics(
+                input in "\\PC{0,64}",
+                at in ::proptest::prelude::any::<::proptest::sample::Index>(),
+                len in 0..4_usize,
+                junk in "\\PC{0,4}",
+            ) {
+                let _ = <$problem as $crate::Problem>::instance(&input);
+
+                // mangling the example gets much further into the parser
+                let example: String = $example.into();
+                let mut chars: Vec<char> = example.chars().collect();
+                let at = at.index(chars.len() + 1);
+                chars.splice(at..(at + len).min(chars.len()), junk.chars());
+                let _ = <$problem as $crate::Problem>::instance(
+                    &chars.into_iter().collect::<String>(),
+                );
+            }
+        }
+    };
+}
@@ -0,0 +1,115 @@
+//! Re-solving after editing a single line of the input, for days where every
+//! line is solved on its own.
+//!
+//! Rather than parsing the whole edited input again, an [Incremental]
+//! problem parses just the new line and only recomputes the results that
+//! depend on it, which makes it cheap to poke at an input interactively.
+//!
+//! ```
+//! use aoc_plumbing::incremental::replace_line;
+//!
+//! assert_eq!(replace_line("a\nb\nc\n", 1, "x").unwrap(), "a\nx\nc\n");
+//! assert_eq!(replace_line("a\r\nb", 1, "x").unwrap(), "a\r\nx");
+//! assert_eq!(replace_line("a\nb", 2, "x"), None);
+//! ```
+use std::fmt::Debug;
+
+use crate::{Problem, Solution};
+
+pub trait Incremental: Problem {
+    /// Replaces line `changed_line` (0-based, counting every line of the
+    /// input, blank or not) with `new_line`. If `new_line` can't be parsed,
+    /// or there is no such line, the instance is left as it was.
+    fn update(&mut self, changed_line: usize, new_line: &str) -> Result<(), Self::ProblemError>;
+}
+
+/// Replaces line `changed_line` of `raw_input` with `new_line`, keeping the
+/// line ending, or returns `None` if there is no such line.
+pub fn replace_line(raw_input: &str, changed_line: usize, new_line: &str) -> Option<String> {
+    let mut start = 0;
+    for _ in 0..changed_line {
+        start += raw_input[start..].find('\n')? + 1;
+    }
+    if start >= raw_input.len() && changed_line > 0 {
+        return None;
+    }
+
+    let rest = &raw_input[start..];
+    let mut end = start + rest.find('\n').unwrap_or(rest.len());
+    if raw_input[..end].ends_with('\r') {
+        end -= 1;
+    }
+
+    Some(format!(
+        "{}{}{}",
+        &raw_input[..start],
+        new_line,
+        &raw_input[end..]
+    ))
+}
+
+/// Applies each of `edits` to an instance of `raw_input` in turn, and panics
+/// if the updated instance doesn't give the same answers as solving the
+/// edited input from scratch. Edits that fail to parse, or that are past the
+/// end of the input, must fail to update.
+pub fn assert_updates_agree<P>(raw_input: &str, edits: &[(usize, &str)])
+where
+    P: Incremental,
+    P::P1: Debug,
+    P::P2: Debug,
+    P::ProblemError: Debug,
+{
+    let mut input = raw_input.to_string();
+    let mut instance = P::instance(raw_input)
+        .map_err(P::ProblemError::from)
+        .expect("the original input should parse");
+
+    for &(line, new_line) in edits {
+        let Some(edited) = replace_line(&input, line, new_line) else {
+            assert!(
+                instance.update(line, new_line).is_err(),
+                "updating line {} succeeded, but the input only has {} lines",
+                line,
+                input.lines().count()
+            );
+            continue;
+        };
+
+        match (P::instance(&edited), instance.update(line, new_line)) {
+            (Ok(_), Ok(())) => input = edited,
+            (Err(_), Err(_)) => continue,
+            (Ok(_), Err(e)) => panic!("updating line {} to {:?} failed: {:?}", line, new_line, e),
+            (Err(_), Ok(())) => panic!(
+                "updating line {} to {:?} succeeded, but the edited input doesn't parse",
+                line, new_line
+            ),
+        }
+
+        let expected = P::solve(&input).expect("the edited input should solve");
+        let actual = Solution::new(
+            instance.part_one().expect("part one should solve"),
+            instance.part_two().expect("part two should solve"),
+        );
+        assert_eq!(
+            actual, expected,
+            "after updating line {} to {:?}, input:\n{}",
+            line, new_line, input
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replacing_lines() {
+        assert_eq!(replace_line("a\nb", 0, "").unwrap(), "\nb");
+        assert_eq!(replace_line("a\n\nc", 1, "b").unwrap(), "a\nb\nc");
+        assert_eq!(replace_line("a\nb\n", 1, "x\r").unwrap(), "a\nx\r\n");
+        assert_eq!(replace_line("", 0, "a").unwrap(), "a");
+        // the empty string after a trailing newline isn't a line
+        assert_eq!(replace_line("a\n", 1, "b"), None);
+        assert_eq!(replace_line("", 1, "b"), None);
+    }
+}
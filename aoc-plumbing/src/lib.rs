@@ -1,3 +1,13 @@
+pub mod answers;
+pub mod describe;
+pub mod differential;
+pub mod fuzz;
+pub mod incremental;
+pub mod ocr;
 pub mod problem;
+pub mod scan;
+pub mod sections;
 
-pub use problem::{Problem, Solution};
+pub use answers::Answers;
+pub use describe::Description;
+pub use problem::{OptionalAnswer, Problem, Solution};
@@ -0,0 +1,334 @@
+//! Reading the capital letters some puzzles draw instead of giving a number.
+//!
+//! There are two fonts: a small one six pixels tall, where most letters are
+//! four wide, and a large one ten pixels tall, where letters are six wide.
+//! Letters are told apart by the empty columns between them, so the exact
+//! spacing doesn't matter, and the font is picked from the height of the
+//! lit pixels.
+//!
+//! ```
+//! use aoc_plumbing::ocr::recognize_str;
+//!
+//! let drawing = "\
+//! .#..#.####.#....#.....##.
+//! .#..#.#....#....#....#..#
+//! .####.###..#....#....#..#
+//! .#..#.#....#....#....#..#
+//! .#..#.#....#....#....#..#
+//! .#..#.####.####.####..##.";
+//! assert_eq!(recognize_str(drawing).unwrap(), "HELLO");
+//! ```
+use std::fmt::Display;
+
+use aoc_common::grid::Grid;
+
+/// The six pixel tall font, with rows separated by `\n`.
+const SMALL: [(char, &str); 18] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+/// The ten pixel tall font, with rows separated by `\n`.
+const LARGE: [(char, &str); 15] = [
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+/// Why a drawing couldn't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// Nothing is lit.
+    Empty,
+    /// The lit pixels aren't as tall as either font.
+    UnsupportedHeight(usize),
+    /// The glyph at `index` (counting from 0) isn't a known letter. The
+    /// glyph is drawn with `#` and `.`.
+    UnknownGlyph { index: usize, glyph: String },
+}
+
+impl Display for OcrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "nothing to read"),
+            Self::UnsupportedHeight(h) => {
+                write!(f, "letters are 6 or 10 pixels tall, but these are {}", h)
+            }
+            Self::UnknownGlyph { index, glyph } => {
+                write!(f, "unknown glyph at position {}:\n{}", index, glyph)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// Reads the letters drawn by the lit `(row, col)` points. Anything around
+/// the letters is ignored, so the points don't need to start at zero.
+pub fn recognize_points<I>(points: I) -> Result<String, OcrError>
+where
+    I: IntoIterator<Item = (usize, usize)>,
+{
+    let points: Vec<(usize, usize)> = points.into_iter().collect();
+    let (Some(top), Some(left)) = (
+        points.iter().map(|p| p.0).min(),
+        points.iter().map(|p| p.1).min(),
+    ) else {
+        return Err(OcrError::Empty);
+    };
+    let bottom = points.iter().map(|p| p.0).max().unwrap_or(top);
+    let right = points.iter().map(|p| p.1).max().unwrap_or(left);
+
+    let mut lit = vec![vec![false; right - left + 1]; bottom - top + 1];
+    for (r, c) in points {
+        lit[r - top][c - left] = true;
+    }
+
+    recognize_rows(&lit)
+}
+
+/// Reads the letters drawn by the `true` cells of `grid`.
+pub fn recognize_grid(grid: &Grid<bool>) -> Result<String, OcrError> {
+    recognize_points(grid.grid.iter().enumerate().flat_map(|(r, row)| {
+        row.iter()
+            .enumerate()
+            .filter(|(_, lit)| **lit)
+            .map(move |(c, _)| (r, c))
+    }))
+}
+
+/// Reads the letters drawn with `#` or `█` for lit pixels, and anything else
+/// for unlit ones.
+pub fn recognize_str(s: &str) -> Result<String, OcrError> {
+    recognize_points(s.lines().enumerate().flat_map(|(r, line)| {
+        line.chars()
+            .enumerate()
+            .filter(|(_, ch)| matches!(ch, '#' | '█'))
+            .map(move |(c, _)| (r, c))
+    }))
+}
+
+/// Reads a drawing trimmed to the lit pixels, splitting it into glyphs at
+/// the empty columns.
+fn recognize_rows(lit: &[Vec<bool>]) -> Result<String, OcrError> {
+    let font: &[(char, &str)] = match lit.len() {
+        6 => &SMALL,
+        10 => &LARGE,
+        h => return Err(OcrError::UnsupportedHeight(h)),
+    };
+
+    let width = lit[0].len();
+    let empty = |c: usize| lit.iter().all(|row| !row[c]);
+
+    let mut ret = String::default();
+    let mut c = 0;
+    while c < width {
+        if empty(c) {
+            c += 1;
+            continue;
+        }
+
+        let start = c;
+        while c < width && !empty(c) {
+            c += 1;
+        }
+
+        let glyph = lit
+            .iter()
+            .map(|row| {
+                row[start..c]
+                    .iter()
+                    .map(|x| if *x { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let letter = font
+            .iter()
+            .find(|(_, g)| *g == glyph)
+            .map(|(letter, _)| *letter)
+            .ok_or_else(|| OcrError::UnknownGlyph {
+                index: ret.chars().count(),
+                glyph,
+            })?;
+        ret.push(letter);
+    }
+
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Draws `letters` side by side with `gap` empty columns between them.
+    fn draw(font: &[(char, &str)], letters: &str, gap: usize) -> String {
+        let glyphs: Vec<Vec<&str>> = letters
+            .chars()
+            .map(|l| {
+                let (_, g) = font.iter().find(|(c, _)| *c == l).unwrap();
+                g.lines().collect()
+            })
+            .collect();
+
+        (0..glyphs[0].len())
+            .map(|r| {
+                glyphs
+                    .iter()
+                    .map(|g| g[r])
+                    .collect::<Vec<_>>()
+                    .join(&".".repeat(gap))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn every_glyph() {
+        for font in [&SMALL[..], &LARGE[..]] {
+            let letters: String = font.iter().map(|(c, _)| *c).collect();
+            for gap in [1, 2, 3] {
+                assert_eq!(recognize_str(&draw(font, &letters, gap)).unwrap(), letters);
+            }
+
+            for (letter, glyph) in font {
+                let rows: Vec<&str> = glyph.lines().collect();
+                assert!(rows.iter().all(|r| r.len() == rows[0].len()), "{}", letter);
+                // every glyph spans the full height, which the font is
+                // picked by
+                assert!(rows[0].contains('#') && rows[rows.len() - 1].contains('#'));
+            }
+        }
+    }
+
+    #[test]
+    fn sources() {
+        // 2016 day 8 and 2019 day 11 style output, drawn with blocks and
+        // padded with blank space
+        let drawing = "
+  ██  ███  █  █
+ █  █ █  █ █ █
+ █    ███  ██
+ █ ██ █  █ █ █
+ █  █ █  █ █ █
+  ███ ███  █  █
+";
+        assert_eq!(recognize_str(drawing).unwrap(), "GBK");
+
+        let mut grid = Grid::new(10, 14, false);
+        for (r, row) in draw(&LARGE, "NX", 2).lines().enumerate() {
+            for (c, ch) in row.chars().enumerate() {
+                grid.grid[r][c] = ch == '#';
+            }
+        }
+        assert_eq!(recognize_grid(&grid).unwrap(), "NX");
+
+        let offset = draw(&SMALL, "ZY", 1)
+            .lines()
+            .enumerate()
+            .flat_map(|(r, row)| {
+                row.chars()
+                    .enumerate()
+                    .filter(|(_, ch)| *ch == '#')
+                    .map(move |(c, _)| (r + 100, c + 7))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(recognize_points(offset).unwrap(), "ZY");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(recognize_str("...\n..."), Err(OcrError::Empty));
+        assert_eq!(
+            recognize_str("#\n#\n#"),
+            Err(OcrError::UnsupportedHeight(3))
+        );
+
+        let drawing = draw(&SMALL, "AC", 1).replacen('.', "#", 1);
+        let err = recognize_str(&drawing).unwrap_err();
+        assert_eq!(
+            err,
+            OcrError::UnknownGlyph {
+                index: 0,
+                glyph: "###.\n#..#\n#..#\n####\n#..#\n#..#".to_string()
+            }
+        );
+        assert!(err
+            .to_string()
+            .starts_with("unknown glyph at position 0:\n###."));
+    }
+}
@@ -2,6 +2,8 @@ use std::{fmt::Display, str::FromStr};
 
 use serde::Serialize;
 
+use crate::Description;
+
 /// This struct enables printing a given solution in either plaintext or JSON,
 /// depending on the presence of the `AOC_OUTPUT_JSON` ENV var. Its main purpose
 /// is to standardize the output for consuption by the CI system.
@@ -57,13 +59,35 @@ where
     }
 }
 
+/// Answers that span several lines, like letters drawn in ASCII art, start
+/// on the line after the label:
+/// ```
+/// use aoc_plumbing::{OptionalAnswer, Solution};
+/// let s = Solution::new("#..#\n####\n#..#".to_string(), OptionalAnswer::<u64>(None));
+///
+/// assert_eq!(s.to_string(), "part 1:\n#..#\n####\n#..#\npart 2: -");
+/// ```
 impl<T, G> Display for Solution<T, G>
 where
     T: Display + Serialize + PartialEq,
     G: Display + Serialize + PartialEq,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "part 1: {}\npart 2: {}", self.part_one, self.part_two)
+        for (part, answer) in [
+            (1, self.part_one.to_string()),
+            (2, self.part_two.to_string()),
+        ] {
+            if part > 1 {
+                writeln!(f)?;
+            }
+            if answer.contains('\n') {
+                write!(f, "part {}:\n{}", part, answer)?;
+            } else {
+                write!(f, "part {}: {}", part, answer)?;
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -77,7 +101,44 @@ where
     }
 }
 
+/// An answer that might not exist, such as part two of day 25, which is
+/// handed out for finishing every other day rather than worked out.
+/// Displays as `-` and serializes as `null` when there's no answer.
+///
+/// ```
+/// use aoc_plumbing::{OptionalAnswer, Solution};
+/// let s = Solution::new(54, OptionalAnswer::<u64>(None));
+///
+/// assert_eq!(s.to_string(), "part 1: 54\npart 2: -");
+/// assert_eq!(
+///     serde_json::to_string(&s).unwrap(),
+///     "{\"part_one\":54,\"part_two\":null}"
+/// );
+/// assert_eq!(OptionalAnswer(Some(3)).to_string(), "3");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct OptionalAnswer<T>(pub Option<T>);
+
+impl<T: Display> Display for OptionalAnswer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(answer) => answer.fmt(f),
+            None => write!(f, "-"),
+        }
+    }
+}
+
+impl<T> From<Option<T>> for OptionalAnswer<T> {
+    fn from(value: Option<T>) -> Self {
+        Self(value)
+    }
+}
+
 pub trait Problem: FromStr {
+    /// The year of the event, so that several years can share the cli and
+    /// benchmarks without their days colliding.
+    const YEAR: usize;
     const DAY: usize;
     const TITLE: &'static str;
     const README: &'static str;
@@ -98,9 +159,24 @@ pub trait Problem: FromStr {
         Ok(Solution::new(inst.part_one()?, inst.part_two()?))
     }
 
+    /// Statistics about the parsed input, such as grid dimensions, graph
+    /// sizes or value ranges. Nothing by default.
+    fn describe(&self) -> Description {
+        Description::default()
+    }
+
+    /// The line counts of `raw_input`, followed by [Problem::describe] for
+    /// the parsed instance.
+    fn describe_input(raw_input: &str) -> Result<Description, Self::ProblemError> {
+        let inst = Self::instance(raw_input)?;
+        Ok(Description::from_input(raw_input).extend(inst.describe()))
+    }
+
+    /// The year, padded day and title, e.g. `2023 017 clumsy crucible`.
     fn problem_label() -> String {
         format!(
-            "{:03} {}",
+            "{} {} {}",
+            <Self as Problem>::YEAR,
             <Self as Problem>::padded_day(),
             <Self as Problem>::TITLE
         )
@@ -0,0 +1,273 @@
+//! A small cursor over the bytes of an input, for parsers that would
+//! otherwise `split` and `collect` every line into short-lived `Vec`s.
+//!
+//! ```
+//! use aoc_plumbing::scan::Scanner;
+//!
+//! let mut s = Scanner::new("1,-2~3\n");
+//! let a: i64 = s.int().unwrap();
+//! s.expect(b',').unwrap();
+//! let b: i64 = s.int().unwrap();
+//! s.expect(b'~').unwrap();
+//! let c: u8 = s.uint().unwrap();
+//! assert_eq!((a, b, c), (1, -2, 3));
+//! assert!(s.eat(b'\n'));
+//! assert!(s.is_empty());
+//! ```
+use std::fmt::Display;
+
+/// What the scanner was looking for, and where, when it gave up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanError {
+    pub pos: usize,
+    pub expected: String,
+    pub found: Option<char>,
+}
+
+impl Display for ScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.found {
+            Some(c) => write!(
+                f,
+                "expected {} at byte {}, found {:?}",
+                self.expected, self.pos, c
+            ),
+            None => write!(
+                f,
+                "expected {} at byte {}, found end of input",
+                self.expected, self.pos
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+#[derive(Debug, Clone)]
+pub struct Scanner<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    /// The byte offset of the next unread byte.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    /// Everything that hasn't been read yet.
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    /// Consumes `c` if it's the next byte, returning whether it was.
+    pub fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes `c`, or fails without consuming anything.
+    pub fn expect(&mut self, c: u8) -> Result<(), ScanError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("{:?}", c as char)))
+        }
+    }
+
+    /// Consumes `s`, or fails without consuming anything.
+    pub fn expect_str(&mut self, s: &'static str) -> Result<(), ScanError> {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            Ok(())
+        } else {
+            Err(self.error(format!("{:?}", s)))
+        }
+    }
+
+    /// Skips spaces and tabs, but not newlines.
+    pub fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips any ASCII whitespace, including newlines.
+    pub fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consumes bytes while `pred` holds and returns them. This only ever
+    /// consumes ASCII, so the result is always a valid `str`.
+    pub fn take_while(&mut self, pred: impl Fn(u8) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(|b| b.is_ascii() && pred(b)) {
+            self.pos += 1;
+        }
+
+        &self.input[start..self.pos]
+    }
+
+    /// Consumes the rest of the current line, and the newline after it if
+    /// there is one. A trailing `\r` is dropped.
+    pub fn line(&mut self) -> &'a str {
+        let rest = self.rest();
+        let line = match rest.find('\n') {
+            Some(idx) => &rest[..idx],
+            None => rest,
+        };
+        self.pos += line.len();
+        self.eat(b'\n');
+        line.strip_suffix('\r').unwrap_or(line)
+    }
+
+    /// Reads an unsigned decimal integer, failing if there are no digits or
+    /// it doesn't fit in `T`.
+    pub fn uint<T: TryFrom<u64>>(&mut self) -> Result<T, ScanError> {
+        let start = self.pos;
+        let value = self.digits().ok_or_else(|| self.error("an integer"))?;
+        T::try_from(value).map_err(|_| {
+            self.pos = start;
+            self.error("an integer in range")
+        })
+    }
+
+    /// Reads a decimal integer with an optional leading `-` or `+`, failing
+    /// if there are no digits or it doesn't fit in `T`.
+    pub fn int<T: TryFrom<i64>>(&mut self) -> Result<T, ScanError> {
+        let start = self.pos;
+        let negative = self.eat(b'-');
+        if !negative {
+            self.eat(b'+');
+        }
+
+        let Some(magnitude) = self.digits() else {
+            let err = self.error("an integer");
+            self.pos = start;
+            return Err(err);
+        };
+
+        let value = if negative {
+            0_i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        };
+
+        match value.and_then(|v| T::try_from(v).ok()) {
+            Some(v) => Ok(v),
+            None => {
+                self.pos = start;
+                Err(self.error("an integer in range"))
+            }
+        }
+    }
+
+    /// Reads a run of digits, returning `None` if there are none or they
+    /// overflow (leaving the position unchanged in either case).
+    fn digits(&mut self) -> Option<u64> {
+        let start = self.pos;
+        let mut value = 0_u64;
+        while let Some(d) = self.peek().filter(u8::is_ascii_digit) {
+            let next = value
+                .checked_mul(10)
+                .and_then(|v| v.checked_add((d - b'0') as u64));
+            match next {
+                Some(next) => value = next,
+                None => {
+                    self.pos = start;
+                    return None;
+                }
+            }
+            self.pos += 1;
+        }
+
+        (self.pos > start).then_some(value)
+    }
+
+    fn error(&self, expected: impl Into<String>) -> ScanError {
+        ScanError {
+            pos: self.pos,
+            expected: expected.into(),
+            found: self.rest().chars().next(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers() {
+        let mut s = Scanner::new("12 -34 +5 -9223372036854775808");
+        assert_eq!(s.uint::<u8>(), Ok(12));
+        s.skip_spaces();
+        assert_eq!(s.int::<i32>(), Ok(-34));
+        s.skip_spaces();
+        assert_eq!(s.int::<isize>(), Ok(5));
+        s.skip_spaces();
+        assert_eq!(s.int::<i64>(), Ok(i64::MIN));
+        assert!(s.is_empty());
+
+        let mut s = Scanner::new("300,x,99999999999999999999");
+        let err = s.uint::<u8>().unwrap_err();
+        assert_eq!((err.pos, err.expected.as_str()), (0, "an integer in range"));
+        assert_eq!(s.uint::<u16>(), Ok(300));
+        s.expect(b',').unwrap();
+        assert_eq!(
+            s.int::<i64>(),
+            Err(ScanError {
+                pos: 4,
+                expected: "an integer".to_string(),
+                found: Some('x')
+            })
+        );
+        assert_eq!(s.take_while(|b| b.is_ascii_alphabetic()), "x");
+        s.expect(b',').unwrap();
+        assert!(s.uint::<u64>().is_err());
+        assert_eq!(s.pos(), 6);
+    }
+
+    #[test]
+    fn delimiters_and_lines() {
+        let mut s = Scanner::new("px{a<2006:qkq}\r\nnext é\n\n");
+        assert_eq!(s.take_while(|b| b.is_ascii_alphabetic()), "px");
+        s.expect(b'{').unwrap();
+        assert!(!s.eat(b'}'));
+        assert_eq!(s.line(), "a<2006:qkq}");
+        assert!(s.expect_str("nope").is_err());
+        s.expect_str("next").unwrap();
+        s.skip_spaces();
+        // stops at non-ASCII rather than splitting a character
+        assert_eq!(s.take_while(|_| true), "");
+        assert_eq!(
+            s.expect(b',').unwrap_err().to_string(),
+            "expected ',' at byte 21, found 'é'"
+        );
+        assert_eq!(s.line(), "é");
+        s.skip_whitespace();
+        assert!(s.is_empty());
+        assert_eq!(
+            s.expect(b'\n').unwrap_err().to_string(),
+            "expected '\\n' at byte 25, found end of input"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Splitting inputs made of several blocks separated by blank lines, and
+//! parsing the blocks in parallel.
+//!
+//! ```
+//! use aoc_plumbing::sections::{par_parse_sections, sections};
+//!
+//! let input = "1\n2\n\n\n3\r\n\r\n4\n";
+//! assert_eq!(sections(input), vec!["1\n2", "3", "4"]);
+//!
+//! let sums: Result<Vec<u32>, std::num::ParseIntError> =
+//!     par_parse_sections(input, |s| s.lines().map(|x| x.trim().parse::<u32>()).sum());
+//! assert_eq!(sums.unwrap(), vec![3, 3, 4]);
+//! ```
+use rayon::prelude::*;
+
+/// Splits `input` on runs of blank (or whitespace only) lines, dropping any
+/// at the start or end. Each section keeps its own line breaks, but not the
+/// one after its last line.
+pub fn sections(input: &str) -> Vec<&str> {
+    let mut ret = Vec::default();
+    // the start of the current section and the end of its last line
+    let mut start = None;
+    let mut end = 0;
+    let mut pos = 0;
+
+    for line in input.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if let Some(start) = start.take() {
+                ret.push(&input[start..end]);
+            }
+        } else {
+            start.get_or_insert(pos);
+            end = pos + line.trim_end_matches(['\n', '\r']).len();
+        }
+        pos += line.len();
+    }
+
+    if let Some(start) = start {
+        ret.push(&input[start..end]);
+    }
+
+    ret
+}
+
+/// Parses every section of `input` with `parse` in parallel, returning them
+/// in input order. If any fail, the error is the one for the first section
+/// that failed, as if they had been parsed one after another.
+pub fn par_parse_sections<T, E, F>(input: &str, parse: F) -> Result<Vec<T>, E>
+where
+    T: Send,
+    E: Send,
+    F: Fn(&str) -> Result<T, E> + Sync + Send,
+{
+    sections(input)
+        .into_par_iter()
+        .map(parse)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splitting() {
+        assert!(sections("").is_empty());
+        assert!(sections("\n \n\t\n").is_empty());
+        assert_eq!(sections("a"), vec!["a"]);
+        assert_eq!(sections("\n\na\nb\n\n"), vec!["a\nb"]);
+        assert_eq!(sections("a\r\nb\r\n  \r\nc"), vec!["a\r\nb", "c"]);
+        // indentation is kept
+        assert_eq!(sections(" a\n\n  b \n"), vec![" a", "  b "]);
+    }
+
+    #[test]
+    fn first_error_wins() {
+        let input = (0..300)
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let parsed: Result<Vec<u8>, String> = par_parse_sections(&input, |s| {
+            let x: u32 = s.parse().unwrap();
+            u8::try_from(x).map_err(|_| s.to_string())
+        });
+        assert_eq!(parsed, Err("256".to_string()));
+
+        let parsed: Result<Vec<u32>, ()> =
+            par_parse_sections(&input, |s| s.parse().map_err(|_| ()));
+        assert_eq!(parsed.unwrap(), (0..300).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,88 @@
+//! Almanacs of seeds and mappings.
+//!
+//! The seed ranges are kept short, so that every seed can still be mapped
+//! one at a time to check the range based approach.
+use std::fmt::Write;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::Generator;
+
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+/// Generates the seed ranges followed by the seven maps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Almanac {
+    /// The number of `start length` pairs on the seeds line.
+    pub seed_ranges: usize,
+    /// The most mappings in each map.
+    pub max_mappings: usize,
+    /// Every range (of seeds, sources or destinations) is within
+    /// `0..max_value`.
+    pub max_value: usize,
+    /// The longest a seed range can be.
+    pub max_range: usize,
+}
+
+impl Default for Almanac {
+    fn default() -> Self {
+        Self {
+            seed_ranges: 10,
+            max_mappings: 8,
+            max_value: 1000,
+            max_range: 100,
+        }
+    }
+}
+
+impl Generator for Almanac {
+    fn generate<R: Rng>(&self, rng: &mut R) -> String {
+        let max_value = self.max_value.max(2);
+        let mut out = String::from("seeds:");
+
+        for _ in 0..self.seed_ranges {
+            let start = rng.gen_range(0..max_value);
+            let len = rng.gen_range(1..=self.max_range.clamp(1, max_value - start));
+            write!(out, " {} {}", start, len).expect("writing to a string");
+        }
+        out.push('\n');
+
+        for pair in CATEGORIES.windows(2) {
+            write!(out, "\n{}-to-{} map:\n", pair[0], pair[1]).expect("writing to a string");
+
+            // the sources of a map never overlap, so cut them out of a random
+            // set of boundaries and leave some of the gaps unmapped
+            let count = rng.gen_range(0..=self.max_mappings);
+            let mut cuts: Vec<usize> = (0..2 * count)
+                .map(|_| rng.gen_range(0..max_value))
+                .collect();
+            cuts.sort();
+            cuts.dedup();
+
+            let mut mappings = Vec::default();
+            for w in cuts.windows(2) {
+                if rng.gen_bool(0.5) {
+                    let (source, length) = (w[0], w[1] - w[0]);
+                    mappings.push((rng.gen_range(0..max_value - length), source, length));
+                }
+            }
+            mappings.shuffle(rng);
+
+            for (destination, source, length) in mappings {
+                writeln!(out, "{} {} {}", destination, source, length)
+                    .expect("writing to a string");
+            }
+        }
+
+        out
+    }
+}
@@ -0,0 +1,200 @@
+//! Pipe mazes with exactly one loop through the start.
+//!
+//! The loop is the outline of a tree: a random spanning tree over a grid of
+//! nodes is drawn at twice the resolution, with a cell for every node and
+//! for every edge of the tree. That shape has no holes, and no two of its
+//! cells touch only at a corner, so its outline never crosses itself. The
+//! pipes run along the outline, with one pipe per cell corner.
+use rand::{seq::SliceRandom, Rng};
+
+use crate::Generator;
+
+/// Generates a pipe maze with a single loop, surrounded by unconnected junk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipeLoop {
+    /// The number of rows of tree nodes. The maze has twice as many rows.
+    pub rows: usize,
+    /// The number of columns of tree nodes. The maze has twice as many
+    /// columns.
+    pub cols: usize,
+    /// The chance that a tile off the loop is a pipe rather than ground.
+    pub junk: f64,
+}
+
+impl Default for PipeLoop {
+    fn default() -> Self {
+        Self {
+            rows: 10,
+            cols: 10,
+            junk: 0.5,
+        }
+    }
+}
+
+/// A generated maze along with its answers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Maze {
+    pub input: String,
+    /// The number of tiles in the loop.
+    pub length: usize,
+    /// The number of tiles enclosed by the loop.
+    pub enclosed: usize,
+}
+
+const NORTH: u8 = 1;
+const SOUTH: u8 = 2;
+const EAST: u8 = 4;
+const WEST: u8 = 8;
+
+impl PipeLoop {
+    pub fn generate_maze<R: Rng>(&self, rng: &mut R) -> Maze {
+        let (rows, cols) = (self.rows.max(1), self.cols.max(1));
+        let (n, m) = (2 * rows - 1, 2 * cols - 1);
+
+        // the cells of the tree outline, filled in by a randomized dfs
+        let mut filled = vec![vec![false; m]; n];
+        let mut stack = vec![(0, 0)];
+        filled[0][0] = true;
+        while let Some(&(r, c)) = stack.last() {
+            let mut next = Vec::with_capacity(4);
+            if r > 0 && !filled[2 * r - 2][2 * c] {
+                next.push((r - 1, c));
+            }
+            if r + 1 < rows && !filled[2 * r + 2][2 * c] {
+                next.push((r + 1, c));
+            }
+            if c > 0 && !filled[2 * r][2 * c - 2] {
+                next.push((r, c - 1));
+            }
+            if c + 1 < cols && !filled[2 * r][2 * c + 2] {
+                next.push((r, c + 1));
+            }
+
+            match next.choose(rng) {
+                Some(&(nr, nc)) => {
+                    filled[r + nr][c + nc] = true;
+                    filled[2 * nr][2 * nc] = true;
+                    stack.push((nr, nc));
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+
+        let cell = |r: usize, c: usize| {
+            (1..=n).contains(&r) && (1..=m).contains(&c) && filled[r - 1][c - 1]
+        };
+        // the corner at (r, c) is shared by cells (r - 1, c - 1) to (r, c),
+        // shifted by one to keep things unsigned. An edge out of a corner is
+        // part of the outline if exactly one of the cells beside it is filled
+        let connections = |r: usize, c: usize| {
+            let mut dirs = 0;
+            if r > 0 && cell(r, c) != cell(r, c + 1) {
+                dirs |= NORTH;
+            }
+            if cell(r + 1, c) != cell(r + 1, c + 1) {
+                dirs |= SOUTH;
+            }
+            if cell(r, c + 1) != cell(r + 1, c + 1) {
+                dirs |= EAST;
+            }
+            if c > 0 && cell(r, c) != cell(r + 1, c) {
+                dirs |= WEST;
+            }
+            dirs
+        };
+
+        let mut tiles = vec![vec!['.'; m + 1]; n + 1];
+        let mut on_loop = Vec::default();
+        for (r, row) in tiles.iter_mut().enumerate() {
+            for (c, tile) in row.iter_mut().enumerate() {
+                let dirs = connections(r, c);
+                if dirs != 0 {
+                    on_loop.push((r, c));
+                }
+
+                *tile = match dirs {
+                    0 => {
+                        if rng.gen_bool(self.junk) {
+                            *b"|-LJ7F".choose(rng).expect("not empty") as char
+                        } else {
+                            '.'
+                        }
+                    }
+                    x if x == NORTH | SOUTH => '|',
+                    x if x == EAST | WEST => '-',
+                    x if x == NORTH | EAST => 'L',
+                    x if x == NORTH | WEST => 'J',
+                    x if x == SOUTH | WEST => '7',
+                    x if x == SOUTH | EAST => 'F',
+                    _ => unreachable!("the outline never crosses itself"),
+                };
+            }
+        }
+
+        // junk next to the start could connect to it, which would make the
+        // start tile ambiguous
+        let &(sr, sc) = on_loop.choose(rng).expect("the loop is never empty");
+        for (r, c) in [
+            (sr + 1, sc),
+            (sr, sc + 1),
+            (sr.wrapping_sub(1), sc),
+            (sr, sc.wrapping_sub(1)),
+        ] {
+            if r <= n && c <= m && connections(r, c) == 0 {
+                tiles[r][c] = '.';
+            }
+        }
+        tiles[sr][sc] = 'S';
+
+        // by Pick's theorem, as every filled cell has an area of one
+        let area = filled.iter().flatten().filter(|x| **x).count();
+        let length = on_loop.len();
+
+        Maze {
+            input: tiles
+                .into_iter()
+                .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+                .collect(),
+            length,
+            enclosed: area + 1 - length / 2,
+        }
+    }
+}
+
+impl Generator for PipeLoop {
+    fn generate<R: Rng>(&self, rng: &mut R) -> String {
+        self.generate_maze(rng).input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_plumbing::{Problem, Solution};
+    use pipe_maze::PipeMaze;
+
+    use super::*;
+    use crate::rng;
+
+    #[test]
+    fn answers_match() {
+        for (seed, rows, cols) in [(0, 1, 1), (1, 1, 7), (2, 5, 5), (3, 20, 30)] {
+            let maze = PipeLoop {
+                rows,
+                cols,
+                ..Default::default()
+            }
+            .generate_maze(&mut rng(seed));
+
+            assert_eq!(maze.input.lines().count(), 2 * rows);
+            assert_eq!(
+                PipeMaze::solve(&maze.input).unwrap(),
+                Solution::new(maze.length as u64 / 2, maze.enclosed as u64),
+                "seed {}\n{}",
+                seed,
+                maze.input
+            );
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Patterns of ash and rocks with a smudge.
+//!
+//! Each pattern is built to mirror both across a horizontal line and across
+//! a vertical one, and then one cell is flipped so that only the horizontal
+//! line still reflects perfectly. Fixing that cell (the smudge) brings back
+//! the vertical reflection. Either orientation may be transposed.
+//!
+//! Random patterns can still have extra lines of reflection by chance, so
+//! checks that rely on the puzzle's guarantees should filter those out.
+use rand::Rng;
+
+use crate::Generator;
+
+/// Generates patterns separated by blank lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Patterns {
+    pub patterns: usize,
+    /// Patterns have between 7 and this many rows and columns, like the real
+    /// ones. Smaller patterns have too many lines of reflection by chance.
+    pub max_size: usize,
+}
+
+impl Default for Patterns {
+    fn default() -> Self {
+        Self {
+            patterns: 100,
+            max_size: 17,
+        }
+    }
+}
+
+impl Patterns {
+    /// Picks a line to reflect across, returning the number of rows (or
+    /// columns) before it, along with the index each row is a copy of.
+    fn mirror<R: Rng>(len: usize, rng: &mut R) -> (usize, Vec<usize>) {
+        let axis = rng.gen_range(1..len);
+        let image = (0..len)
+            .map(|i| {
+                // the reflection of i is 2 * axis - 1 - i, if that's in the
+                // pattern
+                let image = (2 * axis).checked_sub(i + 1).filter(|&x| x < len);
+                image.map_or(i, |x| x.min(i))
+            })
+            .collect();
+        (axis, image)
+    }
+
+    fn pattern<R: Rng>(&self, rng: &mut R) -> Vec<Vec<bool>> {
+        let max_size = self.max_size.clamp(7, 32);
+        let (n, m) = (rng.gen_range(7..=max_size), rng.gen_range(7..=max_size));
+
+        // the horizontal line must leave at least one row unreflected, for
+        // the smudge to go in without breaking it
+        let (mut row_axis, mut row_image) = Self::mirror(n, rng);
+        while 2 * row_axis == n {
+            (row_axis, row_image) = Self::mirror(n, rng);
+        }
+        let (_, col_image) = Self::mirror(m, rng);
+
+        let base: Vec<Vec<bool>> = (0..n)
+            .map(|_| (0..m).map(|_| rng.gen_bool(0.5)).collect())
+            .collect();
+        let mut pattern: Vec<Vec<bool>> = (0..n)
+            .map(|i| (0..m).map(|j| base[row_image[i]][col_image[j]]).collect())
+            .collect();
+
+        // a row with no reflection, and a column that has one
+        let unreflected: Vec<usize> = (0..n)
+            .filter(|&i| (2 * row_axis).checked_sub(i + 1).is_none_or(|x| x >= n))
+            .collect();
+        let reflected: Vec<usize> = (0..m).filter(|&j| col_image[j] != j).collect();
+        let i = unreflected[rng.gen_range(0..unreflected.len())];
+        let j = reflected[rng.gen_range(0..reflected.len())];
+        pattern[i][j] = !pattern[i][j];
+
+        if rng.gen_bool(0.5) {
+            (0..m)
+                .map(|j| (0..n).map(|i| pattern[i][j]).collect())
+                .collect()
+        } else {
+            pattern
+        }
+    }
+}
+
+impl Generator for Patterns {
+    fn generate<R: Rng>(&self, rng: &mut R) -> String {
+        (0..self.patterns.max(1))
+            .map(|_| {
+                self.pattern(rng)
+                    .into_iter()
+                    .map(|row| row.into_iter().map(|x| if x { '#' } else { '.' }).collect())
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
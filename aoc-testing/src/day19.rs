@@ -0,0 +1,115 @@
+//! Workflow graphs and parts.
+//!
+//! Workflows only ever send parts to workflows generated after them, so the
+//! graph is acyclic, and `in` is always the first workflow.
+use std::{collections::HashSet, fmt::Write};
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::Generator;
+
+/// Generates a list of workflows, followed by a list of parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Workflows {
+    pub workflows: usize,
+    /// The most conditional rules in a workflow, before its fallback rule.
+    pub max_rules: usize,
+    pub parts: usize,
+}
+
+impl Default for Workflows {
+    fn default() -> Self {
+        Self {
+            workflows: 500,
+            max_rules: 3,
+            parts: 200,
+        }
+    }
+}
+
+impl Workflows {
+    /// Picks `count` distinct workflow names, with `in` first.
+    fn names<R: Rng>(count: usize, rng: &mut R) -> Vec<String> {
+        let mut names = vec!["in".to_string()];
+        let mut seen: HashSet<String> = names.iter().cloned().collect();
+        while names.len() < count {
+            let len = rng.gen_range(2..=4);
+            let name: String = (0..len).map(|_| rng.gen_range('a'..='z')).collect();
+            // A and R are actions, but lowercase names can't collide with them
+            if seen.insert(name.clone()) {
+                names.push(name);
+            }
+        }
+
+        names
+    }
+}
+
+impl Generator for Workflows {
+    fn generate<R: Rng>(&self, rng: &mut R) -> String {
+        let count = self.workflows.max(1);
+        let names = Self::names(count, rng);
+        let mut out = String::default();
+
+        for (i, name) in names.iter().enumerate() {
+            let target = |rng: &mut R| {
+                // lean towards other workflows, so that the graph is deep
+                // rather than mostly accepting or rejecting straight away
+                if i + 1 < count && rng.gen_bool(0.6) {
+                    names[rng.gen_range(i + 1..count)].as_str()
+                } else if rng.gen_bool(0.5) {
+                    "A"
+                } else {
+                    "R"
+                }
+            };
+
+            out.push_str(name);
+            out.push('{');
+            for _ in 0..rng.gen_range(0..=self.max_rules) {
+                let attribute = *b"xmas".choose(rng).expect("not empty") as char;
+                let op = if rng.gen_bool(0.5) { '<' } else { '>' };
+                let threshold = rng.gen_range(1..=4000);
+                let target = target(rng);
+                write!(out, "{}{}{}:{},", attribute, op, threshold, target)
+                    .expect("writing to a string");
+            }
+            out.push_str(target(rng));
+            out.push_str("}\n");
+        }
+
+        out.push('\n');
+        for _ in 0..self.parts {
+            let [x, m, a, s] = [(); 4].map(|_| rng.gen_range(1..=4000));
+            writeln!(out, "{{x={},m={},a={},s={}}}", x, m, a, s).expect("writing to a string");
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_plumbing::Problem;
+    use aplenty::Aplenty;
+
+    use super::*;
+
+    #[test]
+    fn parses_and_solves() {
+        for seed in 0..10 {
+            let input = Workflows::default().generate_seeded(seed);
+            let solution = Aplenty::solve(&input).unwrap();
+            assert!(solution.part_two <= 4000_u64.pow(4));
+            assert!(solution.part_one <= 200 * 4 * 4000);
+        }
+
+        let single = Workflows {
+            workflows: 1,
+            max_rules: 0,
+            parts: 1,
+        };
+        let input = single.generate_seeded(0);
+        assert!(input.starts_with("in{A}\n\n{x=") || input.starts_with("in{R}\n\n{x="));
+    }
+}
@@ -0,0 +1,109 @@
+//! Stacks of sand slabs.
+//!
+//! Slabs are placed at random and thrown away if they overlap one that's
+//! already been placed, so the stack can end up with fewer slabs than asked
+//! for if it's crowded.
+use std::{collections::HashSet, fmt::Write};
+
+use rand::Rng;
+
+use crate::Generator;
+
+/// Generates a snapshot of non-overlapping slabs, in no particular order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlabStack {
+    pub slabs: usize,
+    /// Slabs have x and y coordinates in `0..width`.
+    pub width: i64,
+    /// Slabs have z coordinates in `1..=height`.
+    pub height: i64,
+    /// The most cubes in a slab.
+    pub max_len: i64,
+}
+
+impl Default for SlabStack {
+    fn default() -> Self {
+        Self {
+            slabs: 1000,
+            width: 10,
+            height: 300,
+            max_len: 5,
+        }
+    }
+}
+
+impl Generator for SlabStack {
+    fn generate<R: Rng>(&self, rng: &mut R) -> String {
+        let (width, height) = (self.width.max(1), self.height.max(1));
+        let mut occupied = HashSet::new();
+        let mut out = String::default();
+        let mut placed = 0;
+
+        for _ in 0..self.slabs * 100 {
+            if placed == self.slabs {
+                break;
+            }
+
+            let start = [
+                rng.gen_range(0..width),
+                rng.gen_range(0..width),
+                rng.gen_range(1..=height),
+            ];
+            let axis = rng.gen_range(0..3);
+            let limit = [width - 1, width - 1, height][axis];
+            let len = rng.gen_range(1..=self.max_len.max(1));
+            let mut end = start;
+            end[axis] = (start[axis] + len - 1).min(limit);
+
+            let cubes: Vec<_> = (start[axis]..=end[axis])
+                .map(|x| {
+                    let mut cube = start;
+                    cube[axis] = x;
+                    cube
+                })
+                .collect();
+            if cubes.iter().any(|c| occupied.contains(c)) {
+                continue;
+            }
+
+            occupied.extend(cubes);
+            placed += 1;
+            writeln!(
+                out,
+                "{},{},{}~{},{},{}",
+                start[0], start[1], start[2], end[0], end[1], end[2]
+            )
+            .expect("writing to a string");
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aoc_plumbing::Problem;
+    use sand_slabs::SandSlabs;
+
+    use super::*;
+
+    #[test]
+    fn parses_and_solves() {
+        for seed in 0..5 {
+            let input = SlabStack::default().generate_seeded(seed);
+            assert_eq!(input.lines().count(), 1000);
+
+            let solution = SandSlabs::solve(&input).unwrap();
+            assert!(solution.part_one <= 1000);
+        }
+
+        // too crowded to fit everything
+        let crowded = SlabStack {
+            slabs: 100,
+            width: 2,
+            height: 5,
+            max_len: 2,
+        };
+        assert!(crowded.generate_seeded(0).lines().count() <= 20);
+    }
+}
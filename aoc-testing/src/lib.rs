@@ -0,0 +1,44 @@
+//! Seeded generators of random, valid inputs for individual days.
+//!
+//! The real inputs are few and all roughly the same size, which makes them a
+//! poor way to compare two approaches to the same day or to see how one
+//! scales. Every generator here produces inputs in the same format as the
+//! real ones, and the same seed always produces the same input, so a failing
+//! case can be reproduced from its seed alone.
+//!
+//! ```
+//! use aoc_testing::{day22::SlabStack, Generator};
+//!
+//! let stack = SlabStack {
+//!     slabs: 10,
+//!     ..Default::default()
+//! };
+//! assert_eq!(stack.generate_seeded(7).lines().count(), 10);
+//! assert_eq!(stack.generate_seeded(7), stack.generate_seeded(7));
+//! ```
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+pub use rand;
+
+pub mod day05;
+pub mod day10;
+pub mod day13;
+pub mod day19;
+pub mod day22;
+
+/// Something that produces random inputs for a day.
+pub trait Generator {
+    /// Generates an input using `rng` as the only source of randomness.
+    fn generate<R: Rng>(&self, rng: &mut R) -> String;
+
+    /// Generates the input for `seed`.
+    fn generate_seeded(&self, seed: u64) -> String {
+        self.generate(&mut rng(seed))
+    }
+}
+
+/// The generator used by [Generator::generate_seeded]. This is fixed so that
+/// seeds stay reproducible.
+pub fn rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
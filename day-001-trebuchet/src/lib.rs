@@ -1,20 +1,22 @@
-use std::str::FromStr;
+use std::{collections::VecDeque, ops::Range, str::FromStr, sync::Arc};
 
 use anyhow::{anyhow, Ok, Result};
 use aoc_plumbing::Problem;
+use rayon::prelude::*;
 
+/// A line of the input, stored as its byte range to avoid copying it.
 #[derive(Debug, Clone)]
 struct Calibration {
-    text: String,
+    range: Range<usize>,
 }
 
 impl Calibration {
-    const WORDS: [&'static str; 9] = [
-        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
-    ];
+    fn text<'a>(&self, input: &'a str) -> &'a str {
+        &input[self.range.clone()]
+    }
 
-    fn recover(&self) -> Result<u32> {
-        let mut iter = self.text.chars();
+    fn recover(&self, input: &str) -> Result<u32> {
+        let mut iter = self.text(input).chars();
 
         let first_digit = iter
             .find_map(|c| c.to_digit(10))
@@ -27,57 +29,173 @@ impl Calibration {
         Ok(first_digit * 10 + last_digit)
     }
 
-    fn recover_enhanced(&self) -> u32 {
-        let mut first = 0;
-        'outer: for i in 0..self.text.len() {
-            for j in 0..Self::WORDS.len() {
-                let word = Self::WORDS[j];
-                if self.text.as_bytes()[i] == (j + 49) as u8 || self.text[i..].starts_with(word) {
-                    first = j + 1;
-                    break 'outer;
-                }
+    fn recover_enhanced(&self, input: &str, matcher: &DigitMatcher) -> u32 {
+        matcher
+            .first_and_last(self.text(input))
+            .map(|(first, last)| (first * 10 + last) as u32)
+            .unwrap_or_default()
+    }
+}
+
+/// What happens when the end of one word is the start of another, as in
+/// "twone".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overlap {
+    /// Words can share letters, so "twone" is a two and a one. This is how
+    /// the puzzle works.
+    #[default]
+    Shared,
+    /// A match uses up its letters, so "twone" is just a two.
+    Consumed,
+}
+
+/// An Aho-Corasick automaton that finds both spelled out and plain digits.
+///
+/// Every state has a transition for every byte, so a line is scanned once,
+/// front to back, with one table lookup per byte.
+#[derive(Debug, Clone)]
+pub struct DigitMatcher {
+    transitions: Vec<[u16; 256]>,
+    // the digit matched on reaching each state, or NONE if there isn't one
+    outputs: Vec<u8>,
+    overlap: Overlap,
+}
+
+impl Default for DigitMatcher {
+    /// Matches the English words for one to nine, which may overlap.
+    fn default() -> Self {
+        Self::new(Self::ENGLISH.into_iter().zip(1..), Overlap::default())
+    }
+}
+
+impl DigitMatcher {
+    pub const ENGLISH: [&'static str; 9] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+
+    const NONE: u8 = u8::MAX;
+
+    /// Builds a matcher for the given words and the digits they stand for.
+    /// The plain digits `0` to `9` are always matched.
+    pub fn new<'a>(words: impl IntoIterator<Item = (&'a str, u8)>, overlap: Overlap) -> Self {
+        const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+        // nothing transitions back into the root while building the trie, so
+        // 0 doubles as "no transition"
+        let mut transitions = vec![[0_u16; 256]];
+        let mut outputs = vec![Self::NONE];
+
+        for (pattern, digit) in words.into_iter().chain(DIGITS.into_iter().zip(0..)) {
+            let mut state = 0;
+            for b in pattern.bytes() {
+                let next = transitions[state][b as usize] as usize;
+                state = if next == 0 {
+                    transitions.push([0; 256]);
+                    outputs.push(Self::NONE);
+                    let new = transitions.len() - 1;
+                    transitions[state][b as usize] = new as u16;
+                    new
+                } else {
+                    next
+                };
             }
+            outputs[state] = digit;
         }
 
-        let mut last = 0;
-        'outer: for i in (0..self.text.len()).rev() {
-            for j in 0..Self::WORDS.len() {
-                let word = Self::WORDS[j];
-                if self.text.as_bytes()[i] == (j + 49) as u8 || self.text[i..].starts_with(word) {
-                    last = j + 1;
-                    break 'outer;
+        // fill in the missing transitions breadth first, so the failure state
+        // of every state is complete before the state itself is visited
+        let mut fail = vec![0; transitions.len()];
+        let mut queue = VecDeque::from_iter(transitions[0].iter().filter(|s| **s != 0).copied());
+        while let Some(state) = queue.pop_front() {
+            let state = state as usize;
+            if outputs[state] == Self::NONE {
+                outputs[state] = outputs[fail[state]];
+            }
+
+            let fallbacks = transitions[fail[state]];
+            for (next, fallback) in transitions[state].iter_mut().zip(fallbacks) {
+                if *next == 0 {
+                    *next = fallback;
+                } else {
+                    fail[*next as usize] = fallback as usize;
+                    queue.push_back(*next);
                 }
             }
         }
 
-        (first * 10 + last) as u32
+        Self {
+            transitions,
+            outputs,
+            overlap,
+        }
+    }
+
+    /// Returns every digit in `text`, in order.
+    pub fn digits(&self, text: &str) -> Vec<u8> {
+        let mut ret = Vec::default();
+        self.scan(text, |digit| ret.push(digit));
+        ret
+    }
+
+    /// Returns the first and last digits in `text`, if there are any.
+    ///
+    /// Matches are ordered by where they end, which is the same as where
+    /// they start as long as no word contains another.
+    pub fn first_and_last(&self, text: &str) -> Option<(u8, u8)> {
+        let mut ret = None;
+        self.scan(text, |digit| {
+            ret = match ret {
+                Some((first, _)) => Some((first, digit)),
+                None => Some((digit, digit)),
+            };
+        });
+        ret
+    }
+
+    fn scan(&self, text: &str, mut found: impl FnMut(u8)) {
+        let mut state = 0;
+
+        for b in text.bytes() {
+            state = self.transitions[state][b as usize] as usize;
+            let digit = self.outputs[state];
+            if digit != Self::NONE {
+                found(digit);
+
+                if self.overlap == Overlap::Consumed {
+                    state = 0;
+                }
+            }
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Trebuchet {
+    input: Arc<str>,
     calibrations: Vec<Calibration>,
+    matcher: DigitMatcher,
 }
 
 impl Trebuchet {
     fn recover(&self) -> Result<u32> {
-        let mut ret = 0;
-
-        for calibration in &self.calibrations {
-            ret += calibration.recover()?;
-        }
-
-        Ok(ret)
+        self.calibrations
+            .par_iter()
+            .map(|c| c.recover(&self.input))
+            .sum()
     }
 
     fn recover_enhanced(&self) -> Result<u32> {
-        let mut ret = 0;
-
-        for calibration in &self.calibrations {
-            ret += calibration.recover_enhanced();
-        }
+        Ok(self.recover_with(&self.matcher))
+    }
 
-        Ok(ret)
+    /// Like part two, but finds the digits with the given matcher, e.g. one
+    /// that knows other words or doesn't allow them to overlap. Lines without
+    /// any digits count as 0.
+    pub fn recover_with(&self, matcher: &DigitMatcher) -> u32 {
+        self.calibrations
+            .par_iter()
+            .map(|c| c.recover_enhanced(&self.input, matcher))
+            .sum()
     }
 }
 
@@ -85,39 +203,48 @@ impl FromStr for Trebuchet {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let calibrations = s
+        let input: Arc<str> = Arc::from(s);
+        let base = input.as_ptr() as usize;
+        let calibrations = input
             .lines()
-            .map(|line| Calibration {
-                text: line.to_owned(),
+            .map(|line| {
+                let start = line.as_ptr() as usize - base;
+                Calibration {
+                    range: start..start + line.len(),
+                }
             })
             .collect();
-        Ok(Self { calibrations })
+        Ok(Self {
+            input,
+            calibrations,
+            matcher: DigitMatcher::default(),
+        })
     }
 }
 
 impl Problem for Trebuchet {
+    const YEAR: usize = 2023;
     const DAY: usize = 1;
     const TITLE: &'static str = "trebuchet";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = u32;
-    type P2 = u32;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        self.recover()
+        self.recover().map(u64::from)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        self.recover_enhanced()
+        self.recover_enhanced().map(u64::from)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::Solution;
 
     #[test]
     #[ignore]
@@ -137,6 +264,82 @@ treb7uchet";
         assert_eq!(solution, Solution::new(142, 142));
     }
 
+    #[test]
+    fn digit_matcher() {
+        let matcher = DigitMatcher::default();
+        assert_eq!(matcher.first_and_last("two1nine"), Some((2, 9)));
+        assert_eq!(matcher.first_and_last("eightwo"), Some((8, 2)));
+        assert_eq!(matcher.first_and_last("oneight"), Some((1, 8)));
+        assert_eq!(matcher.first_and_last("sevenine"), Some((7, 9)));
+        // a failed partial match has to fall back onto a suffix of itself
+        assert_eq!(matcher.first_and_last("ninine"), Some((9, 9)));
+        assert_eq!(matcher.first_and_last("fonefivx"), Some((1, 1)));
+        assert_eq!(matcher.first_and_last("xx7xx"), Some((7, 7)));
+        assert_eq!(matcher.first_and_last("tw thre"), None);
+        assert_eq!(matcher.first_and_last("zer0"), Some((0, 0)));
+        assert_eq!(matcher.first_and_last(""), None);
+    }
+
+    #[test]
+    fn overlap_policies() {
+        let shared = DigitMatcher::default();
+        let consumed = DigitMatcher::new(
+            DigitMatcher::ENGLISH.into_iter().zip(1..),
+            Overlap::Consumed,
+        );
+
+        let cases: [(&str, &[u8], &[u8]); 8] = [
+            ("twone", &[2, 1], &[2]),
+            ("oneight", &[1, 8], &[1]),
+            ("eightwo", &[8, 2], &[8]),
+            ("eighthree", &[8, 3], &[8]),
+            ("sevenine", &[7, 9], &[7]),
+            ("nineight", &[9, 8], &[9]),
+            ("twoneight", &[2, 1, 8], &[2, 8]),
+            ("xtwone3four", &[2, 1, 3, 4], &[2, 3, 4]),
+        ];
+        for (text, expected_shared, expected_consumed) in cases {
+            assert_eq!(shared.digits(text), expected_shared, "{}", text);
+            assert_eq!(consumed.digits(text), expected_consumed, "{}", text);
+        }
+    }
+
+    #[test]
+    fn configured_words() {
+        let input = "zero1nine
+oneight
+nulleinsacht
+7";
+        let instance = Trebuchet::instance(input).unwrap();
+        // zero isn't a word by default, and neither line three word is
+        assert_eq!(
+            instance.recover_with(&DigitMatcher::default()),
+            19 + 18 + 77
+        );
+
+        let with_zero = DigitMatcher::new(
+            DigitMatcher::ENGLISH
+                .into_iter()
+                .zip(1..)
+                .chain([("zero", 0)]),
+            Overlap::Shared,
+        );
+        assert_eq!(instance.recover_with(&with_zero), 9 + 18 + 77);
+
+        let consumed = DigitMatcher::new(
+            DigitMatcher::ENGLISH.into_iter().zip(1..),
+            Overlap::Consumed,
+        );
+        assert_eq!(instance.recover_with(&consumed), 19 + 11 + 77);
+
+        let german = [
+            "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+        ];
+        let german = DigitMatcher::new(german.into_iter().zip(0..), Overlap::Shared);
+        assert_eq!(german.first_and_last("fünfzig"), Some((5, 5)));
+        assert_eq!(instance.recover_with(&german), 11 + 8 + 77);
+    }
+
     #[test]
     fn example_2() {
         let input = "two1nine
@@ -149,4 +352,9 @@ zoneight234
         let mut instance = Trebuchet::instance(input).unwrap();
         assert_eq!(instance.part_two().unwrap(), 281);
     }
+
+    aoc_plumbing::parsing_never_panics!(
+        Trebuchet,
+        "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet\ntwo1nine\nzoneight234"
+    );
 }
@@ -1,16 +1,33 @@
-use std::str::FromStr;
+use std::{collections::BTreeMap, fmt, str::FromStr};
 
-use anyhow::{bail, Result};
-use aoc_plumbing::Problem;
+use anyhow::{anyhow, bail, Result};
+use aoc_plumbing::{incremental::Incremental, Problem};
 
-#[derive(Debug, Clone, Default)]
-struct CubeSet {
-    red: usize,
-    green: usize,
-    blue: usize,
+/// A count of cubes of each color.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CubeSet {
+    pub red: usize,
+    pub green: usize,
+    pub blue: usize,
 }
 
 impl CubeSet {
+    /// The bag part one asks about.
+    pub const BAG: Self = Self::new(12, 13, 14);
+
+    pub const fn new(red: usize, green: usize, blue: usize) -> Self {
+        Self { red, green, blue }
+    }
+
+    /// Whether every color fits within `limits`.
+    pub fn fits_within(&self, limits: &Self) -> bool {
+        self.red <= limits.red && self.green <= limits.green && self.blue <= limits.blue
+    }
+
+    pub fn power(&self) -> usize {
+        self.red * self.green * self.blue
+    }
+
     fn max_with(&mut self, other: &Self) {
         self.red = self.red.max(other.red);
         self.green = self.green.max(other.green);
@@ -44,55 +61,127 @@ impl FromStr for CubeSet {
 #[derive(Debug, Clone)]
 struct Game {
     id: usize,
+    // 1-based line the game was read from, for reporting
+    line: usize,
     max_cube_set: CubeSet,
 }
 
-impl Game {
-    fn is_possible(&self, red: usize, green: usize, blue: usize) -> bool {
-        self.max_cube_set.red <= red
-            && self.max_cube_set.green <= green
-            && self.max_cube_set.blue <= blue
-    }
-
-    fn minimum_set_power(&self) -> usize {
-        self.max_cube_set.red * self.max_cube_set.green * self.max_cube_set.blue
-    }
-}
-
 impl FromStr for Game {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
         if let Some((left, right)) = s.split_once(": ") {
-            let id = left[5..].parse()?;
+            let id = left
+                .strip_prefix("Game ")
+                .ok_or_else(|| anyhow!("expected a game id, got {:?}", left))?
+                .parse()?;
             let mut max_cube_set = CubeSet::default();
 
             for token in right.split("; ") {
                 max_cube_set.max_with(&CubeSet::from_str(token)?);
             }
 
-            Ok(Game { id, max_cube_set })
+            Ok(Game {
+                id,
+                line: 0,
+                max_cube_set,
+            })
         } else {
             bail!("could not parse game")
         }
     }
 }
 
+/// Problems with the game ids in the input. None of these stop the input
+/// from being solved, but they usually mean the input was concatenated or
+/// shuffled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdWarning {
+    Duplicate { id: usize, lines: Vec<usize> },
+    Missing { id: usize },
+    OutOfOrder { id: usize, line: usize },
+}
+
+impl fmt::Display for IdWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duplicate { id, lines } => {
+                write!(f, "game {} appears on multiple lines: {:?}", id, lines)
+            }
+            Self::Missing { id } => write!(f, "game {} is missing", id),
+            Self::OutOfOrder { id, line } => {
+                write!(f, "game {} on line {} is out of order", id, line)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CubeConundrum {
+    // sorted by id, ties in input order
     games: Vec<Game>,
 }
 
 impl CubeConundrum {
-    fn possible_ids_sum(&self, red: usize, green: usize, blue: usize) -> usize {
+    /// Sums the ids of the games that could have been played with a bag
+    /// holding `limits`.
+    pub fn possible_ids_sum(&self, limits: &CubeSet) -> usize {
         self.games
             .iter()
-            .filter_map(|x| x.is_possible(red, green, blue).then_some(x.id))
+            .filter_map(|x| x.max_cube_set.fits_within(limits).then_some(x.id))
             .sum()
     }
 
+    /// Returns, for each game by id, the fewest cubes of each color the bag
+    /// must have held.
+    pub fn minimum_sets(&self) -> Vec<(usize, CubeSet)> {
+        self.games.iter().map(|x| (x.id, x.max_cube_set)).collect()
+    }
+
     fn minimum_set_power_sum(&self) -> usize {
-        self.games.iter().map(|x| x.minimum_set_power()).sum()
+        self.games.iter().map(|x| x.max_cube_set.power()).sum()
+    }
+
+    /// Checks that the game ids are unique and run from 1 without gaps in
+    /// input order.
+    pub fn verify_ids(&self) -> Vec<IdWarning> {
+        let mut ret = Vec::default();
+
+        let mut by_id: BTreeMap<usize, Vec<usize>> = BTreeMap::default();
+        for game in &self.games {
+            by_id.entry(game.id).or_default().push(game.line);
+        }
+
+        for (id, lines) in by_id.iter() {
+            if lines.len() > 1 {
+                ret.push(IdWarning::Duplicate {
+                    id: *id,
+                    lines: lines.clone(),
+                });
+            }
+        }
+
+        let max = by_id.keys().next_back().copied().unwrap_or_default();
+        for id in 1..=max {
+            if !by_id.contains_key(&id) {
+                ret.push(IdWarning::Missing { id });
+            }
+        }
+
+        let mut in_input_order = self.games.iter().collect::<Vec<_>>();
+        in_input_order.sort_by_key(|g| g.line);
+        let mut prev = 0;
+        for game in in_input_order {
+            if game.id < prev {
+                ret.push(IdWarning::OutOfOrder {
+                    id: game.id,
+                    line: game.line,
+                });
+            }
+            prev = prev.max(game.id);
+        }
+
+        ret
     }
 }
 
@@ -100,37 +189,68 @@ impl FromStr for CubeConundrum {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let games = s
+        let mut games = s
             .lines()
-            .map(Game::from_str)
+            .enumerate()
+            .map(|(i, line)| {
+                let mut game = Game::from_str(line)?;
+                game.line = i + 1;
+                Ok(game)
+            })
             .collect::<Result<Vec<Game>>>()?;
+        // results are keyed on the parsed id, so the order of the input
+        // doesn't matter
+        games.sort_by_key(|g| g.id);
         Ok(Self { games })
     }
 }
 
 impl Problem for CubeConundrum {
+    const YEAR: usize = 2023;
     const DAY: usize = 2;
     const TITLE: &'static str = "cube conundrum";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.possible_ids_sum(12, 13, 14))
+        Ok(self.possible_ids_sum(&CubeSet::BAG) as u64)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        Ok(self.minimum_set_power_sum())
+        Ok(self.minimum_set_power_sum() as u64)
+    }
+}
+
+impl Incremental for CubeConundrum {
+    /// Re-parses just the changed game, moving it if its id changed.
+    fn update(&mut self, changed_line: usize, new_line: &str) -> Result<()> {
+        let line = changed_line + 1;
+        let idx = self
+            .games
+            .iter()
+            .position(|g| g.line == line)
+            .ok_or_else(|| anyhow!("there is no line {}", line))?;
+
+        let mut game = Game::from_str(new_line)?;
+        game.line = line;
+        self.games.remove(idx);
+        // after any games with the same id that come earlier in the input
+        let at = self
+            .games
+            .partition_point(|g| (g.id, g.line) < (game.id, game.line));
+        self.games.insert(at, game);
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::{incremental::assert_updates_agree, Solution};
 
     #[test]
     fn full_dataset() {
@@ -149,4 +269,116 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
         let solution = CubeConundrum::solve(input).unwrap();
         assert_eq!(solution, Solution::new(8, 2286));
     }
+
+    #[test]
+    fn limits_and_minimum_sets() {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        let instance = CubeConundrum::instance(input).unwrap();
+
+        assert_eq!(instance.possible_ids_sum(&CubeSet::BAG), 8);
+        assert_eq!(instance.possible_ids_sum(&CubeSet::new(20, 13, 15)), 15);
+        assert_eq!(instance.possible_ids_sum(&CubeSet::default()), 0);
+
+        assert_eq!(
+            instance.minimum_sets(),
+            vec![
+                (1, CubeSet::new(4, 2, 6)),
+                (2, CubeSet::new(1, 3, 4)),
+                (3, CubeSet::new(20, 13, 6)),
+                (4, CubeSet::new(14, 3, 15)),
+                (5, CubeSet::new(6, 3, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn verify_ids() {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        assert!(CubeConundrum::instance(input)
+            .unwrap()
+            .verify_ids()
+            .is_empty());
+
+        let shuffled = "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red";
+        let instance = CubeConundrum::instance(shuffled).unwrap();
+        assert_eq!(
+            instance.verify_ids(),
+            vec![
+                IdWarning::OutOfOrder { id: 2, line: 2 },
+                IdWarning::OutOfOrder { id: 1, line: 3 },
+                IdWarning::OutOfOrder { id: 3, line: 4 },
+                IdWarning::OutOfOrder { id: 4, line: 5 },
+            ]
+        );
+        assert_eq!(
+            CubeConundrum::solve(shuffled).unwrap(),
+            Solution::new(8, 2286)
+        );
+
+        let concatenated = "Game 1: 3 blue, 4 red
+Game 3: 1 blue, 2 green
+Game 1: 8 green, 6 blue, 20 red";
+        assert_eq!(
+            CubeConundrum::instance(concatenated).unwrap().verify_ids(),
+            vec![
+                IdWarning::Duplicate {
+                    id: 1,
+                    lines: vec![1, 3]
+                },
+                IdWarning::Missing { id: 2 },
+                IdWarning::OutOfOrder { id: 1, line: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    fn updates() {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        assert_updates_agree::<CubeConundrum>(
+            input,
+            &[
+                (2, "Game 3: 8 green, 6 blue, 2 red"),
+                (0, "Game 1: 30 blue"),
+                (4, "Game 2: 1 red"),
+                (1, "Game 2: 1 blue"),
+                (3, "Game 4: 1 grey"),
+                (3, ""),
+            ],
+        );
+
+        let mut instance = CubeConundrum::instance(input).unwrap();
+        instance.update(4, "Game 2: 1 red").unwrap();
+        assert_eq!(
+            instance.verify_ids(),
+            vec![
+                IdWarning::Duplicate {
+                    id: 2,
+                    lines: vec![2, 5]
+                },
+                IdWarning::OutOfOrder { id: 2, line: 5 },
+            ]
+        );
+        assert!(instance.update(5, "Game 6: 1 red").is_err());
+    }
+
+    aoc_plumbing::parsing_never_panics!(
+        CubeConundrum,
+        "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 green; 3 green, 4 blue, 1 red"
+    );
 }
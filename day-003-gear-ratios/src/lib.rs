@@ -11,9 +11,59 @@ struct PartNumber {
     number: usize,
 }
 
+/// Lines longer than this use a per-row index instead of a map from every
+/// digit's coordinate.
+const WIDE_LINE_THRESHOLD: usize = 4096;
+
+/// Finds the part number, if any, covering a given coordinate.
+#[derive(Debug, Clone)]
+enum PartIndex {
+    /// An entry for every digit. Fast, but large for long lines.
+    Map(FxHashMap<Coordinate, PartNumber>),
+    /// The `(start, end, part)` column ranges for each row, sorted by column
+    /// and searched with a binary search.
+    Rows(Vec<Vec<(usize, usize, PartNumber)>>),
+}
+
+impl PartIndex {
+    fn new(parts: Vec<(usize, usize, PartNumber)>, rows: usize, wide: bool) -> Self {
+        if wide {
+            let mut index = vec![Vec::default(); rows];
+            // parts are found in order, so each row is already sorted
+            for part in parts {
+                index[part.2.start.row() as usize].push(part);
+            }
+            Self::Rows(index)
+        } else {
+            let mut index = FxHashMap::default();
+            for (start, end, part) in parts {
+                let row = part.start.row() as usize;
+                for k in start..end {
+                    index.insert((row, k).into(), part);
+                }
+            }
+            Self::Map(index)
+        }
+    }
+
+    fn get(&self, coord: &Coordinate) -> Option<PartNumber> {
+        match self {
+            Self::Map(index) => index.get(coord).copied(),
+            Self::Rows(index) => {
+                let row = index.get(usize::try_from(coord.row()).ok()?)?;
+                let col = usize::try_from(coord.col()).ok()?;
+                let idx = row.partition_point(|(_, end, _)| *end <= col);
+                row.get(idx)
+                    .filter(|(start, _, _)| *start <= col)
+                    .map(|(_, _, part)| *part)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GearRatios {
-    coords_to_part_numbers: FxHashMap<Coordinate, PartNumber>,
+    part_index: PartIndex,
     symbol_coords: FxHashSet<Coordinate>,
     gear_coords: FxHashSet<Coordinate>,
 }
@@ -24,8 +74,8 @@ impl GearRatios {
 
         for coord in &self.symbol_coords {
             for neighbour in coord.neighbours() {
-                if let Some(x) = self.coords_to_part_numbers.get(&neighbour) {
-                    part_numbers.insert(*x);
+                if let Some(x) = self.part_index.get(&neighbour) {
+                    part_numbers.insert(x);
                 }
             }
         }
@@ -34,38 +84,45 @@ impl GearRatios {
     }
 
     fn gear_ratios_sum(&self) -> usize {
-        let mut sum = 0;
-
-        for coord in &self.gear_coords {
-            let mut adjacent_parts = FxHashSet::default();
-
-            for neighbour in coord.neighbours() {
-                if let Some(x) = self.coords_to_part_numbers.get(&neighbour) {
-                    adjacent_parts.insert(*x);
-                }
-            }
+        self.gears()
+            .into_iter()
+            .filter(|(_, parts)| parts.len() == 2)
+            .map(|(_, parts)| parts.iter().product::<usize>())
+            .sum()
+    }
 
-            if adjacent_parts.len() == 2 {
-                sum += adjacent_parts
+    /// Returns every `*` symbol, in row-major order, with the part numbers
+    /// adjacent to it ordered by where they start.
+    pub fn gears(&self) -> Vec<(Coordinate, Vec<usize>)> {
+        let mut ret = self
+            .gear_coords
+            .iter()
+            .map(|coord| {
+                let mut adjacent_parts = coord
+                    .neighbours()
+                    .iter()
+                    .filter_map(|n| self.part_index.get(n))
+                    .collect::<FxHashSet<_>>()
                     .into_iter()
-                    .map(|x| x.number)
-                    .product::<usize>();
-            }
-        }
+                    .collect::<Vec<_>>();
+                adjacent_parts.sort_by_key(|p| (p.start.row(), p.start.col()));
 
-        sum
-    }
-}
+                (*coord, adjacent_parts.iter().map(|p| p.number).collect())
+            })
+            .collect::<Vec<_>>();
 
-impl FromStr for GearRatios {
-    type Err = anyhow::Error;
+        ret.sort_by_key(|(coord, _)| (coord.row(), coord.col()));
+        ret
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut coords_to_part_numbers = FxHashMap::default();
+    fn parse(s: &str, wide: bool) -> anyhow::Result<Self> {
+        let mut parts = Vec::default();
+        let mut rows = 0;
         let mut symbol_coords = FxHashSet::default();
         let mut gear_coords = FxHashSet::default();
 
         for (i, line) in s.lines().enumerate() {
+            rows += 1;
             let mut left = 0;
             let mut prev_is_digit = false;
 
@@ -87,9 +144,7 @@ impl FromStr for GearRatios {
                         number,
                     };
 
-                    for k in left..j {
-                        coords_to_part_numbers.insert((i, k).into(), part_number);
-                    }
+                    parts.push((left, j, part_number));
                 }
 
                 if *cur != b'.' {
@@ -110,43 +165,50 @@ impl FromStr for GearRatios {
                     number,
                 };
 
-                for k in left..bytes.len() {
-                    coords_to_part_numbers.insert((i, k).into(), part_number);
-                }
+                parts.push((left, bytes.len(), part_number));
             }
         }
 
         Ok(Self {
-            coords_to_part_numbers,
+            part_index: PartIndex::new(parts, rows, wide),
             symbol_coords,
             gear_coords,
         })
     }
 }
 
+impl FromStr for GearRatios {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wide = s.lines().any(|l| l.len() > WIDE_LINE_THRESHOLD);
+        Self::parse(s, wide)
+    }
+}
+
 impl Problem for GearRatios {
+    const YEAR: usize = 2023;
     const DAY: usize = 3;
     const TITLE: &'static str = "gear ratios";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.part_numbers_sum())
+        Ok(self.part_numbers_sum() as u64)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        Ok(self.gear_ratios_sum())
+        Ok(self.gear_ratios_sum() as u64)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::Solution;
 
     #[test]
     #[ignore]
@@ -163,10 +225,63 @@ mod tests {
         assert_eq!(solution, Solution::new(4361, 467835));
     }
 
+    #[test]
+    fn gears() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = GearRatios::instance(&input).unwrap();
+        assert_eq!(
+            instance.gears(),
+            vec![
+                (Coordinate::new(1, 3), vec![467, 35]),
+                (Coordinate::new(4, 3), vec![617]),
+                (Coordinate::new(8, 5), vec![755, 598]),
+            ]
+        );
+
+        let input = "1.2
+.*.
+3.4";
+        let instance = GearRatios::instance(input).unwrap();
+        assert_eq!(
+            instance.gears(),
+            vec![(Coordinate::new(1, 1), vec![1, 2, 3, 4])]
+        );
+    }
+
     #[test]
     fn example_two() {
         let input = "..#789";
         let solution = GearRatios::solve(input).unwrap();
         assert_eq!(solution, Solution::new(789, 0));
     }
+
+    #[test]
+    fn row_index_matches_map() {
+        let example = std::fs::read_to_string("example.txt").expect("Unable to load input");
+
+        // tile the example horizontally so that the lines are long enough
+        // to pick the row index automatically
+        let copies = WIDE_LINE_THRESHOLD / 10 + 1;
+        let wide = example
+            .lines()
+            .map(|l| format!("{}.", l).repeat(copies))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        for input in [example.as_str(), "..#789", wide.as_str()] {
+            let mut map = GearRatios::parse(input, false).unwrap();
+            let mut rows = GearRatios::parse(input, true).unwrap();
+            assert!(matches!(map.part_index, PartIndex::Map(_)));
+            assert!(matches!(rows.part_index, PartIndex::Rows(_)));
+            assert_eq!(map.part_one().unwrap(), rows.part_one().unwrap());
+            assert_eq!(map.part_two().unwrap(), rows.part_two().unwrap());
+        }
+
+        let mut instance = GearRatios::instance(&wide).unwrap();
+        assert!(matches!(instance.part_index, PartIndex::Rows(_)));
+        assert_eq!(instance.part_one().unwrap(), 4361 * copies as u64);
+        assert_eq!(instance.part_two().unwrap(), 467835 * copies as u64);
+    }
+
+    aoc_plumbing::parsing_never_panics!(GearRatios);
 }
@@ -1,13 +1,17 @@
 use std::str::FromStr;
 
-use anyhow::{bail, Result};
-use aoc_plumbing::Problem;
+use anyhow::{anyhow, bail, Result};
+use aoc_plumbing::{incremental::Incremental, Problem};
 use rustc_hash::FxHashSet;
 
 #[derive(Debug, Clone)]
 struct Card {
     matching_count: usize,
-    points: u32,
+    points: u64,
+    #[cfg(feature = "inspect")]
+    winning_numbers: Vec<u32>,
+    #[cfg(feature = "inspect")]
+    my_numbers: Vec<u32>,
 }
 
 impl FromStr for Card {
@@ -19,20 +23,35 @@ impl FromStr for Card {
                 let winning_numbers = winning_token
                     .split_whitespace()
                     .map(|x| x.parse::<u32>())
-                    .collect::<Result<FxHashSet<u32>, _>>()?;
+                    .collect::<Result<Vec<u32>, _>>()?;
                 let my_numbers = mine_token
                     .split_whitespace()
                     .map(|x| x.parse::<u32>())
-                    .collect::<Result<FxHashSet<u32>, _>>()?;
-                let matching_count = winning_numbers.intersection(&my_numbers).count();
+                    .collect::<Result<Vec<u32>, _>>()?;
+
+                let winning_set = winning_numbers.iter().collect::<FxHashSet<_>>();
+                let matching_count = my_numbers
+                    .iter()
+                    .collect::<FxHashSet<_>>()
+                    .intersection(&winning_set)
+                    .count();
                 let points = match matching_count {
                     0 => 0,
-                    _ => 2_u32.pow((matching_count - 1) as u32),
+                    _ => u32::try_from(matching_count - 1)
+                        .ok()
+                        .and_then(|exp| 2_u64.checked_pow(exp))
+                        .ok_or_else(|| {
+                            anyhow!("{} matches is worth too many points", matching_count)
+                        })?,
                 };
 
                 return Ok(Self {
                     matching_count,
                     points,
+                    #[cfg(feature = "inspect")]
+                    winning_numbers,
+                    #[cfg(feature = "inspect")]
+                    my_numbers,
                 });
             }
         }
@@ -60,51 +79,94 @@ impl FromStr for Scratchcards {
 }
 
 impl Scratchcards {
-    fn total_points(&self) -> u32 {
-        self.cards.iter().map(|x| x.points).sum()
+    fn total_points(&self) -> Result<u64> {
+        self.cards
+            .iter()
+            .try_fold(0_u64, |acc, x| acc.checked_add(x.points))
+            .ok_or_else(|| anyhow!("total points overflowed"))
+    }
+
+    fn total_copies(&self) -> Result<u64> {
+        self.copies()?
+            .into_iter()
+            .try_fold(0_u64, |acc, x| acc.checked_add(x))
+            .ok_or_else(|| anyhow!("total copies overflowed"))
     }
 
-    fn total_copies(&self) -> u32 {
-        let mut copies = vec![1; self.cards.len()];
-        let mut count = 0;
+    /// Returns the number of copies of each card we end up with, including
+    /// the original, in input order.
+    pub fn copies(&self) -> Result<Vec<u64>> {
+        let mut copies = vec![1_u64; self.cards.len()];
 
         for i in 0..self.cards.len() {
             let card = &self.cards[i];
 
-            for j in (i + 1)..(i + card.matching_count + 1) {
-                copies[j] += copies[i];
+            // matches past the end of the table don't win anything
+            let end = (i + card.matching_count + 1).min(self.cards.len());
+            for j in (i + 1)..end {
+                copies[j] = copies[j]
+                    .checked_add(copies[i])
+                    .ok_or_else(|| anyhow!("copies of card {} overflowed", j + 1))?;
             }
-
-            count += copies[i];
         }
 
-        count
+        Ok(copies)
+    }
+
+    /// Returns how many of our numbers are winning numbers on each card, in
+    /// input order.
+    pub fn matching_counts(&self) -> Vec<usize> {
+        self.cards.iter().map(|x| x.matching_count).collect()
+    }
+
+    /// Returns the winning numbers and the numbers we have for the card at
+    /// `idx` (0-based), in input order.
+    #[cfg(feature = "inspect")]
+    pub fn numbers(&self, idx: usize) -> Option<(&[u32], &[u32])> {
+        self.cards
+            .get(idx)
+            .map(|card| (card.winning_numbers.as_slice(), card.my_numbers.as_slice()))
     }
 }
 
 impl Problem for Scratchcards {
+    const YEAR: usize = 2023;
     const DAY: usize = 4;
     const TITLE: &'static str = "scratchcards";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = u32;
-    type P2 = u32;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.total_points())
+        self.total_points()
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        Ok(self.total_copies())
+        self.total_copies()
+    }
+}
+
+impl Incremental for Scratchcards {
+    /// Re-parses just the changed card. The totals only add up what was
+    /// worked out for each card while parsing, so there's nothing else to
+    /// redo.
+    fn update(&mut self, changed_line: usize, new_line: &str) -> Result<()> {
+        let card = self
+            .cards
+            .get_mut(changed_line)
+            .ok_or_else(|| anyhow!("there is no line {}", changed_line + 1))?;
+        *card = Card::from_str(new_line)?;
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::{incremental::assert_updates_agree, Solution};
 
     #[test]
     #[ignore]
@@ -120,4 +182,91 @@ mod tests {
         let solution = Scratchcards::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(13, 30));
     }
+
+    #[test]
+    fn copies() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = Scratchcards::instance(&input).unwrap();
+        assert_eq!(instance.matching_counts(), vec![4, 2, 2, 1, 0, 0]);
+        assert_eq!(instance.copies().unwrap(), vec![1, 2, 4, 8, 14, 1]);
+    }
+
+    fn synthetic_card(id: usize, matches: usize, extra: usize) -> String {
+        let winning = (1..=matches + extra)
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mine = (1..=matches)
+            .chain(1000..1000 + extra)
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!("Card {}: {} | {}", id, winning, mine)
+    }
+
+    #[test]
+    fn oversized_cards() {
+        // 40 matches is more than a u32 can hold
+        let input = [
+            synthetic_card(1, 40, 200),
+            synthetic_card(2, 64, 0),
+            synthetic_card(3, 0, 500),
+        ]
+        .join("\n");
+        let solution = Scratchcards::solve(&input).unwrap();
+        // the first card's copies run off the end of the table
+        assert_eq!(solution, Solution::new((1 << 39) + (1 << 63), 1 + 2 + 4));
+
+        // 65 matches is more than a u64 can hold
+        let input = synthetic_card(1, 65, 0);
+        assert!(Scratchcards::instance(&input).is_err());
+
+        // as are two cards with 64 matches
+        let input = [synthetic_card(1, 64, 0), synthetic_card(2, 64, 0)].join("\n");
+        assert!(Scratchcards::solve(&input).is_err());
+
+        // and 2^64 copies of one card
+        let mut cards = (1..=64)
+            .map(|i| synthetic_card(i, 64, 0))
+            .collect::<Vec<_>>();
+        cards.push(synthetic_card(65, 0, 0));
+        let instance = Scratchcards::instance(&cards.join("\n")).unwrap();
+        assert_eq!(
+            instance.copies().unwrap_err().to_string(),
+            "copies of card 65 overflowed"
+        );
+    }
+
+    #[test]
+    fn updates() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        assert_updates_agree::<Scratchcards>(
+            &input,
+            &[
+                (0, &synthetic_card(1, 0, 3)),
+                (2, &synthetic_card(3, 5, 0)),
+                (5, &synthetic_card(6, 2, 1)),
+                (1, "Card 2: 1 2 |"),
+                (3, "Card 4: 1 2 | x"),
+                (6, &synthetic_card(7, 1, 0)),
+            ],
+        );
+    }
+
+    #[cfg(feature = "inspect")]
+    #[test]
+    fn numbers() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = Scratchcards::instance(&input).unwrap();
+        assert_eq!(
+            instance.numbers(0),
+            Some((
+                [41, 48, 83, 86, 17].as_slice(),
+                [83, 86, 6, 31, 17, 9, 48, 53].as_slice()
+            ))
+        );
+        assert_eq!(instance.numbers(6), None);
+    }
+
+    aoc_plumbing::parsing_never_panics!(Scratchcards);
 }
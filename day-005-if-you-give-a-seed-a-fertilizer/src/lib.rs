@@ -1,7 +1,13 @@
 use std::str::FromStr;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
+use aoc_common::interval::Interval;
 use aoc_plumbing::Problem;
+use rayon::prelude::*;
+
+/// Stages with more seed ranges than this are mapped in parallel, in chunks
+/// of this size.
+const PAR_CHUNK: usize = 1024;
 
 #[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
 struct Mapping {
@@ -23,13 +29,17 @@ impl Mapping {
     /// Returns a mapped destination value regardless if the source value falls with the mapping
     /// range
     fn map(&self, source_value: usize, check_range: bool) -> Option<usize> {
-        if !check_range || (source_value >= self.source && source_value < self.source + self.length)
-        {
+        if !check_range || self.source_range().contains(source_value) {
             Some(source_value - self.source + self.destination)
         } else {
             None
         }
     }
+
+    /// The values this mapping applies to.
+    fn source_range(&self) -> Interval<usize> {
+        Interval::new(self.source, self.source + self.length)
+    }
 }
 
 impl FromStr for Mapping {
@@ -40,14 +50,40 @@ impl FromStr for Mapping {
             .splitn(3, ' ')
             .map(|x| x.parse::<usize>())
             .collect::<Result<Vec<usize>, _>>()?;
+        let [destination, source, length] = tokens[..] else {
+            bail!("expected 3 numbers in a mapping, got {:?}", s);
+        };
         Ok(Mapping {
-            destination: tokens[0],
-            source: tokens[1],
-            length: tokens[2],
+            destination,
+            source,
+            length,
         })
     }
 }
 
+/// A half-open range of values at some mapping layer, along with the seed
+/// that maps to its start. Since mappings only ever shift ranges, the seed for
+/// any value in the range is at the same offset from `seed`.
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, PartialEq, Eq)]
+struct SeedRange {
+    values: Interval<usize>,
+    seed: usize,
+}
+
+impl SeedRange {
+    fn new(start: usize, end: usize, seed: usize) -> Self {
+        Self {
+            values: Interval::new(start, end),
+            seed,
+        }
+    }
+
+    /// The sub range `[s, e)`, which must be within this range.
+    fn slice(&self, s: usize, e: usize) -> Self {
+        Self::new(s, e, self.seed + (s - self.values.start()))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct IfYouGiveASeedAFertilizer {
     seeds: Vec<usize>,
@@ -77,42 +113,46 @@ impl IfYouGiveASeedAFertilizer {
 
     /// Give a list of seed ranges, and a list of mappings, return a list of mapped seed ranges
     ///
-    /// Both inputs and the outputs are disjoint and sorted
-    fn map_seeds(seed_ranges: &[(usize, usize)], mappings: &[Mapping]) -> Vec<(usize, usize)> {
+    /// The mappings must be sorted and disjoint. The seed ranges may overlap,
+    /// as can the mapped ranges when two mappings share a destination.
+    fn map_seeds(seed_ranges: &[SeedRange], mappings: &[Mapping]) -> Vec<SeedRange> {
         let mut ret = Vec::default();
-        let mut j = 0;
 
         for seed_range in seed_ranges {
-            let &(mut s, e) = seed_range;
+            let (mut s, e) = (seed_range.values.start(), seed_range.values.end());
+            let mut j = mappings.partition_point(|x| x.source_range().less_than(s));
 
             while s < e {
-                while j < mappings.len() && mappings[j].source + mappings[j].length <= s {
+                while j < mappings.len() && mappings[j].source_range().less_than(s) {
                     j += 1;
                 }
 
                 if j >= mappings.len() {
                     // we saw all the mappings, so this range won't get mapped
-                    ret.push((s, e));
+                    ret.push(seed_range.slice(s, e));
                     break;
                 }
 
                 let mapping = &mappings[j];
-                let (ms, me) = (mapping.source, mapping.source + mapping.length);
+                let source = mapping.source_range();
+                let (ms, me) = (source.start(), source.end());
 
                 if ms >= e {
                     // segment is below the mapping range
-                    ret.push((s, e));
+                    ret.push(seed_range.slice(s, e));
                     break;
                 }
 
                 if ms > s {
-                    ret.push((s, ms));
+                    ret.push(seed_range.slice(s, ms));
                 }
 
                 // we can unwrap because its guaranteed to be in mapping range
-                ret.push((
-                    mapping.map(s.max(ms), false).unwrap(),
-                    mapping.map(e.min(me), false).unwrap(),
+                let (a, b) = (s.max(ms), e.min(me));
+                ret.push(SeedRange::new(
+                    mapping.map(a, false).unwrap(),
+                    mapping.map(b, false).unwrap(),
+                    seed_range.slice(a, b).seed,
                 ));
 
                 s = me;
@@ -122,20 +162,66 @@ impl IfYouGiveASeedAFertilizer {
         ret
     }
 
-    fn min_location_with_seed_ranges(&mut self) -> usize {
-        let mut seed_ranges: Vec<_> = self.seeds.chunks(2).map(|x| (x[0], x[0] + x[1])).collect();
+    /// Like [`Self::map_seeds`], but splits the seed ranges into chunks that
+    /// are mapped in parallel and then merged back into sorted order. Small
+    /// stages aren't worth the overhead, so are mapped sequentially.
+    fn par_map_seeds(seed_ranges: &[SeedRange], mappings: &[Mapping]) -> Vec<SeedRange> {
+        if seed_ranges.len() <= PAR_CHUNK {
+            let mut ret = Self::map_seeds(seed_ranges, mappings);
+            ret.sort();
+            return ret;
+        }
+
+        let mut ret: Vec<_> = seed_ranges
+            .par_chunks(PAR_CHUNK)
+            .flat_map_iter(|chunk| Self::map_seeds(chunk, mappings))
+            .collect();
+        ret.par_sort_unstable();
+        ret
+    }
+
+    /// Returns the `(seed, location)` pair with the lowest location, treating
+    /// the seeds as ranges.
+    pub fn min_location_witness(&self) -> Option<(usize, usize)> {
+        self.witness(Self::par_map_seeds)
+    }
+
+    /// [`Self::min_location_witness`], but mapping every stage on a single
+    /// thread.
+    pub fn min_location_witness_sequential(&self) -> Option<(usize, usize)> {
+        self.witness(|seed_ranges, mappings| {
+            let mut ret = Self::map_seeds(seed_ranges, mappings);
+            ret.sort();
+            ret
+        })
+    }
+
+    /// Pushes the seed ranges through every stage with `map`, which must
+    /// return the mapped ranges sorted.
+    fn witness(
+        &self,
+        map: impl Fn(&[SeedRange], &[Mapping]) -> Vec<SeedRange>,
+    ) -> Option<(usize, usize)> {
+        let mut seed_ranges: Vec<_> = self
+            .seeds
+            .chunks_exact(2)
+            .map(|x| SeedRange::new(x[0], x[0] + x[1], x[0]))
+            .collect();
         seed_ranges.sort();
 
-        for mapping_group in &mut self.mappings {
-            mapping_group.sort();
-            seed_ranges = Self::map_seeds(&seed_ranges, mapping_group);
-            seed_ranges.sort();
+        for mapping_group in &self.mappings {
+            seed_ranges = map(&seed_ranges, mapping_group);
         }
 
         seed_ranges
             .iter()
-            .min()
-            .map(|(s, _)| *s)
+            .find(|x| !x.values.is_empty())
+            .map(|x| (x.seed, x.values.start()))
+    }
+
+    fn min_location_with_seed_ranges(&self) -> usize {
+        self.min_location_witness()
+            .map(|(_, location)| location)
             .unwrap_or_default()
     }
 }
@@ -170,6 +256,8 @@ impl FromStr for IfYouGiveASeedAFertilizer {
                 mapping_group.push(Mapping::from_str(l.unwrap())?);
                 l = lines.next();
             }
+
+            mapping_group.sort();
         }
 
         Ok(IfYouGiveASeedAFertilizer { seeds, mappings })
@@ -177,28 +265,41 @@ impl FromStr for IfYouGiveASeedAFertilizer {
 }
 
 impl Problem for IfYouGiveASeedAFertilizer {
+    const YEAR: usize = 2023;
     const DAY: usize = 5;
     const TITLE: &'static str = "if you give a seed a fertilizer";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.min_location())
+        Ok(self.min_location() as u64)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        Ok(self.min_location_with_seed_ranges())
+        Ok(self.min_location_with_seed_ranges() as u64)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::{differential::assert_agree, Solution};
+    use aoc_testing::{day05::Almanac, Generator};
+
+    impl IfYouGiveASeedAFertilizer {
+        /// Maps every seed in every range one at a time.
+        fn min_location_per_seed(&self) -> usize {
+            self.seeds
+                .chunks_exact(2)
+                .flat_map(|x| x[0]..x[0] + x[1])
+                .map(|seed| self.seed_to_location(seed))
+                .min()
+                .unwrap_or_default()
+        }
+    }
 
     #[test]
     #[ignore]
@@ -206,6 +307,11 @@ mod tests {
         let input = std::fs::read_to_string("input.txt").expect("Unable to load input");
         let solution = IfYouGiveASeedAFertilizer::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(3374647, 6082852));
+
+        let instance = IfYouGiveASeedAFertilizer::instance(&input).unwrap();
+        let (seed, location) = instance.min_location_witness().unwrap();
+        assert_eq!(location, 6082852);
+        assert_eq!(instance.seed_to_location(seed), location);
     }
 
     #[test]
@@ -214,4 +320,47 @@ mod tests {
         let solution = IfYouGiveASeedAFertilizer::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(35, 46));
     }
+
+    #[test]
+    fn min_location_witness() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = IfYouGiveASeedAFertilizer::instance(&input).unwrap();
+        assert_eq!(instance.min_location_witness(), Some((82, 46)));
+        assert_eq!(instance.seed_to_location(82), 46);
+    }
+
+    #[test]
+    fn ranges_match_per_seed() {
+        assert_agree(
+            0..200,
+            |seed| Almanac::default().generate_seeded(seed),
+            |input| {
+                IfYouGiveASeedAFertilizer::instance(input)
+                    .unwrap()
+                    .min_location_per_seed() as u64
+            },
+            |input| IfYouGiveASeedAFertilizer::solve(input).unwrap().part_two,
+        );
+    }
+
+    #[test]
+    fn parallel_matches_sequential() {
+        // enough seed ranges that the first few stages are split into chunks
+        let almanac = Almanac {
+            seed_ranges: 3 * PAR_CHUNK,
+            max_mappings: 20,
+            max_value: 1_000_000,
+            max_range: 10_000,
+        };
+        for seed in 0..5 {
+            let input = almanac.generate_seeded(seed);
+            let instance = IfYouGiveASeedAFertilizer::instance(&input).unwrap();
+            assert_eq!(
+                instance.min_location_witness(),
+                instance.min_location_witness_sequential()
+            );
+        }
+    }
+
+    aoc_plumbing::parsing_never_panics!(IfYouGiveASeedAFertilizer);
 }
@@ -1,7 +1,7 @@
 use std::str::FromStr;
 
-use anyhow::bail;
-use aoc_plumbing::Problem;
+use anyhow::{anyhow, bail};
+use aoc_plumbing::{incremental::Incremental, Problem};
 
 #[derive(Debug, Clone)]
 struct Race {
@@ -10,22 +10,47 @@ struct Race {
 }
 
 impl Race {
+    /// Past this, the squares in the quadratic formula might not be exactly
+    /// representable as `f64`s.
+    const FLOAT_LIMIT: u128 = 1 << 53;
+
     fn ways_to_beat_record(&self) -> usize {
         // solve x^2 - bx + c = 0 using quadratic formula
         // where b is the time and c is the distance
-        let (a, b, c) = (1.0, -(self.time as f64), self.distance as f64);
+        let (b, c) = (self.time as u128, self.distance as u128);
+        let Some(d) = (b * b).checked_sub(4 * c) else {
+            // the boat can't even reach the record
+            return 0;
+        };
 
-        let sqrt_d = (b * b - 4.0 * a * c).sqrt();
-        let roots = ((-b + sqrt_d) / (2.0 * a), (-b - sqrt_d) / (2.0 * a));
+        let min = if b * b < Self::FLOAT_LIMIT && 4 * c < Self::FLOAT_LIMIT {
+            ((b as f64 - (d as f64).sqrt()) / 2.0).floor() as u128
+        } else {
+            (b - d.isqrt()) / 2
+        };
 
-        let min = roots.0.min(roots.1).floor() as usize;
-        self.time - min - min - 1
+        // either way, min is at most one off from the last losing hold time,
+        // so step to the first winning one
+        let wins = |x: u128| x <= b && x * (b - x) > c;
+        let mut first = min.saturating_sub(1);
+        while first <= b / 2 && !wins(first) {
+            first += 1;
+        }
+
+        if first > b / 2 {
+            return 0;
+        }
+
+        // the winning hold times are symmetric about b / 2
+        (b - 2 * first + 1) as usize
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct WaitForIt {
     races: Vec<Race>,
+    // the input, for re-parsing after an update
+    lines: Vec<String>,
 }
 
 impl WaitForIt {
@@ -33,72 +58,161 @@ impl WaitForIt {
         self.races.iter().map(|x| x.ways_to_beat_record()).product()
     }
 
-    fn margin_of_error_single_race(&self) -> usize {
-        let (time, distance) = self.races.iter().fold((0, 0), |a, e| {
-            (
-                a.0 * 10_u32.pow(e.time.checked_ilog10().unwrap() + 1) as usize + e.time,
-                a.1 * 10_u32.pow(e.distance.checked_ilog10().unwrap() + 1) as usize + e.distance,
-            )
-        });
+    fn margin_of_error_single_race(&self) -> anyhow::Result<usize> {
+        let concat = |a: usize, b: usize| {
+            10_usize
+                .checked_pow(b.checked_ilog10().unwrap_or_default() + 1)
+                .and_then(|shift| a.checked_mul(shift))
+                .and_then(|a| a.checked_add(b))
+        };
+
+        let (mut time, mut distance) = (0, 0);
+        for race in self.races.iter() {
+            time = concat(time, race.time).ok_or_else(|| anyhow!("the time overflowed"))?;
+            distance = concat(distance, race.distance)
+                .ok_or_else(|| anyhow!("the distance overflowed"))?;
+        }
 
         let race = Race { time, distance };
-        race.ways_to_beat_record()
+        Ok(race.ways_to_beat_record())
     }
 }
 
-impl FromStr for WaitForIt {
-    type Err = anyhow::Error;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Label {
+    Time,
+    Distance,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut races = Vec::default();
-        let mut iter = s.lines();
-
-        if let Some(l) = iter.next() {
-            for token in l.split_whitespace().skip(1) {
-                races.push(Race {
-                    time: token.parse()?,
-                    distance: 0,
-                });
-            }
+impl Label {
+    fn parse(token: &str) -> Option<Self> {
+        let token = token.strip_suffix(':').unwrap_or(token);
+
+        if token.eq_ignore_ascii_case("time") {
+            Some(Self::Time)
+        } else if token.eq_ignore_ascii_case("distance") {
+            Some(Self::Distance)
         } else {
-            bail!("invalid number of lines in input");
+            None
+        }
+    }
+}
+
+/// Splits on whitespace and commas, so that both the puzzle format and
+/// CSV-style lists are accepted.
+fn tokens(s: &str) -> impl Iterator<Item = &str> {
+    s.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|t| !t.is_empty())
+}
+
+/// Parses races from any of:
+///
+/// - the puzzle format, with labeled times and distances on separate lines
+/// - the same, but with everything on a single line
+/// - two unlabeled lines of times then distances
+///
+/// where numbers may be separated by whitespace or commas.
+fn parse_races(s: &str) -> anyhow::Result<Vec<Race>> {
+    let mut times = Vec::default();
+    let mut distances = Vec::default();
+
+    if tokens(s).any(|t| Label::parse(t).is_some()) {
+        let mut cur = None;
+
+        for token in tokens(s) {
+            if let Some(label) = Label::parse(token) {
+                cur = Some(label);
+                continue;
+            }
+
+            match cur {
+                Some(Label::Time) => times.push(token.parse()?),
+                Some(Label::Distance) => distances.push(token.parse()?),
+                None => bail!("expected a label before {}", token),
+            }
         }
+    } else {
+        let mut lines = s.lines().filter(|l| !l.trim().is_empty());
 
-        if let Some(l) = iter.next() {
-            for (i, token) in l.split_whitespace().skip(1).enumerate() {
-                races[i].distance = token.parse()?;
+        match (lines.next(), lines.next(), lines.next()) {
+            (Some(a), Some(b), None) => {
+                times = tokens(a).map(|t| t.parse()).collect::<Result<_, _>>()?;
+                distances = tokens(b).map(|t| t.parse()).collect::<Result<_, _>>()?;
             }
-        } else {
-            bail!("invalid number of lines in input");
+            _ => bail!("invalid number of lines in input"),
         }
+    }
+
+    if times.is_empty() {
+        bail!("no races in input");
+    }
+
+    if times.len() != distances.len() {
+        bail!(
+            "found {} times but {} distances",
+            times.len(),
+            distances.len()
+        );
+    }
+
+    Ok(times
+        .into_iter()
+        .zip(distances)
+        .map(|(time, distance)| Race { time, distance })
+        .collect())
+}
+
+impl FromStr for WaitForIt {
+    type Err = anyhow::Error;
 
-        Ok(Self { races })
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            races: parse_races(s)?,
+            lines: s.lines().map(String::from).collect(),
+        })
     }
 }
 
 impl Problem for WaitForIt {
+    const YEAR: usize = 2023;
     const DAY: usize = 6;
     const TITLE: &'static str = "wait for it";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.margin_of_error())
+        Ok(self.margin_of_error() as u64)
     }
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        Ok(self.margin_of_error_single_race())
-        //Ok(self.foo())
+        Ok(self.margin_of_error_single_race()? as u64)
+    }
+}
+
+impl Incremental for WaitForIt {
+    /// Every race takes its time and distance from different lines, and
+    /// labels can move numbers between lists, so this re-parses the whole
+    /// edited input. There are only ever a handful of races.
+    fn update(&mut self, changed_line: usize, new_line: &str) -> anyhow::Result<()> {
+        let mut lines = self.lines.clone();
+        *lines
+            .get_mut(changed_line)
+            .ok_or_else(|| anyhow!("there is no line {}", changed_line + 1))? =
+            new_line.to_string();
+
+        self.races = parse_races(&lines.join("\n"))?;
+        self.lines = lines;
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::{incremental::assert_updates_agree, Solution};
 
     #[test]
     #[ignore]
@@ -114,4 +228,126 @@ mod tests {
         let solution = WaitForIt::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(288, 71503));
     }
+
+    #[test]
+    fn formats() {
+        let inputs = [
+            "Time:      7  15   30\nDistance:  9  40  200",
+            "Time: 7 15 30 Distance: 9 40 200",
+            "7,15,30\n9,40,200\n",
+            "time,7,15,30\ndistance,9,40,200",
+            "Time: 7, 15, 30\n\nDistance: 9, 40, 200\n",
+        ];
+
+        for input in inputs {
+            let solution = WaitForIt::solve(input).unwrap();
+            assert_eq!(solution, Solution::new(288, 71503), "{}", input);
+        }
+    }
+
+    #[test]
+    fn invalid_formats() {
+        let inputs = [
+            "",
+            "Time: 7 15 30",
+            "Time: 7 15 30\nDistance: 9 40",
+            "7 15 30",
+            "7 15 30\n9 40 200\n1 2 3",
+            "7 Time: 15 30 Distance: 9 40 200",
+            "Time: 7 x 30\nDistance: 9 40 200",
+        ];
+
+        for input in inputs {
+            assert!(WaitForIt::instance(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn ways_to_beat_record() {
+        let ways = |time, distance| Race { time, distance }.ways_to_beat_record();
+        let naive = |time: usize, distance: usize| {
+            (0..=time as u128)
+                .filter(|x| x * (time as u128 - x) > distance as u128)
+                .count()
+        };
+
+        for time in 0..40 {
+            for distance in 0..time * time / 4 + 3 {
+                assert_eq!(
+                    ways(time, distance),
+                    naive(time, distance),
+                    "{} {}",
+                    time,
+                    distance
+                );
+            }
+        }
+
+        // around where f64 stops being exact, checking that the count starts
+        // at the first winning hold time
+        let check = |time: usize, distance: usize| {
+            let (t, d) = (time as u128, distance as u128);
+            let first = (t + 1 - ways(time, distance) as u128) / 2;
+            assert!(first * (t - first) > d, "{} {}", time, distance);
+            assert!(
+                first == 0 || (first - 1) * (t - first + 1) <= d,
+                "{} {}",
+                time,
+                distance
+            );
+        };
+        for time in [
+            (1 << 27) - 1,
+            1 << 27,
+            (1 << 27) + 1,
+            94_000_000,
+            3_000_000_001,
+        ] {
+            for distance in [0, time * time / 4 - time, time * time / 4 - 1] {
+                check(time, distance);
+            }
+        }
+
+        // x = 2^31 - 1 and 2^31 + 1 tie with the record
+        let time = 1 << 32;
+        assert_eq!(ways(time, (1 << 62) - 1), 1);
+        assert_eq!(ways(time, (1 << 62) - 2), 3);
+        assert_eq!(ways(time, 1 << 62), 0);
+        assert_eq!(ways(usize::MAX, usize::MAX), usize::MAX - 3);
+        assert_eq!(ways(usize::MAX, usize::MAX / 2), usize::MAX - 1);
+    }
+
+    #[test]
+    fn big_single_race() {
+        let input = "Time: 4294967 296\nDistance: 4611686018427 387902";
+        let instance = WaitForIt::instance(input).unwrap();
+        assert_eq!(instance.margin_of_error_single_race().unwrap(), 3);
+
+        let input = "Time: 18446744073709551615 1\nDistance: 1 1";
+        assert!(WaitForIt::solve(input).is_err());
+        assert_eq!(
+            WaitForIt::solve("Time: 0 5\nDistance: 0 4")
+                .unwrap()
+                .part_two,
+            2
+        );
+    }
+
+    #[test]
+    fn updates() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        assert_updates_agree::<WaitForIt>(
+            &input,
+            &[
+                (0, "Time: 8 15 30"),
+                (1, "Distance: 9 40"),
+                (1, "Distance: 9 40 200 Time: 4"),
+                (1, "Distance: 9 40 200"),
+                (0, "7 15 30"),
+                (2, "Time: 1"),
+            ],
+        );
+    }
+
+    aoc_plumbing::parsing_never_panics!(WaitForIt);
 }
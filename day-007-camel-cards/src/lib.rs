@@ -2,10 +2,12 @@ use std::str::FromStr;
 
 use anyhow::bail;
 use aoc_plumbing::Problem;
+use rustc_hash::FxHashMap;
 
-#[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq, Copy, Hash)]
+#[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq, Copy, Hash, Default)]
 enum Card {
     Joker = 1,
+    #[default]
     Two,
     Three,
     Four,
@@ -21,37 +23,32 @@ enum Card {
     Ace,
 }
 
-impl Default for Card {
-    fn default() -> Self {
-        Self::Two
-    }
-}
+impl TryFrom<char> for Card {
+    type Error = anyhow::Error;
 
-impl From<char> for Card {
-    fn from(value: char) -> Self {
+    fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
-            'O' => Self::Joker, // use O to denote joker
-            '2' => Self::Two,
-            '3' => Self::Three,
-            '4' => Self::Four,
-            '5' => Self::Five,
-            '6' => Self::Six,
-            '7' => Self::Seven,
-            '8' => Self::Eight,
-            '9' => Self::Nine,
-            'T' => Self::Ten,
-            'J' => Self::Jack,
-            'Q' => Self::Queen,
-            'K' => Self::King,
-            'A' => Self::Ace,
-            _ => unreachable!(),
+            'O' => Ok(Self::Joker), // use O to denote joker
+            '2' => Ok(Self::Two),
+            '3' => Ok(Self::Three),
+            '4' => Ok(Self::Four),
+            '5' => Ok(Self::Five),
+            '6' => Ok(Self::Six),
+            '7' => Ok(Self::Seven),
+            '8' => Ok(Self::Eight),
+            '9' => Ok(Self::Nine),
+            'T' => Ok(Self::Ten),
+            'J' => Ok(Self::Jack),
+            'Q' => Ok(Self::Queen),
+            'K' => Ok(Self::King),
+            'A' => Ok(Self::Ace),
+            _ => bail!("invalid card {:?}", value),
         }
     }
 }
 
 #[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
 enum HandKind {
-    Unknown,
     HighCard,
     OnePair,
     TwoPair,
@@ -116,24 +113,78 @@ impl HandKind {
     }
 }
 
-#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
+/// How hands of the same kind are ordered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tiebreak {
+    /// Compare the cards one by one, in the order they were dealt.
+    #[default]
+    Positional,
+    /// Compare the cards as in poker: the largest group first, with the
+    /// higher card first among groups of the same size, so `KKQQ2` beats
+    /// `2QQKK`. Jokers are grouped as their own card for this.
+    BestCards,
+}
+
+/// The variant of camel cards to rank hands under.
+#[derive(Debug, Clone, Copy)]
+pub struct Rules {
+    /// Whether `J` is a joker rather than a jack.
+    pub jokers: bool,
+    pub tiebreak: Tiebreak,
+    /// What a hand wins given its rank (starting at 1 for the weakest hand)
+    /// and its bid.
+    pub payout: fn(u64, u64) -> u64,
+}
+
+impl Rules {
+    /// The rules for part one.
+    pub const STANDARD: Self = Self {
+        jokers: false,
+        tiebreak: Tiebreak::Positional,
+        payout: |rank, bid| rank * bid,
+    };
+
+    /// The rules for part two.
+    pub const JOKERS: Self = Self {
+        jokers: true,
+        ..Self::STANDARD
+    };
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Hand {
-    kind: HandKind,
     cards: [Card; 5],
-    bid: usize,
+    bid: u64,
 }
 
 impl Hand {
-    fn determine_kind(&mut self) {
-        self.kind = HandKind::from_cards(&self.cards);
-    }
-
-    fn jacks_to_joker(&mut self) {
-        for x in self.cards.iter_mut() {
-            if *x == Card::Jack {
-                *x = Card::Joker;
+    /// What hands are ranked by under `rules`. The bid is deliberately not
+    /// part of this.
+    fn strength(&self, rules: &Rules) -> (HandKind, [Card; 5]) {
+        let mut cards = self.cards;
+        if rules.jokers {
+            for x in cards.iter_mut() {
+                if *x == Card::Jack {
+                    *x = Card::Joker;
+                }
             }
         }
+
+        let kind = HandKind::from_cards(&cards);
+        if rules.tiebreak == Tiebreak::BestCards {
+            let count = |c: &Card| cards.iter().filter(|x| *x == c).count();
+            let mut sorted = cards;
+            sorted.sort_by_key(|c| std::cmp::Reverse((count(c), *c)));
+            cards = sorted;
+        }
+
+        (kind, cards)
     }
 }
 
@@ -143,13 +194,14 @@ impl FromStr for Hand {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Some((left, right)) = s.split_once(' ') {
             let mut cards = [Card::default(); 5];
+            if left.chars().count() != cards.len() {
+                bail!("a hand should have 5 cards, got {:?}", left);
+            }
 
-            for (i, c) in left.chars().take(5).enumerate() {
-                let card = c.into();
-                cards[i] = card;
+            for (i, c) in left.chars().enumerate() {
+                cards[i] = Card::try_from(c)?;
             }
             Ok(Hand {
-                kind: HandKind::Unknown,
                 cards,
                 bid: right.parse()?,
             })
@@ -165,32 +217,48 @@ pub struct CamelCards {
 }
 
 impl CamelCards {
-    fn winnings(&mut self) -> usize {
-        let mut ret = 0;
-
-        self.hands.iter_mut().for_each(|x| x.determine_kind());
-        self.hands.sort();
-
-        for (i, x) in self.hands.iter().enumerate() {
-            ret += (i + 1) * x.bid;
-        }
+    /// Ranks the hands from weakest to strongest under `rules` and totals
+    /// their payouts.
+    ///
+    /// Identical hands can't be told apart by strength, so they are ranked by
+    /// their order in the input, with the earlier hand ranked lower. The sort
+    /// is stable, so this is deterministic.
+    pub fn rank_with(&self, rules: &Rules) -> u64 {
+        let mut ranked: Vec<_> = self
+            .hands
+            .iter()
+            .map(|x| (x.strength(rules), x.bid))
+            .collect();
+        ranked.sort_by(|a, b| a.0.cmp(&b.0));
+
+        ranked
+            .iter()
+            .enumerate()
+            .map(|(i, (_, bid))| (rules.payout)(i as u64 + 1, *bid))
+            .sum()
+    }
 
-        ret
+    pub fn winnings(&self) -> u64 {
+        self.rank_with(&Rules::STANDARD)
     }
 
-    fn winnings_with_jokers(&mut self) -> usize {
-        let mut ret = 0;
+    pub fn winnings_with_jokers(&self) -> u64 {
+        self.rank_with(&Rules::JOKERS)
+    }
 
-        self.hands.iter_mut().for_each(|x| {
-            x.jacks_to_joker();
-            x.determine_kind();
-        });
-        self.hands.sort();
+    /// Returns every pair of input indices `(i, j)`, with `i < j`, of hands
+    /// that have identical cards. These are the hands ranked by input order.
+    pub fn ties(&self) -> Vec<(usize, usize)> {
+        let mut seen: FxHashMap<&[Card; 5], Vec<usize>> = FxHashMap::default();
+        let mut ret = Vec::default();
 
-        for (i, x) in self.hands.iter().enumerate() {
-            ret += (i + 1) * x.bid;
+        for (j, hand) in self.hands.iter().enumerate() {
+            let earlier = seen.entry(&hand.cards).or_default();
+            ret.extend(earlier.iter().map(|&i| (i, j)));
+            earlier.push(j);
         }
 
+        ret.sort();
         ret
     }
 }
@@ -209,13 +277,14 @@ impl FromStr for CamelCards {
 }
 
 impl Problem for CamelCards {
+    const YEAR: usize = 2023;
     const DAY: usize = 7;
     const TITLE: &'static str = "camel cards";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
         Ok(self.winnings())
@@ -228,9 +297,8 @@ impl Problem for CamelCards {
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::Solution;
 
     #[test]
     #[ignore]
@@ -253,4 +321,63 @@ mod tests {
         let solution = CamelCards::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(6440, 5905));
     }
+
+    #[test]
+    fn duplicate_hands() {
+        let input = "32T3K 10
+KK677 5
+32T3K 20
+T55J5 1
+32T3K 30";
+        let solution = CamelCards::solve(input).unwrap();
+        // the three 32T3K are ranked 1, 2, 3 in input order
+        assert_eq!(solution, Solution::new(165, 165));
+
+        let instance = CamelCards::instance(input).unwrap();
+        assert_eq!(instance.ties(), vec![(0, 2), (0, 4), (2, 4)]);
+
+        let example = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        assert!(CamelCards::instance(&example).unwrap().ties().is_empty());
+    }
+
+    #[test]
+    fn ties_do_not_use_bids() {
+        let a = CamelCards::solve("AAAAA 1\nAAAAA 100").unwrap();
+        let b = CamelCards::solve("AAAAA 100\nAAAAA 1").unwrap();
+        assert_eq!(a, Solution::new(201, 201));
+        assert_eq!(b, Solution::new(102, 102));
+    }
+
+    #[test]
+    fn variants() {
+        let instance = CamelCards::instance("KKQQ2 1\n2QQKK 2\nJJ234 3").unwrap();
+        // positionally 2QQKK is the weaker two pair
+        assert_eq!(instance.winnings(), 3 + 2 * 2 + 3);
+
+        let poker = Rules {
+            tiebreak: Tiebreak::BestCards,
+            ..Rules::STANDARD
+        };
+        // the pair of jacks is the weakest hand either way, and the two pairs
+        // are now the same strength, so stay in input order
+        assert_eq!(instance.rank_with(&poker), 3 + 2 + 3 * 2);
+
+        // with jokers, JJ234 is three of a kind
+        assert_eq!(instance.winnings_with_jokers(), 2 + 2 + 3 * 3);
+        assert_eq!(
+            instance.rank_with(&Rules {
+                jokers: true,
+                ..poker
+            }),
+            1 + 2 * 2 + 3 * 3
+        );
+
+        let flat = Rules {
+            payout: |_, bid| bid,
+            ..Rules::STANDARD
+        };
+        assert_eq!(instance.rank_with(&flat), 6);
+    }
+
+    aoc_plumbing::parsing_never_panics!(CamelCards);
 }
@@ -1,4 +1,5 @@
 use anyhow::{anyhow, bail};
+use aoc_common::math::lcm;
 use aoc_plumbing::Problem;
 use rayon::prelude::*;
 use rustc_hash::FxHashMap;
@@ -18,12 +19,14 @@ enum Direction {
     Right,
 }
 
-impl From<char> for Direction {
-    fn from(value: char) -> Self {
+impl TryFrom<char> for Direction {
+    type Error = anyhow::Error;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
-            'L' => Self::Left,
-            'R' => Self::Right,
-            _ => unreachable!(),
+            'L' => Ok(Self::Left),
+            'R' => Ok(Self::Right),
+            _ => bail!("invalid direction {:?}", value),
         }
     }
 }
@@ -38,7 +41,11 @@ impl FromStr for Node {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((left, right)) = s[1..s.len() - 1].split_once(", ") {
+        if let Some((left, right)) = s
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .and_then(|s| s.split_once(", "))
+        {
             Ok(Node {
                 left: label_to_id(left),
                 right: label_to_id(right),
@@ -49,6 +56,58 @@ impl FromStr for Node {
     }
 }
 
+/// Iterator over the positions of every ghost as they step in lockstep. The
+/// first item is the starting positions.
+#[derive(Debug, Clone)]
+pub struct Ghosts<'a> {
+    wasteland: &'a HauntedWasteland,
+    positions: Vec<u32>,
+    dir_index: usize,
+}
+
+impl<'a> Iterator for Ghosts<'a> {
+    type Item = Vec<u32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cur = self.positions.clone();
+        let direction = &self.wasteland.directions[self.dir_index];
+        for pos in self.positions.iter_mut() {
+            *pos = self.wasteland.traverse_one(*pos, direction);
+        }
+        self.dir_index = (self.dir_index + 1) % self.wasteland.directions.len();
+
+        Some(cur)
+    }
+}
+
+/// The path a single ghost takes, which always ends up looping since the
+/// state of a ghost is just its node and its position in the directions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GhostCycle {
+    pub start: u32,
+    /// The number of steps taken before the ghost enters the loop.
+    pub prefix: usize,
+    /// The number of steps it takes to go around the loop once.
+    pub period: usize,
+    /// Every step in `0..prefix + period` on which the ghost is on a node
+    /// ending in `Z`.
+    pub z_steps: Vec<usize>,
+}
+
+impl GhostCycle {
+    /// Whether the ghost is on a node ending in `Z` after `step` steps.
+    pub fn is_at_z(&self, step: usize) -> bool {
+        let end = self.prefix + self.period;
+        if step < end {
+            return self.z_steps.contains(&step);
+        }
+
+        self.z_steps
+            .iter()
+            .any(|&z| z >= self.prefix && (step - z).is_multiple_of(self.period))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct HauntedWasteland {
     directions: Vec<Direction>,
@@ -56,16 +115,74 @@ pub struct HauntedWasteland {
 }
 
 impl HauntedWasteland {
-    fn gcd(a: usize, b: usize) -> usize {
-        if b == 0 {
-            return a;
+    fn ghost_starts(&self) -> Vec<u32> {
+        let mut starts: Vec<_> = self
+            .graph
+            .keys()
+            .filter(|&x| ends_with(*x, b'A'))
+            .copied()
+            .collect();
+        starts.sort_unstable();
+        starts
+    }
+
+    /// Returns an iterator over the positions of all ghosts, starting on every
+    /// node ending in `A` (sorted by node id), as they move simultaneously.
+    pub fn ghosts(&self) -> Ghosts<'_> {
+        Ghosts {
+            wasteland: self,
+            positions: self.ghost_starts(),
+            dir_index: 0,
         }
+    }
 
-        Self::gcd(b, a % b)
+    /// Steps every ghost simultaneously until they are all on a node ending in
+    /// `Z`, returning the number of steps taken, or `None` if that does not
+    /// happen within `limit` steps. This is far slower than part two's LCM,
+    /// but makes no assumptions about the structure of the input.
+    pub fn simulate_ghosts(&self, limit: usize) -> Option<usize> {
+        self.ghosts()
+            .take(limit + 1)
+            .position(|p| p.iter().all(|&x| ends_with(x, b'Z')))
     }
 
-    fn lcm(a: usize, b: usize) -> usize {
-        a * b / Self::gcd(a, b)
+    /// Finds the loop each ghost ends up in, in the same order as
+    /// [Self::ghosts].
+    ///
+    /// A ghost is only back where it started when both its node and its
+    /// position in the directions repeat, so both are part of the key. The
+    /// puzzle inputs happen to loop cleanly on the node alone, but in general
+    /// the same node can be visited partway through the directions first.
+    pub fn cycles(&self) -> Vec<GhostCycle> {
+        self.ghost_starts()
+            .into_iter()
+            .map(|start| {
+                let mut seen = FxHashMap::default();
+                let mut z_steps = Vec::default();
+                let mut cur = start;
+                let mut step = 0;
+
+                loop {
+                    let dir_index = step % self.directions.len();
+                    if let Some(&first) = seen.get(&(cur, dir_index)) {
+                        return GhostCycle {
+                            start,
+                            prefix: first,
+                            period: step - first,
+                            z_steps,
+                        };
+                    }
+                    seen.insert((cur, dir_index), step);
+
+                    if ends_with(cur, b'Z') {
+                        z_steps.push(step);
+                    }
+
+                    cur = self.traverse_one(cur, &self.directions[dir_index]);
+                    step += 1;
+                }
+            })
+            .collect()
     }
 
     fn traverse_one(&self, source: u32, direction: &Direction) -> u32 {
@@ -119,8 +236,11 @@ impl FromStr for HauntedWasteland {
             .next()
             .ok_or_else(|| anyhow!("not enough lines in input"))?
             .chars()
-            .map(Direction::from)
-            .collect();
+            .map(Direction::try_from)
+            .collect::<Result<_, _>>()?;
+        if directions.is_empty() {
+            bail!("no directions");
+        }
 
         iter.next();
 
@@ -138,16 +258,17 @@ impl FromStr for HauntedWasteland {
 }
 
 impl Problem for HauntedWasteland {
+    const YEAR: usize = 2023;
     const DAY: usize = 8;
     const TITLE: &'static str = "haunted wasteland";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.traverse(label_to_id("AAA"), label_to_id("ZZZ")))
+        Ok(self.traverse(label_to_id("AAA"), label_to_id("ZZZ")) as u64)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
@@ -157,16 +278,15 @@ impl Problem for HauntedWasteland {
             .filter(|&x| ends_with(*x, b'A'))
             .collect::<Vec<_>>()
             .par_iter()
-            .map(|&&x| self.traverse_to_any_z(x))
-            .reduce(|| 1, HauntedWasteland::lcm))
+            .map(|&&x| self.traverse_to_any_z(x) as u64)
+            .reduce(|| 1, lcm))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::Solution;
 
     #[test]
     #[ignore]
@@ -182,16 +302,6 @@ mod tests {
         assert!(ends_with(label_to_id("AAZ"), b'Z'));
     }
 
-    #[test]
-    fn gcd_test() {
-        assert_eq!(HauntedWasteland::gcd(48, 18), 6);
-    }
-
-    #[test]
-    fn lcm_test() {
-        assert_eq!(HauntedWasteland::lcm(21, 6), 42);
-    }
-
     #[test]
     fn example() {
         let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
@@ -210,6 +320,38 @@ ZZZ = (ZZZ, ZZZ)";
         assert_eq!(solution, Solution::new(6, 6));
     }
 
+    #[test]
+    fn cycles_key_on_direction_index() {
+        // 11A is visited on both an L and an R, and only loops once it is
+        // visited on an R for the second time. Keying on the node alone
+        // would give a period of 2 starting from step 0, which puts the ghost
+        // on 11Z at step 3 when it is really on 11A.
+        let input = "LR
+
+11A = (11Z, 11Z)
+11Z = (11A, 11B)
+11B = (11A, 11A)";
+        let instance = HauntedWasteland::instance(input).unwrap();
+        let cycles = instance.cycles();
+        assert_eq!(
+            cycles,
+            vec![GhostCycle {
+                start: label_to_id("11A"),
+                prefix: 3,
+                period: 2,
+                z_steps: vec![1, 4],
+            }]
+        );
+
+        let simulated: Vec<_> = instance
+            .ghosts()
+            .take(20)
+            .map(|p| ends_with(p[0], b'Z'))
+            .collect();
+        let predicted: Vec<_> = (0..20).map(|step| cycles[0].is_at_z(step)).collect();
+        assert_eq!(predicted, simulated);
+    }
+
     #[test]
     fn example_part_two() {
         let input = "LR
@@ -224,5 +366,19 @@ ZZZ = (ZZZ, ZZZ)";
 XXX = (XXX, XXX)";
         let mut instance = HauntedWasteland::instance(input).unwrap();
         assert_eq!(instance.part_two().unwrap(), 6);
+        assert_eq!(instance.simulate_ghosts(100), Some(6));
+        assert_eq!(instance.simulate_ghosts(5), None);
+
+        let positions: Vec<_> = instance.ghosts().take(3).collect();
+        assert_eq!(
+            positions,
+            vec![
+                vec![label_to_id("11A"), label_to_id("22A")],
+                vec![label_to_id("11B"), label_to_id("22B")],
+                vec![label_to_id("11Z"), label_to_id("22C")],
+            ]
+        );
     }
+
+    aoc_plumbing::parsing_never_panics!(HauntedWasteland);
 }
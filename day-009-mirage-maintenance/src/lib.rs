@@ -1,11 +1,13 @@
 use rayon::prelude::*;
 use std::str::FromStr;
 
-use anyhow::{bail, Result};
-use aoc_plumbing::Problem;
+use anyhow::{anyhow, bail, Context, Result};
+use aoc_plumbing::{incremental::Incremental, Problem};
 
 #[derive(Debug, Clone)]
 struct History {
+    // 0-based line the history was read from
+    line: usize,
     values: Vec<i64>,
     next_value: i64,
     prev_value: i64,
@@ -13,23 +15,31 @@ struct History {
 }
 
 impl History {
+    /// Reduces the values to their differences over and over in a single
+    /// buffer until they're all zero. The previous value is the alternating
+    /// sum of the first value at each level, and the next value is the sum of
+    /// the last.
     fn edge_values_helper(values: &[i64]) -> Result<(i64, i64)> {
-        if values.iter().all(|x| *x == 0) {
-            return Ok((0, 0));
-        }
+        let mut buf = values.to_vec();
+        let mut len = buf.len();
+        let (mut prev_value, mut next_value) = (0, 0);
+        let mut sign = 1;
 
-        if values.len() < 2 {
-            bail!("not enough values");
-        }
+        while buf[..len].iter().any(|x| *x != 0) {
+            if len < 2 {
+                bail!("not enough values");
+            }
+
+            prev_value += sign * buf[0];
+            next_value += buf[len - 1];
+            sign = -sign;
 
-        let mut next_values = Vec::default();
-        for i in 1..values.len() {
-            next_values.push(values[i] - values[i - 1]);
+            for i in 0..len - 1 {
+                buf[i] = buf[i + 1] - buf[i];
+            }
+            len -= 1;
         }
 
-        let result = Self::edge_values_helper(&next_values)?;
-        let prev_value = values[0] - result.0;
-        let next_value = values[values.len() - 1] + result.1;
         Ok((prev_value, next_value))
     }
 
@@ -52,12 +62,24 @@ impl History {
 impl FromStr for History {
     type Err = anyhow::Error;
 
+    /// Values may be separated by commas, whitespace or both.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|x| !x.is_empty())
+            .map(|x| {
+                x.parse()
+                    .map_err(|e| anyhow!("invalid value {:?}: {}", x, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if values.is_empty() {
+            bail!("history has no values");
+        }
+
         Ok(Self {
-            values: s
-                .split_whitespace()
-                .map(|x| x.parse())
-                .collect::<Result<Vec<_>, _>>()?,
+            line: 0,
+            values,
             next_value: 0,
             prev_value: 0,
             processed: false,
@@ -67,23 +89,35 @@ impl FromStr for History {
 
 #[derive(Debug, Clone)]
 pub struct MirageMaintenance {
+    // in input order
     histories: Vec<History>,
+    line_count: usize,
 }
 
 impl FromStr for MirageMaintenance {
     type Err = anyhow::Error;
 
+    /// Parses one history per line, skipping blank lines.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Self {
             histories: s
                 .lines()
-                .map(History::from_str)
-                .collect::<Result<Vec<_>, _>>()?,
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(i, line)| {
+                    let mut history =
+                        History::from_str(line).with_context(|| format!("line {}", i + 1))?;
+                    history.line = i;
+                    Ok(history)
+                })
+                .collect::<Result<Vec<_>>>()?,
+            line_count: s.lines().count(),
         })
     }
 }
 
 impl Problem for MirageMaintenance {
+    const YEAR: usize = 2023;
     const DAY: usize = 9;
     const TITLE: &'static str = "mirage maintenance";
     const README: &'static str = include_str!("../README.md");
@@ -113,11 +147,45 @@ impl Problem for MirageMaintenance {
     }
 }
 
+impl Incremental for MirageMaintenance {
+    /// Re-parses just the changed history, leaving the values already
+    /// extrapolated for every other one. Blank lines are skipped as when
+    /// parsing, so a line can be blanked or filled in.
+    fn update(&mut self, changed_line: usize, new_line: &str) -> Result<()> {
+        if changed_line >= self.line_count {
+            bail!("there is no line {}", changed_line + 1);
+        }
+
+        let idx = self.histories.partition_point(|h| h.line < changed_line);
+        let existing = self
+            .histories
+            .get(idx)
+            .is_some_and(|h| h.line == changed_line);
+
+        if new_line.trim().is_empty() {
+            if existing {
+                self.histories.remove(idx);
+            }
+            return Ok(());
+        }
+
+        let mut history =
+            History::from_str(new_line).with_context(|| format!("line {}", changed_line + 1))?;
+        history.line = changed_line;
+        if existing {
+            self.histories[idx] = history;
+        } else {
+            self.histories.insert(idx, history);
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::{incremental::assert_updates_agree, Solution};
 
     #[test]
     #[ignore]
@@ -133,4 +201,67 @@ mod tests {
         let solution = MirageMaintenance::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(114, 2));
     }
+
+    #[test]
+    fn edge_values() {
+        let edges = |s: &str| History::from_str(s).unwrap().edge_values();
+
+        assert_eq!(edges("10 13 16 21 30 45").unwrap(), (5, 68));
+        assert_eq!(edges("0 0 0").unwrap(), (0, 0));
+        assert_eq!(edges("0").unwrap(), (0, 0));
+        assert_eq!(edges("7 7").unwrap(), (7, 7));
+        assert_eq!(edges("1 4 9 16").unwrap(), (0, 25));
+        assert_eq!(edges("-3 -1 4 12").unwrap(), (-2, 23));
+        // the differences run out before reaching zero
+        assert!(edges("5").is_err());
+        assert!(edges("1 2 4 8").is_err());
+    }
+
+    #[test]
+    fn formats() {
+        let input = "0,3, 6 9  12,15\n\n1\t3 6,10 15 21\n\n\n10  13  16  21  30  45\n";
+        let solution = MirageMaintenance::solve(input).unwrap();
+        assert_eq!(solution, Solution::new(114, 2));
+    }
+
+    #[test]
+    fn invalid_formats() {
+        let err = MirageMaintenance::instance("0 3 6\n\n1 3 x 10").unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            format!(
+                "line 3: invalid value \"x\": {}",
+                "x".parse::<i64>().unwrap_err()
+            )
+        );
+
+        let err = MirageMaintenance::instance("0 3 6\n , ,\n").unwrap_err();
+        assert_eq!(format!("{:#}", err), "line 2: history has no values");
+    }
+
+    #[test]
+    fn updates() {
+        let input = "0 3 6 9 12 15\n\n1 3 6 10 15 21\n10 13 16 21 30 45\n";
+        assert_updates_agree::<MirageMaintenance>(
+            input,
+            &[
+                (2, "1 4 9 16"),
+                (1, "-3 -1 4 12"),
+                (0, ""),
+                (3, "1 x"),
+                (0, "5 5"),
+                (3, " , "),
+                (4, "1 2 3"),
+            ],
+        );
+
+        // values are only extrapolated again for the changed history
+        let mut instance = MirageMaintenance::instance(input).unwrap();
+        instance.part_one().unwrap();
+        instance.update(2, "1 4 9 16").unwrap();
+        let processed: Vec<bool> = instance.histories.iter().map(|h| h.processed).collect();
+        assert_eq!(processed, vec![true, false, true]);
+    }
+
+    aoc_plumbing::parsing_never_panics!(MirageMaintenance);
 }
@@ -10,7 +10,7 @@ use aoc_plumbing::Problem;
 use rustc_hash::FxHashSet;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Tile {
+pub enum Tile {
     NS,
     EW,
     NE,
@@ -22,6 +22,21 @@ enum Tile {
 }
 
 impl Tile {
+    /// The two directions this tile connects, if it is a pipe.
+    pub fn directions(&self) -> Option<[Cardinal; 2]> {
+        let ret = match self {
+            Self::NS => [Cardinal::North, Cardinal::South],
+            Self::EW => [Cardinal::East, Cardinal::West],
+            Self::NE => [Cardinal::North, Cardinal::East],
+            Self::NW => [Cardinal::North, Cardinal::West],
+            Self::SW => [Cardinal::South, Cardinal::West],
+            Self::SE => [Cardinal::South, Cardinal::East],
+            Self::Ground | Self::Start => return None,
+        };
+
+        Some(ret)
+    }
+
     fn connects(&self, dir: &Cardinal) -> bool {
         matches!(
             (self, dir),
@@ -62,20 +77,15 @@ impl TryFrom<char> for Tile {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 enum TileKind {
+    #[default]
     Unknown,
     Loop(Tile),
     Inside,
     Outside,
 }
 
-impl Default for TileKind {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
 impl fmt::Display for TileKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let c = match self {
@@ -96,6 +106,14 @@ impl fmt::Display for TileKind {
     }
 }
 
+/// The main loop, in order, beginning at the start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopPath {
+    pub path: Vec<Coordinate>,
+    /// The pipe hidden under the start tile.
+    pub start_tile: Tile,
+}
+
 #[derive(Debug, Clone)]
 pub struct PipeMaze {
     grid: Grid<Tile>,
@@ -171,38 +189,47 @@ impl PipeMaze {
         }
     }
 
-    fn inside(&self) -> Result<usize> {
-        let mut memo = Grid::new(self.grid.n, self.grid.m, TileKind::Unknown);
+    /// Walks the main loop from the start, leaving the start in the first
+    /// direction its resolved tile connects.
+    pub fn loop_path(&self) -> Result<LoopPath> {
+        let start_tile = self.determine_start_tile()?;
+        let [mut dir, _] = start_tile
+            .directions()
+            .ok_or_else(|| anyhow!("invalid start tile"))?;
 
-        // populate the loop
-        let mut q = VecDeque::default();
-        let mut visited = FxHashSet::default();
-        q.push_back(self.start);
+        let mut path = vec![self.start];
+        let mut coord = self.start.neighbour(&dir);
 
-        while !q.is_empty() {
-            let coord = q.pop_front().unwrap();
-
-            if !self.grid.is_in_bounds(coord) {
-                continue;
-            }
+        while coord != self.start {
+            let tile = self
+                .grid
+                .get(coord)
+                .ok_or_else(|| anyhow!("loop leaves the grid"))?;
+            let [a, b] = tile
+                .directions()
+                .filter(|x| x.contains(&dir.opposite()))
+                .ok_or_else(|| anyhow!("loop is broken at {:?}", coord))?;
+
+            path.push(coord);
+            dir = if a == dir.opposite() { b } else { a };
+            coord = coord.neighbour(&dir);
+        }
 
-            if visited.contains(&coord) {
-                continue;
-            }
+        Ok(LoopPath { path, start_tile })
+    }
 
-            visited.insert(coord);
-            memo[coord] = if self.grid[coord] == Tile::Start {
-                TileKind::Loop(self.determine_start_tile()?)
-            } else {
-                TileKind::Loop(self.grid[coord])
-            };
+    /// Counts the tiles enclosed by the loop by classifying and flood filling
+    /// each region of non-loop tiles. See `inside_fast` and `inside_scanline`
+    /// for quicker alternatives.
+    pub fn inside(&self) -> Result<usize> {
+        let mut memo = Grid::new(self.grid.n, self.grid.m, TileKind::Unknown);
 
-            for dir in Cardinal::all() {
-                if self.connects(&coord, &dir) {
-                    q.push_back(coord.neighbour(&dir));
-                }
-            }
+        // populate the loop
+        let LoopPath { path, start_tile } = self.loop_path()?;
+        for coord in path {
+            memo[coord] = TileKind::Loop(self.grid[coord]);
         }
+        memo[self.start] = TileKind::Loop(start_tile);
 
         // test and fill tiles
         let mut count = 0;
@@ -226,6 +253,61 @@ impl PipeMaze {
         Ok(count)
     }
 
+    /// Counts the tiles enclosed by the loop using the shoelace formula for
+    /// the area of the loop, then Pick's theorem to get the interior points
+    /// from the area and the number of boundary points.
+    pub fn inside_fast(&self) -> Result<usize> {
+        let LoopPath { path, .. } = self.loop_path()?;
+
+        let twice_area = path
+            .iter()
+            .zip(path.iter().cycle().skip(1))
+            .map(|(a, b)| a.row() * b.col() - b.row() * a.col())
+            .sum::<isize>()
+            .unsigned_abs();
+
+        // A = i + b/2 - 1
+        Ok((twice_area + 2 - path.len()) / 2)
+    }
+
+    /// Counts the tiles enclosed by the loop by scanning each row once and
+    /// toggling between inside and outside whenever the row crosses the loop.
+    ///
+    /// A row crosses the loop at a `|`, and at an `L-*7` or `F-*J` run, but
+    /// not at `L-*J` or `F-*7`. Counting only the loop tiles that connect
+    /// north gets exactly one toggle for each crossing run and none (or two)
+    /// for the others.
+    pub fn inside_scanline(&self) -> Result<usize> {
+        let LoopPath { path, start_tile } = self.loop_path()?;
+        let m = self.grid.m;
+
+        let mut on_loop = vec![false; self.grid.n * m];
+        for coord in path {
+            on_loop[coord.row() as usize * m + coord.col() as usize] = true;
+        }
+
+        let mut count = 0;
+        for (i, row) in self.grid.grid.iter().enumerate() {
+            let mut inside = false;
+            for (j, tile) in row.iter().enumerate() {
+                if on_loop[i * m + j] {
+                    let tile = if *tile == Tile::Start {
+                        start_tile
+                    } else {
+                        *tile
+                    };
+                    if matches!(tile, Tile::NS | Tile::NE | Tile::NW) {
+                        inside = !inside;
+                    }
+                } else if inside {
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
     fn fill(&self, source: &Coordinate, memo: &mut Grid<TileKind>, kind: &TileKind) -> usize {
         let mut count = 0;
         let mut q = VecDeque::default();
@@ -333,7 +415,7 @@ impl FromStr for PipeMaze {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let grid = Grid::from_str(s)?;
+        let grid = Grid::parse(s)?;
         let start = grid
             .find_coordinate(|x| *x == Tile::Start)
             .ok_or_else(|| anyhow!("could not find start location"))?;
@@ -342,28 +424,28 @@ impl FromStr for PipeMaze {
 }
 
 impl Problem for PipeMaze {
+    const YEAR: usize = 2023;
     const DAY: usize = 10;
     const TITLE: &'static str = "pipe maze";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        self.max_distance()
+        self.max_distance().map(|x| x as u64)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        self.inside()
+        self.inside_fast().map(|x| x as u64)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::Solution;
 
     #[test]
     #[ignore]
@@ -371,6 +453,9 @@ mod tests {
         let input = std::fs::read_to_string("input.txt").expect("Unable to load input");
         let solution = PipeMaze::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(7066, 401));
+
+        let instance = PipeMaze::instance(&input).unwrap();
+        assert_eq!(instance.inside_scanline().unwrap(), 401);
     }
 
     #[test]
@@ -394,5 +479,53 @@ L.L7LFJ|||||FJL7||LJ
 L7JLJL-JLJLJL--JLJ.L";
         let mut instance = PipeMaze::instance(input).unwrap();
         assert_eq!(instance.part_two().unwrap(), 10);
+        assert_eq!(instance.inside().unwrap(), 10);
+        assert_eq!(instance.inside_scanline().unwrap(), 10);
     }
+
+    #[test]
+    fn inside_fast_matches_fill() {
+        let inputs = [
+            std::fs::read_to_string("example.txt").expect("Unable to load input"),
+            "..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+.........."
+                .to_string(),
+        ];
+
+        for input in inputs {
+            let instance = PipeMaze::instance(&input).unwrap();
+            assert_eq!(instance.inside_fast().unwrap(), instance.inside().unwrap());
+            assert_eq!(
+                instance.inside_scanline().unwrap(),
+                instance.inside().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn loop_path() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = PipeMaze::instance(&input).unwrap();
+        let LoopPath { path, start_tile } = instance.loop_path().unwrap();
+
+        assert_eq!(start_tile, Tile::SE);
+        assert_eq!(path.len(), 16);
+        assert_eq!(path[0], instance.start);
+        assert_eq!(path[1], instance.start.south());
+
+        let unique: FxHashSet<_> = path.iter().collect();
+        assert_eq!(unique.len(), path.len());
+        for (a, b) in path.iter().zip(path.iter().cycle().skip(1)) {
+            assert_eq!(a.manhattan_distance(b), 1);
+        }
+    }
+
+    aoc_plumbing::parsing_never_panics!(PipeMaze);
 }
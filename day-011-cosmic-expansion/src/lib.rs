@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use aoc_common::grid::Coordinate;
+use aoc_common::grid::{check_shape, Coordinate};
 use aoc_plumbing::Problem;
 
 #[derive(Debug, Clone)]
@@ -11,30 +11,82 @@ pub struct CosmicExpansion {
 }
 
 impl CosmicExpansion {
-    fn distance_between(&self, a: &Coordinate, b: &Coordinate, expansion: usize) -> usize {
-        let mut dist = a.manhattan_distance(b);
-
+    /// Returns the unexpanded distance between the two galaxies, along with
+    /// the number of empty rows and columns crossed between them.
+    fn distance_between(&self, a: &Coordinate, b: &Coordinate) -> (usize, usize) {
         let top = self.empty_rows.partition_point(|&x| x <= a.row() as usize);
         let bottom = self.empty_rows.partition_point(|&x| x < b.row() as usize);
         let left = self.empty_cols.partition_point(|&x| x <= a.col() as usize);
         let right = self.empty_cols.partition_point(|&x| x < b.col() as usize);
 
-        dist += bottom.abs_diff(top) * (expansion - 1);
-        dist += right.abs_diff(left) * (expansion - 1);
-
-        dist
+        (
+            a.manhattan_distance(b),
+            bottom.abs_diff(top) + right.abs_diff(left),
+        )
     }
 
-    fn total_distances(&self, expansion: usize) -> usize {
-        let mut total = 0;
+    /// The total distance between all pairs of galaxies is linear in the
+    /// expansion factor. Returns `(base, slope)` such that the total for an
+    /// expansion factor `e` is `base + slope * e`.
+    pub fn total_distances_formula(&self) -> (u64, u64) {
+        let mut distance = 0;
+        let mut crossings = 0;
         for (i, a) in self.galaxies.iter().enumerate() {
             for b in self.galaxies[i + 1..].iter() {
-                total += self.distance_between(a, b, expansion);
+                let (d, c) = self.distance_between(a, b);
+                distance += d as u64;
+                crossings += c as u64;
             }
         }
 
-        total
+        // every crossed empty row or column contributes 1 to the unexpanded
+        // distance, and `e` once expanded
+        (distance - crossings, crossings)
     }
+
+    /// The same as `total_distances_formula`, but in `O(G log G)` rather
+    /// than `O(G^2)` for `G` galaxies, for when there are very many of them.
+    ///
+    /// The Manhattan distance splits into independent row and column
+    /// distances, and the pairwise distances between sorted values sum to
+    /// `sum(v[j] * j - (v[0] + .. + v[j - 1]))`. Since galaxies are never in
+    /// empty rows or columns, the number crossed between two galaxies is the
+    /// difference of the numbers before each, which sums the same way.
+    pub fn total_distances_formula_sorted(&self) -> (u64, u64) {
+        let mut rows: Vec<usize> = self.galaxies.iter().map(|g| g.row() as usize).collect();
+        let mut cols: Vec<usize> = self.galaxies.iter().map(|g| g.col() as usize).collect();
+        rows.sort_unstable();
+        cols.sort_unstable();
+
+        let (mut distance, mut crossings) = (0, 0);
+        for (values, empty) in [(&rows, &self.empty_rows), (&cols, &self.empty_cols)] {
+            distance += pairwise_sum(values.iter().map(|&v| v as u64));
+            crossings += pairwise_sum(
+                values
+                    .iter()
+                    .map(|&v| empty.partition_point(|&x| x < v) as u64),
+            );
+        }
+
+        (distance - crossings, crossings)
+    }
+
+    fn total_distances(&self, expansion: u64) -> u64 {
+        let (base, slope) = self.total_distances_formula();
+        base + slope * expansion
+    }
+}
+
+/// The sum of the differences between every pair of the non-decreasing
+/// `values`.
+fn pairwise_sum(values: impl Iterator<Item = u64>) -> u64 {
+    let mut total = 0;
+    let mut prefix = 0;
+    for (j, v) in values.enumerate() {
+        total += v * j as u64 - prefix;
+        prefix += v;
+    }
+    total
 }
 
 impl FromStr for CosmicExpansion {
@@ -43,12 +95,10 @@ impl FromStr for CosmicExpansion {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut galaxies = Vec::default();
         let mut empty_rows = Vec::default();
-        let mut is_empty_cols = Vec::default();
+        let (_, m) = check_shape(s)?;
+        let mut is_empty_cols = vec![true; m];
 
         for (i, line) in s.lines().enumerate() {
-            if i == 0 {
-                is_empty_cols = vec![true; line.len()];
-            }
             let mut is_empty_row = true;
 
             for (j, c) in line.chars().enumerate() {
@@ -80,13 +130,14 @@ impl FromStr for CosmicExpansion {
 }
 
 impl Problem for CosmicExpansion {
+    const YEAR: usize = 2023;
     const DAY: usize = 11;
     const TITLE: &'static str = "cosmic expansion";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
         Ok(self.total_distances(2))
@@ -100,6 +151,7 @@ impl Problem for CosmicExpansion {
 #[cfg(test)]
 mod tests {
     use aoc_plumbing::Solution;
+    use proptest::prelude::*;
 
     use super::*;
 
@@ -109,6 +161,12 @@ mod tests {
         let input = std::fs::read_to_string("input.txt").expect("Unable to load input");
         let solution = CosmicExpansion::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(9556896, 685038186836));
+
+        let instance = CosmicExpansion::instance(&input).unwrap();
+        assert_eq!(
+            instance.total_distances_formula_sorted(),
+            instance.total_distances_formula()
+        );
     }
 
     #[test]
@@ -117,4 +175,55 @@ mod tests {
         let solution = CosmicExpansion::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(374, 82000210));
     }
+
+    #[test]
+    fn formula_is_linear() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = CosmicExpansion::instance(&input).unwrap();
+
+        // compute the distances directly with the expansion applied
+        let direct = |expansion: u64| {
+            let mut total = 0;
+            for (i, a) in instance.galaxies.iter().enumerate() {
+                for b in instance.galaxies[i + 1..].iter() {
+                    let (d, c) = instance.distance_between(a, b);
+                    total += d as u64 + c as u64 * (expansion - 1);
+                }
+            }
+            total
+        };
+
+        let (base, slope) = instance.total_distances_formula();
+        assert_eq!(instance.total_distances_formula_sorted(), (base, slope));
+        for expansion in [1, 2, 10, 100, 1000000] {
+            assert_eq!(base + slope * expansion, direct(expansion));
+        }
+        assert_eq!(direct(10), 1030);
+        assert_eq!(direct(100), 8410);
+    }
+
+    proptest! {
+        #[test]
+        fn sorted_matches_pairwise(
+            galaxies in prop::collection::vec((0..30_usize, 0..30_usize), 0..40),
+        ) {
+            let mut image = vec![vec!['.'; 30]; 30];
+            for (i, j) in galaxies {
+                image[i][j] = '#';
+            }
+            let image = image
+                .into_iter()
+                .map(|row| row.into_iter().collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            let instance = CosmicExpansion::instance(&image).unwrap();
+            prop_assert_eq!(
+                instance.total_distances_formula_sorted(),
+                instance.total_distances_formula()
+            );
+        }
+    }
+
+    aoc_plumbing::parsing_never_panics!(CosmicExpansion);
 }
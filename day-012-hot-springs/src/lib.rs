@@ -1,9 +1,11 @@
 use rayon::prelude::*;
-use std::str::FromStr;
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
-use anyhow::bail;
+use anyhow::{anyhow, bail, Result};
 use aoc_plumbing::Problem;
-use rustc_hash::FxHashMap;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Spring {
@@ -24,6 +26,10 @@ impl Spring {
     fn damaged(&self) -> bool {
         *self == Self::Damaged
     }
+
+    fn operational(&self) -> bool {
+        *self == Self::Operational
+    }
 }
 
 impl TryFrom<char> for Spring {
@@ -45,6 +51,8 @@ impl TryFrom<char> for Spring {
 struct Record {
     springs: Vec<Spring>,
     groups: Vec<usize>,
+    // 1-based line the record was read from, for reporting
+    line: usize,
 }
 
 impl Record {
@@ -60,87 +68,274 @@ impl Record {
         println!("{}", line);
     }
 
-    fn arrangements(&self, folds: usize) -> usize {
-        if folds == 1 {
-            self.arrangements_helper(&self.springs, &self.groups, &mut FxHashMap::default())
-        } else {
-            let mut springs: Vec<_> = (0..folds)
-                .flat_map(|_| {
-                    let mut x = self.springs.clone();
-                    x.push(Spring::Unknown);
-                    x
-                })
-                .collect();
-            springs.pop();
-            let groups: Vec<_> = (0..folds).flat_map(|_| self.groups.clone()).collect();
-
-            self.arrangements_helper(&springs, &groups, &mut FxHashMap::default())
+    /// The number of cells in the DP table for this record after unfolding
+    /// it `folds` times, which is what the time and memory taken scale with.
+    fn table_size(&self, folds: usize) -> usize {
+        let springs = (self.springs.len() + 1)
+            .saturating_mul(folds)
+            .saturating_sub(1);
+        let groups = self.groups.len().saturating_mul(folds);
+        (springs + 1).saturating_mul(groups + 1)
+    }
+
+    /// Returns `None` if the count doesn't fit in a `u64`.
+    fn arrangements_with(&self, folds: usize, scratch: &mut Scratch) -> Option<u64> {
+        match self.arrangements_before(folds, None, scratch) {
+            Ok(count) => Some(count),
+            Err(Interrupted::Overflow) => None,
+            Err(Interrupted::Deadline) => unreachable!("there is no deadline to miss"),
         }
     }
 
-    fn arrangements_helper(
+    /// Counts the arrangements with a bottom-up DP over (springs, groups),
+    /// where `dp[i][j]` is the number of arrangements of `springs[i..]` that
+    /// match `groups[j..]`. All of the buffers live in `scratch` so they can
+    /// be reused across records.
+    ///
+    /// Counts saturate at `u64::MAX`, which then sticks to every count it's
+    /// added to, so the whole count is reported as overflowing if it's
+    /// `u64::MAX` or more rather than returned saturated.
+    fn arrangements_before(
         &self,
-        springs: &[Spring],
-        groups: &[usize],
-        memo: &mut FxHashMap<(usize, usize), usize>,
-    ) -> usize {
-        let key = (springs.len(), groups.len());
-
-        if let Some(&x) = memo.get(&key) {
-            return x;
+        folds: usize,
+        deadline: Option<Instant>,
+        scratch: &mut Scratch,
+    ) -> Result<u64, Interrupted> {
+        scratch.unfold(self, folds);
+        scratch.propagate();
+        let Scratch {
+            springs,
+            groups,
+            runs,
+            dp,
+            ..
+        } = scratch;
+
+        // operational springs at either end can't affect the count, and
+        // propagating tends to pin a few of them
+        let start = springs
+            .iter()
+            .position(|x| !x.operational())
+            .unwrap_or(springs.len());
+        let end = springs
+            .iter()
+            .rposition(|x| !x.operational())
+            .map_or(start, |x| x + 1);
+        let springs = &springs[start..end];
+
+        let n = springs.len();
+        let m = groups.len();
+        let width = m + 1;
+
+        // runs[i] is the number of consecutive potentially damaged springs
+        // starting at i
+        runs.clear();
+        runs.resize(n + 1, 0);
+        for i in (0..n).rev() {
+            if springs[i].potentially_damaged() {
+                runs[i] = runs[i + 1] + 1;
+            }
         }
 
-        if groups.is_empty() {
-            if springs.iter().any(|x| x.damaged()) {
-                // if there is still any damaged spring, then it's impossible
-                return 0;
-            } else {
-                // otherwise there are no more groups to match, and no outstanding damaged springs,
-                // so this is one arrangement
-                return 1;
+        dp.clear();
+        dp.resize((n + 1) * width, 0);
+        dp[n * width + m] = 1;
+
+        for i in (0..n).rev() {
+            // checking the time is much slower than filling in a cell, so
+            // only check every few rows
+            if let Some(deadline) = deadline {
+                if i % 64 == 0 && Instant::now() > deadline {
+                    return Err(Interrupted::Deadline);
+                }
+            }
+
+            for j in 0..=m {
+                let mut ret = 0;
+
+                // kick the group down the line, which we can only do if the
+                // leading spring is not damaged
+                if !springs[i].damaged() {
+                    ret = dp[(i + 1) * width + j];
+                }
+
+                // or match the group here, as long as it isn't followed by a
+                // damaged spring
+                if j < m {
+                    let group = groups[j];
+                    if runs[i] >= group {
+                        if i + group == n {
+                            ret = dp[n * width + j + 1].saturating_add(ret);
+                        } else if springs[i + group].potentially_operational() {
+                            ret = dp[(i + group + 1) * width + j + 1].saturating_add(ret);
+                        }
+                    }
+                }
+
+                dp[i * width + j] = ret;
             }
         }
 
-        let group = groups[0];
-        if group > springs.len() {
-            return 0;
+        match dp[0] {
+            u64::MAX => Err(Interrupted::Overflow),
+            count => Ok(count),
         }
+    }
+}
 
-        let mut ret = 0;
-
-        // match the group now
-        if self.all_potentially_damaged(&springs[0..group]) {
-            if group == springs.len() {
-                // if this matches all the way to the end...
-                if groups.len() == 1 {
-                    // and there are no more groups to match, then this is one arrangement
-                    return 1;
-                } else {
-                    // but there are more groups to match, then it's impossible
-                    return 0;
+/// Why a record's arrangements weren't counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Interrupted {
+    Deadline,
+    Overflow,
+}
+
+/// How many unknown springs constraint propagation worked out before
+/// counting arrangements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pins {
+    pub damaged: usize,
+    pub operational: usize,
+}
+
+/// Reusable buffers for counting arrangements.
+#[derive(Debug, Clone, Default)]
+struct Scratch {
+    springs: Vec<Spring>,
+    groups: Vec<usize>,
+    runs: Vec<usize>,
+    dp: Vec<u64>,
+    // the earliest and latest each group can start
+    earliest: Vec<usize>,
+    latest: Vec<usize>,
+}
+
+impl Scratch {
+    fn unfold(&mut self, record: &Record, folds: usize) {
+        self.springs.clear();
+        self.groups.clear();
+
+        for i in 0..folds {
+            if i > 0 {
+                self.springs.push(Spring::Unknown);
+            }
+            self.springs.extend_from_slice(&record.springs);
+            self.groups.extend_from_slice(&record.groups);
+        }
+    }
+
+    /// Pins the unknown springs that are the same in every arrangement.
+    ///
+    /// Packing the groups as far left as they fit (ignoring which springs
+    /// are known to be damaged) bounds where each group can start from
+    /// below, and packing them right bounds it from above. Every placement
+    /// of a group covers the springs between its latest start and its
+    /// earliest end, so those are damaged, and springs outside of every
+    /// group's bounds are operational.
+    ///
+    /// This never changes the number of arrangements, and pins nothing if
+    /// the groups don't fit at all.
+    fn propagate(&mut self) -> Pins {
+        let mut pins = Pins::default();
+        let (springs, groups) = (&mut self.springs, &self.groups);
+        let n = springs.len();
+
+        if !earliest_starts(
+            |i| springs[i],
+            n,
+            groups.iter().copied(),
+            &mut self.earliest,
+        ) || !earliest_starts(
+            |i| springs[n - 1 - i],
+            n,
+            groups.iter().rev().copied(),
+            &mut self.latest,
+        ) {
+            return pins;
+        }
+
+        // flip the right packing back around, to starts from the left
+        self.latest.reverse();
+        for (start, group) in self.latest.iter_mut().zip(groups) {
+            *start = n - *start - group;
+        }
+
+        if self.earliest.iter().zip(&self.latest).any(|(e, l)| e > l) {
+            return pins;
+        }
+
+        for ((&earliest, &latest), &group) in self.earliest.iter().zip(&self.latest).zip(groups) {
+            for spring in springs.iter_mut().take(earliest + group).skip(latest) {
+                if *spring == Spring::Unknown {
+                    *spring = Spring::Damaged;
+                    pins.damaged += 1;
                 }
-            } else if springs[group].potentially_operational() {
-                // we can match this group here, so we recur starting from after the matched group
-                // plus one buffer space
-                ret += self.arrangements_helper(&springs[group + 1..], &groups[1..], memo);
             }
-            // otherwise we cannot match the group here, so we will kick in down the line
         }
 
-        // or kick it down the line
-        if !springs[0].damaged() {
-            // we can only kick it if the leading spring is not damaged. if it is damaged, then
-            // we have to match the group now
-            ret += self.arrangements_helper(&springs[1..], groups, memo);
+        // the bounds are sorted on both ends, so the first group that ends
+        // after a spring is the only one that could start before it
+        let mut j = 0;
+        for (i, spring) in springs.iter_mut().enumerate() {
+            while j < groups.len() && self.latest[j] + groups[j] <= i {
+                j += 1;
+            }
+
+            let covered = j < groups.len() && self.earliest[j] <= i;
+            if !covered && *spring == Spring::Unknown {
+                *spring = Spring::Operational;
+                pins.operational += 1;
+            }
         }
 
-        memo.insert(key, ret);
-        ret
+        pins
     }
+}
+
+/// Packs `groups` as far towards the start of the `n` springs as they fit,
+/// writing where each group starts to `out`. Groups only need to avoid
+/// operational springs and each other, so this is a lower bound on where
+/// each group starts in any arrangement. Returns false if they don't fit.
+fn earliest_starts(
+    spring: impl Fn(usize) -> Spring,
+    n: usize,
+    groups: impl Iterator<Item = usize>,
+    out: &mut Vec<usize>,
+) -> bool {
+    out.clear();
+    // the first spring the next group may use
+    let mut next = 0;
+
+    for group in groups {
+        // the number of potentially damaged springs ending at i
+        let mut run = 0;
+        let mut i = next;
+        loop {
+            if i >= n {
+                return false;
+            }
 
-    fn all_potentially_damaged(&self, springs: &[Spring]) -> bool {
-        springs.iter().all(|x| x.potentially_damaged())
+            run = if spring(i).potentially_damaged() {
+                run + 1
+            } else {
+                0
+            };
+            i += 1;
+
+            if run >= group {
+                let start = i - group;
+                let fits_before = start == 0 || spring(start - 1).potentially_operational();
+                let fits_after = i == n || spring(i).potentially_operational();
+                if fits_before && fits_after {
+                    out.push(start);
+                    next = i + 1;
+                    break;
+                }
+            }
+        }
     }
+
+    true
 }
 
 impl FromStr for Record {
@@ -158,7 +353,11 @@ impl FromStr for Record {
                 .map(|x| x.parse())
                 .collect::<Result<Vec<_>, _>>()?;
 
-            Ok(Self { springs, groups })
+            Ok(Self {
+                springs,
+                groups,
+                line: 0,
+            })
         } else {
             bail!("could not parse record")
         }
@@ -171,44 +370,126 @@ pub struct HotSprings {
 }
 
 impl HotSprings {
-    fn sum_arrangements(&self, folds: usize) -> usize {
-        self.records.par_iter().map(|x| x.arrangements(folds)).sum()
+    /// Returns the number of arrangements for each record, in input order,
+    /// after unfolding every record `folds` times. Part one is `folds = 1` and
+    /// part two is `folds = 5`. Counts that don't fit in a `u64` are `None`.
+    pub fn arrangements(&self, folds: usize) -> Vec<Option<u64>> {
+        self.records
+            .par_iter()
+            .map_init(Scratch::default, |scratch, x| {
+                x.arrangements_with(folds, scratch)
+            })
+            .collect()
+    }
+
+    /// Returns the size of the DP table needed for each record, in input
+    /// order, after unfolding every record `folds` times. This is roughly
+    /// the number of springs times the number of groups, and can be used to
+    /// turn away records that would take too long before trying them.
+    pub fn estimated_cells(&self, folds: usize) -> Vec<usize> {
+        self.records.iter().map(|x| x.table_size(folds)).collect()
+    }
+
+    /// Returns how many springs constraint propagation pins for each record,
+    /// in input order, after unfolding every record `folds` times.
+    pub fn pins(&self, folds: usize) -> Vec<Pins> {
+        let mut scratch = Scratch::default();
+        self.records
+            .iter()
+            .map(|x| {
+                scratch.unfold(x, folds);
+                scratch.propagate()
+            })
+            .collect()
+    }
+
+    /// Like [Self::arrangements], but gives up on any record that takes
+    /// longer than `budget` or whose count overflows, with an error naming
+    /// the line it came from.
+    pub fn arrangements_within(&self, folds: usize, budget: Duration) -> Result<Vec<u64>> {
+        self.records
+            .par_iter()
+            .map_init(Scratch::default, |scratch, x| {
+                let deadline = Instant::now() + budget;
+                x.arrangements_before(folds, Some(deadline), scratch)
+                    .map_err(|e| match e {
+                        Interrupted::Deadline => anyhow!(
+                            "line {}: gave up after {:?} ({} cells to fill in)",
+                            x.line,
+                            budget,
+                            x.table_size(folds)
+                        ),
+                        Interrupted::Overflow => overflow(x.line, folds),
+                    })
+            })
+            .collect()
+    }
+
+    fn sum_arrangements(&self, folds: usize) -> Result<u64> {
+        self.records
+            .par_iter()
+            .map_init(Scratch::default, |scratch, x| {
+                x.arrangements_with(folds, scratch)
+                    .ok_or_else(|| overflow(x.line, folds))
+            })
+            .try_reduce(
+                || 0,
+                |a, b| {
+                    a.checked_add(b)
+                        .ok_or_else(|| anyhow!("the total arrangements overflow"))
+                },
+            )
     }
 }
 
+fn overflow(line: usize, folds: usize) -> anyhow::Error {
+    anyhow!(
+        "line {}: too many arrangements to count when unfolded {} times",
+        line,
+        folds
+    )
+}
+
 impl FromStr for HotSprings {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let records = s
             .lines()
-            .map(Record::from_str)
-            .collect::<Result<Vec<_>, _>>()?;
+            .enumerate()
+            .map(|(i, line)| {
+                let mut record = Record::from_str(line)?;
+                record.line = i + 1;
+                Ok(record)
+            })
+            .collect::<Result<Vec<_>>>()?;
         Ok(Self { records })
     }
 }
 
 impl Problem for HotSprings {
+    const YEAR: usize = 2023;
     const DAY: usize = 12;
     const TITLE: &'static str = "hot springs";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.sum_arrangements(1))
+        self.sum_arrangements(1)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        Ok(self.sum_arrangements(5))
+        self.sum_arrangements(5)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use aoc_plumbing::Solution;
+    use proptest::prelude::*;
 
     use super::*;
 
@@ -222,20 +503,20 @@ mod tests {
 
     #[test]
     fn arrangements_test() {
-        let mut record = Record::from_str("### 3").unwrap();
-        assert_eq!(record.arrangements(1), 1);
-
-        record = Record::from_str("?. 1").unwrap();
-        assert_eq!(record.arrangements(1), 1);
-
-        record = Record::from_str(".### 3").unwrap();
-        assert_eq!(record.arrangements(1), 1);
-
-        record = Record::from_str("?.# 1,1").unwrap();
-        assert_eq!(record.arrangements(1), 1);
+        let mut scratch = Scratch::default();
+        let mut count = |s: &str| {
+            Record::from_str(s)
+                .unwrap()
+                .arrangements_with(1, &mut scratch)
+                .unwrap()
+        };
 
-        record = Record::from_str("?...??#??. 1,5").unwrap();
-        assert_eq!(record.arrangements(1), 1);
+        assert_eq!(count("### 3"), 1);
+        assert_eq!(count("?. 1"), 1);
+        assert_eq!(count(".### 3"), 1);
+        assert_eq!(count("?.# 1,1"), 1);
+        assert_eq!(count("?...??#??. 1,5"), 1);
+        assert_eq!(count("?###???????? 3,2,1"), 10);
     }
 
     #[test]
@@ -244,4 +525,172 @@ mod tests {
         let solution = HotSprings::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(21, 525152));
     }
+
+    #[test]
+    fn per_record_arrangements() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let springs = HotSprings::from_str(&input).unwrap();
+
+        let some = |counts: Vec<u64>| counts.into_iter().map(Some).collect::<Vec<_>>();
+        assert_eq!(springs.arrangements(1), some(vec![1, 4, 1, 1, 4, 10]));
+        assert_eq!(
+            springs.arrangements(5),
+            some(vec![1, 16384, 1, 16, 2500, 506250])
+        );
+        assert_eq!(springs.arrangements(0), vec![Some(1); 6]);
+    }
+
+    #[test]
+    fn overflow() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let springs = HotSprings::from_str(&input).unwrap();
+
+        // .??..??...?##. 1,1,3 has 4 arrangements, and 4 * 8^(k - 1) when
+        // unfolded k times, so 21 folds is 2^62 and 22 is 2^65
+        assert_eq!(springs.arrangements(21)[1], Some(1 << 62));
+        let overflowed = springs.arrangements(22);
+        assert_eq!(overflowed[0], Some(1));
+        assert_eq!(overflowed[1], None);
+
+        let err = springs
+            .arrangements_within(22, Duration::from_secs(10))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2: too many arrangements to count when unfolded 22 times"
+        );
+        assert!(springs.sum_arrangements(22).is_err());
+
+        // each record fits, but not their sum
+        let line = input.lines().nth(1).unwrap();
+        let springs = HotSprings::from_str(&[line; 4].join("\n")).unwrap();
+        assert_eq!(springs.arrangements(21), vec![Some(1 << 62); 4]);
+        assert_eq!(
+            springs.sum_arrangements(21).unwrap_err().to_string(),
+            "the total arrangements overflow"
+        );
+        let springs = HotSprings::from_str(&[line; 3].join("\n")).unwrap();
+        assert_eq!(springs.sum_arrangements(21).unwrap(), 3 << 62);
+    }
+
+    #[test]
+    fn budget() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let springs = HotSprings::from_str(&input).unwrap();
+
+        // ???.### 1,1,3 has 7 springs and 3 groups
+        assert_eq!(springs.estimated_cells(1)[0], 8 * 4);
+        assert_eq!(springs.estimated_cells(5)[0], 40 * 16);
+        assert_eq!(springs.estimated_cells(0)[0], 1);
+
+        assert_eq!(
+            springs
+                .arrangements_within(5, Duration::from_secs(10))
+                .unwrap()
+                .into_iter()
+                .map(Some)
+                .collect::<Vec<_>>(),
+            springs.arrangements(5)
+        );
+
+        let input = format!(
+            "{}\n{} {}",
+            input.trim_end(),
+            "?".repeat(5_000),
+            vec!["1"; 1_000].join(",")
+        );
+        let springs = HotSprings::from_str(&input).unwrap();
+        assert_eq!(springs.estimated_cells(1)[6], 5_001 * 1_001);
+        let err = springs
+            .arrangements_within(1, Duration::from_millis(1))
+            .unwrap_err();
+        assert!(err.to_string().starts_with("line 7: gave up after"));
+    }
+
+    #[test]
+    fn pins() {
+        let springs = HotSprings::from_str("?###???????? 3,2,1").unwrap();
+        // the 3 can only go in one place, which rules out the springs on
+        // either side of it
+        assert_eq!(
+            springs.pins(1),
+            vec![Pins {
+                damaged: 0,
+                operational: 2
+            }]
+        );
+
+        let springs = HotSprings::from_str("??????? 2,1,2\n??????? 1\n??.? 3").unwrap();
+        assert_eq!(
+            springs.pins(1),
+            vec![
+                Pins {
+                    damaged: 5,
+                    operational: 2
+                },
+                Pins::default(),
+                Pins::default()
+            ]
+        );
+        assert_eq!(springs.arrangements(1), vec![Some(1), Some(7), Some(0)]);
+
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let springs = HotSprings::from_str(&input).unwrap();
+        // ???.### 1,1,3 only has one arrangement, which propagation finds
+        // outright
+        assert_eq!(
+            springs.pins(1)[0],
+            Pins {
+                damaged: 2,
+                operational: 1
+            }
+        );
+        assert_eq!(
+            springs.pins(5)[0],
+            Pins {
+                damaged: 10,
+                operational: 9
+            }
+        );
+    }
+
+    /// Counts arrangements by trying every assignment of the unknowns.
+    fn brute_force(record: &Record) -> u64 {
+        let unknowns: Vec<usize> = (0..record.springs.len())
+            .filter(|&i| record.springs[i] == Spring::Unknown)
+            .collect();
+
+        (0..1_u32 << unknowns.len())
+            .filter(|mask| {
+                let mut springs = record.springs.clone();
+                for (bit, &i) in unknowns.iter().enumerate() {
+                    if mask & (1 << bit) != 0 {
+                        springs[i] = Spring::Damaged;
+                    }
+                }
+
+                let groups: Vec<usize> = springs
+                    .split(|x| !x.damaged())
+                    .map(|x| x.len())
+                    .filter(|x| *x > 0)
+                    .collect();
+                groups == record.groups
+            })
+            .count() as u64
+    }
+
+    proptest! {
+        #[test]
+        fn propagation_keeps_counts(
+            springs in "[.#?]{1,14}",
+            groups in prop::collection::vec(1..5_usize, 1..5),
+        ) {
+            let groups = groups.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+            let record = Record::from_str(&format!("{} {}", springs, groups.join(","))).unwrap();
+            let mut scratch = Scratch::default();
+            prop_assert_eq!(record.arrangements_with(1, &mut scratch), Some(brute_force(&record)));
+        }
+    }
+
+    aoc_plumbing::parsing_never_panics!(HotSprings);
 }
@@ -2,7 +2,8 @@ use std::fmt;
 use std::str::FromStr;
 
 use anyhow::bail;
-use aoc_plumbing::Problem;
+use aoc_common::grid::check_shape;
+use aoc_plumbing::{sections::par_parse_sections, Problem};
 use rayon::prelude::*;
 
 #[derive(Debug, Clone)]
@@ -60,6 +61,111 @@ impl Pattern {
     }
 }
 
+/// A line of reflection in a pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reflection {
+    /// Between rows, with this many rows above the line.
+    Horizontal(usize),
+    /// Between columns, with this many columns to the left of the line.
+    Vertical(usize),
+}
+
+impl Reflection {
+    /// The value this reflection contributes to the puzzle's summary.
+    pub fn summary(&self) -> usize {
+        match self {
+            Self::Horizontal(rows) => rows * 100,
+            Self::Vertical(cols) => *cols,
+        }
+    }
+}
+
+impl Pattern {
+    /// Returns every line of reflection across which exactly `errors` cells
+    /// fail to match their reflection, horizontal ones first.
+    fn reflections(&self, errors: u32) -> Vec<Reflection> {
+        Self::axes_helper(&self.rows, errors)
+            .map(Reflection::Horizontal)
+            .chain(Self::axes_helper(&self.cols, errors).map(Reflection::Vertical))
+            .collect()
+    }
+
+    /// Returns the summary value of every axis across which exactly `errors`
+    /// cells fail to match their reflection.
+    fn axes_with_errors(&self, errors: u32) -> Vec<usize> {
+        self.reflections(errors)
+            .iter()
+            .map(Reflection::summary)
+            .collect()
+    }
+
+    fn axes_helper(slice: &[u32], errors: u32) -> impl Iterator<Item = usize> + '_ {
+        let n = slice.len();
+
+        (1..n).filter(move |&i| {
+            (0..i.min(n - i))
+                .map(|j| (slice[i - 1 - j] ^ slice[i + j]).count_ones())
+                .sum::<u32>()
+                == errors
+        })
+    }
+}
+
+/// A way in which a pattern breaks the puzzle's guarantees. Axes are given
+/// as their summary values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AxisWarning {
+    NoReflection {
+        pattern: usize,
+    },
+    MultipleReflections {
+        pattern: usize,
+        axes: Vec<usize>,
+    },
+    NoSmudge {
+        pattern: usize,
+    },
+    MultipleSmudges {
+        pattern: usize,
+        axes: Vec<usize>,
+    },
+    /// The smudge search settled on the same axis as part one.
+    SameAxis {
+        pattern: usize,
+        axis: usize,
+    },
+}
+
+impl fmt::Display for AxisWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoReflection { pattern } => {
+                write!(f, "pattern {} has no reflection", pattern)
+            }
+            Self::MultipleReflections { pattern, axes } => {
+                write!(
+                    f,
+                    "pattern {} has multiple reflections: {:?}",
+                    pattern, axes
+                )
+            }
+            Self::NoSmudge { pattern } => {
+                write!(f, "pattern {} has no smudged reflection", pattern)
+            }
+            Self::MultipleSmudges { pattern, axes } => write!(
+                f,
+                "pattern {} has multiple smudged reflections: {:?}",
+                pattern, axes
+            ),
+            Self::SameAxis { pattern, axis } => write!(
+                f,
+                "pattern {} has the same axis, {}, for both parts",
+                pattern, axis
+            ),
+        }
+    }
+}
+
 impl fmt::Display for Pattern {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for row in &self.rows {
@@ -74,10 +180,16 @@ impl FromStr for Pattern {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // rows and columns are stored as bitmasks
+        let (n, m) = check_shape(s)?;
+        if n > u32::BITS as usize || m > u32::BITS as usize {
+            bail!("pattern is {}x{}, but can be at most 32x32", n, m);
+        }
+
         let mut rows = Vec::default();
-        let mut cols = Vec::default();
+        let mut cols = vec![0; m];
 
-        for (i, line) in s.lines().enumerate() {
+        for line in s.lines() {
             let mut row = 0;
 
             for (j, c) in line.chars().enumerate() {
@@ -88,10 +200,6 @@ impl FromStr for Pattern {
                         _ => bail!("invalid char"),
                     };
 
-                if i == 0 {
-                    cols.push(0);
-                }
-
                 cols[j] = cols[j] << 1
                     | match c {
                         '.' => 0,
@@ -116,26 +224,83 @@ pub struct PointOfIncidence {
     patterns: Vec<Pattern>,
 }
 
+impl PointOfIncidence {
+    /// Returns every perfect line of reflection in each pattern.
+    pub fn reflections(&self) -> Vec<Vec<Reflection>> {
+        self.patterns.iter().map(|p| p.reflections(0)).collect()
+    }
+
+    /// Returns, for each pattern, every line of reflection that would be
+    /// perfect if exactly one smudge were fixed. These are never the same as
+    /// the perfect ones, as fixing a smudge always changes the pattern on
+    /// one side of the line.
+    pub fn smudged_reflections(&self) -> Vec<Vec<Reflection>> {
+        self.patterns.iter().map(|p| p.reflections(1)).collect()
+    }
+
+    /// Checks that every pattern has exactly one reflection, exactly one
+    /// reflection that needs a single smudge fixed, and that the two differ.
+    /// Patterns that don't would otherwise quietly contribute the wrong value
+    /// (or nothing) to the sums.
+    pub fn verify_axes(&self) -> Vec<AxisWarning> {
+        let mut ret = Vec::default();
+
+        for (idx, pattern) in self.patterns.iter().enumerate() {
+            let perfect = pattern.axes_with_errors(0);
+            match perfect.len() {
+                0 => ret.push(AxisWarning::NoReflection { pattern: idx }),
+                1 => (),
+                _ => ret.push(AxisWarning::MultipleReflections {
+                    pattern: idx,
+                    axes: perfect,
+                }),
+            }
+
+            let smudged = pattern.axes_with_errors(1);
+            match smudged.len() {
+                0 => ret.push(AxisWarning::NoSmudge { pattern: idx }),
+                1 => (),
+                _ => ret.push(AxisWarning::MultipleSmudges {
+                    pattern: idx,
+                    axes: smudged,
+                }),
+            }
+
+            let mut solver = pattern.clone();
+            solver.original_inflection = solver.inflection();
+            if let Some(axis) = solver.original_inflection {
+                if solver.inflection_with_smudge() == Some(axis) {
+                    ret.push(AxisWarning::SameAxis { pattern: idx, axis });
+                }
+            }
+        }
+
+        ret
+    }
+}
+
 impl FromStr for PointOfIncidence {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let patterns = s
-            .split("\n\n")
-            .map(Pattern::from_str)
-            .collect::<Result<Vec<_>, _>>()?;
+        let patterns = par_parse_sections(s, Pattern::from_str)?;
+        if patterns.is_empty() {
+            bail!("no patterns in input");
+        }
+
         Ok(Self { patterns })
     }
 }
 
 impl Problem for PointOfIncidence {
+    const YEAR: usize = 2023;
     const DAY: usize = 13;
     const TITLE: &'static str = "point of incidence";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
         let mut sum = 0;
@@ -144,7 +309,7 @@ impl Problem for PointOfIncidence {
             pattern.original_inflection = result;
 
             if let Some(x) = result {
-                sum += x;
+                sum += x as u64;
             }
         }
 
@@ -155,16 +320,16 @@ impl Problem for PointOfIncidence {
         Ok(self
             .patterns
             .par_iter_mut()
-            .map(|x| x.inflection_with_smudge().unwrap_or_default())
+            .map(|x| x.inflection_with_smudge().unwrap_or_default() as u64)
             .sum())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::{differential::assert_agree, Solution};
+    use aoc_testing::{day13::Patterns, Generator};
 
     #[test]
     #[ignore]
@@ -181,6 +346,46 @@ mod tests {
         assert_eq!(solution, Solution::new(405, 400));
     }
 
+    #[test]
+    fn verify_axes() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = PointOfIncidence::instance(&input).unwrap();
+        assert!(instance.verify_axes().is_empty());
+
+        let input = "#.#
+...
+###
+
+##
+##";
+        let instance = PointOfIncidence::instance(input).unwrap();
+        assert_eq!(
+            instance.verify_axes(),
+            vec![
+                AxisWarning::NoReflection { pattern: 0 },
+                // the first and second columns each differ from the middle
+                // one by a single cell
+                AxisWarning::MultipleSmudges {
+                    pattern: 0,
+                    axes: vec![1, 2]
+                },
+                AxisWarning::MultipleReflections {
+                    pattern: 1,
+                    axes: vec![100, 1]
+                },
+                AxisWarning::NoSmudge { pattern: 1 },
+            ]
+        );
+        assert_eq!(
+            instance.reflections()[1],
+            vec![Reflection::Horizontal(1), Reflection::Vertical(1)]
+        );
+        assert_eq!(
+            instance.verify_axes()[0].to_string(),
+            "pattern 0 has no reflection"
+        );
+    }
+
     #[test]
     fn example_two() {
         let input = "##..#.######..##.
@@ -209,6 +414,22 @@ mod tests {
 ##.#..###
 ##...####";
 
+        let instance = PointOfIncidence::instance(input).unwrap();
+        assert_eq!(
+            instance.reflections(),
+            vec![
+                vec![Reflection::Horizontal(10)],
+                vec![Reflection::Vertical(8)],
+            ]
+        );
+        assert_eq!(
+            instance.smudged_reflections(),
+            vec![
+                vec![Reflection::Horizontal(5)],
+                vec![Reflection::Horizontal(5)]
+            ]
+        );
+
         let solution = PointOfIncidence::solve(input).unwrap();
         assert_eq!(solution, Solution::new(1008, 1000));
     }
@@ -240,4 +461,46 @@ mod tests {
         let solution = PointOfIncidence::solve(input).unwrap();
         assert_eq!(solution, Solution::new(809, 1111));
     }
+
+    #[test]
+    fn popcount_matches_flipping() {
+        // only patterns that keep the puzzle's guarantees have a single
+        // answer to agree on
+        let generate = |seed| {
+            let input = Patterns::default().generate_seeded(seed);
+            input
+                .split("\n\n")
+                .filter(|p| {
+                    PointOfIncidence::instance(p)
+                        .unwrap()
+                        .verify_axes()
+                        .is_empty()
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        };
+
+        assert_agree(
+            0..50,
+            generate,
+            |input| PointOfIncidence::solve(input).unwrap().part_two,
+            |input| {
+                PointOfIncidence::instance(input)
+                    .unwrap()
+                    .smudged_reflections()
+                    .iter()
+                    .flatten()
+                    .map(|r| r.summary() as u64)
+                    .sum::<u64>()
+            },
+        );
+
+        // most patterns should make it through the filter
+        let kept = (0..50)
+            .map(|seed| generate(seed).split("\n\n").count())
+            .sum::<usize>();
+        assert!(kept > 50 * 80, "only kept {} patterns", kept);
+    }
+
+    aoc_plumbing::parsing_never_panics!(PointOfIncidence);
 }
@@ -1,11 +1,11 @@
-use std::str::FromStr;
+use std::{borrow::Cow, str::FromStr, sync::Arc};
 
 use anyhow::bail;
 use aoc_common::{
     direction::Cardinal,
     grid::{Coordinate, Grid},
 };
-use aoc_plumbing::Problem;
+use aoc_plumbing::{Description, Problem};
 use rustc_hash::FxHashMap;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -42,66 +42,220 @@ impl TryFrom<char> for Tile {
     }
 }
 
+/// How the round rocks are moved when the platform is tilted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TiltStrategy {
+    /// Walk every row or column of the grid, moving rocks as they are found.
+    #[default]
+    GridWalk,
+    /// Precompute the runs of cells between cube rocks along every row and
+    /// column, and only track the round rocks, so a tilt is `O(rounds)`
+    /// rather than `O(n * m)`.
+    Segments,
+}
+
 #[derive(Debug, Clone)]
 pub struct ParabolicReflectorDish {
+    /// With [TiltStrategy::Segments] the round rocks here are stale, and
+    /// `segments` has where they really are.
     platform: Grid<Tile>,
+    segments: Option<Segments>,
 }
 
 impl ParabolicReflectorDish {
-    fn total_load(&self) -> usize {
-        let mut total = 0;
-
-        for j in 0..self.platform.m {
-            for i in 0..self.platform.n {
-                let coord = (i, j).into();
-                if self.platform[coord] == Tile::Round {
-                    total += self.platform.n - i;
-                }
+    /// Uses `strategy` for every tilt from now on.
+    pub fn with_strategy(mut self, strategy: TiltStrategy) -> Self {
+        if let Some(segments) = self.segments.take() {
+            set_round_cells(&mut self.platform, &segments.rocks);
+        }
+        if strategy == TiltStrategy::Segments {
+            self.segments = Some(Segments::new(&self.platform));
+        }
+        self
+    }
+
+    /// The platform with the round rocks where they currently are.
+    fn platform(&self) -> Cow<'_, Grid<Tile>> {
+        match &self.segments {
+            None => Cow::Borrowed(&self.platform),
+            Some(segments) => {
+                let mut platform = self.platform.clone();
+                set_round_cells(&mut platform, &segments.rocks);
+                Cow::Owned(platform)
             }
         }
+    }
+
+    /// The load on the north support beams.
+    pub fn total_load(&self) -> usize {
+        if let Some(segments) = &self.segments {
+            return segments.load(&segments.rocks);
+        }
 
-        total
+        self.platform
+            .grid
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                (self.platform.n - i) * row.iter().filter(|t| **t == Tile::Round).count()
+            })
+            .sum()
     }
-    fn cycle(&mut self, cycles: usize) {
-        let mut cache = FxHashMap::default();
-        let mut key = u128::MAX;
-        let mut period = 0;
-        let mut start = 0;
-
-        // first find the cycle start and period
-        for i in 0..cycles {
-            self.tilt(Cardinal::North);
-            self.tilt(Cardinal::West);
-            self.tilt(Cardinal::South);
-            self.tilt(Cardinal::East);
-
-            // keep a key of the last 5 loads
-            key = key << 32 | self.total_load() as u128;
-
-            if i == cycles - 1 {
-                return;
+
+    /// Finds `(start, period)` such that the platform after `start` spin
+    /// cycles is the same as after `start + period` cycles, with `start` as
+    /// small as possible.
+    pub fn detect_cycle(&self) -> (usize, usize) {
+        match &self.segments {
+            None => {
+                let (_, start, period) = self.spin_until_repeat(|_| ());
+                (start, period)
+            }
+            Some(segments) => {
+                let (_, start, period) = segments.spin_until_repeat();
+                (start, period)
+            }
+        }
+    }
+
+    /// Returns the north support load after each spin cycle, starting from
+    /// the first, forever. Once the platform starts repeating, the loads
+    /// are read back from the cycle, so this never spins the platform more
+    /// than [ParabolicReflectorDish::detect_cycle] does.
+    pub fn loads(&self) -> Loads {
+        let mut loads = Vec::default();
+        let (start, period) = match &self.segments {
+            None => {
+                let (_, start, period) =
+                    self.spin_until_repeat(|platform| loads.push(platform.total_load()));
+                (start, period)
+            }
+            Some(segments) => {
+                let (states, start, period) = segments.spin_until_repeat();
+                loads.extend(states.iter().map(|rocks| segments.load(rocks)));
+                (start, period)
             }
+        };
+
+        Loads {
+            loads,
+            start,
+            period,
+            next: 1,
+        }
+    }
+
+    /// Spins a copy of the platform until it gets back to a state it has
+    /// been in, calling `visit` on every distinct state along the way.
+    /// Returns the repeated state, which is the platform after `start`
+    /// cycles, along with the cycle like
+    /// [ParabolicReflectorDish::detect_cycle].
+    fn spin_until_repeat(&self, mut visit: impl FnMut(&Self)) -> (Self, usize, usize) {
+        let mut platform = self.clone();
+        let mut seen = FxHashMap::default();
+        let mut i = 0;
+
+        loop {
+            if let Some(&start) = seen.get(&platform.platform.grid) {
+                return (platform, start, i - start);
+            }
+
+            visit(&platform);
+            seen.insert(platform.platform.grid.clone(), i);
+            platform.spin();
+            i += 1;
+        }
+    }
+
+    /// Returns the platform after `k` full spin cycles, leaving this one
+    /// untouched.
+    pub fn state_at_cycle(&self, k: usize) -> Self {
+        match &self.segments {
+            None => {
+                // carry on from where detecting the cycle left off, unless
+                // it's before the cycle starts
+                let (repeated, start, period) = self.spin_until_repeat(|_| ());
+                let (mut platform, spins) = if k >= start {
+                    (repeated, (k - start) % period)
+                } else {
+                    (self.clone(), k)
+                };
+                for _ in 0..spins {
+                    platform.spin();
+                }
+                platform
+            }
+            Some(segments) => {
+                let (mut states, start, period) = segments.spin_until_repeat();
+                let at = if k > start {
+                    start + (k - start) % period
+                } else {
+                    k
+                };
+                let mut platform = self.clone();
+                if let Some(segments) = &mut platform.segments {
+                    segments.rocks = states.swap_remove(at);
+                }
+                platform
+            }
+        }
+    }
 
-            if let Some(&x) = cache.get(&key) {
-                period = i - x;
-                start = x;
-                break;
-            } else {
-                cache.insert(key, i);
+    /// Renders the platform before any tilts and after every tilt in the
+    /// first `cycles` spin cycles.
+    #[cfg(feature = "visualize")]
+    pub fn render_frames<R: aoc_common::visualize::Renderer>(
+        &self,
+        renderer: &mut R,
+        cycles: usize,
+    ) -> std::io::Result<()> {
+        use aoc_common::visualize::frame;
+
+        let glyph = |t: &Tile| match t {
+            Tile::Round => 'O',
+            Tile::Cube => '#',
+            Tile::Empty => '.',
+        };
+        let mut platform = self.clone();
+        renderer.render(&frame(&platform.platform(), glyph))?;
+
+        for _ in 0..cycles {
+            for dir in [
+                Cardinal::North,
+                Cardinal::West,
+                Cardinal::South,
+                Cardinal::East,
+            ] {
+                platform.tilt(dir);
+                renderer.render(&frame(&platform.platform(), glyph))?;
             }
         }
 
-        // then jump ahead and process the remaining cycles
-        let remaining = (cycles - start - 1) % period;
-        for _ in 0..remaining {
-            self.tilt(Cardinal::North);
-            self.tilt(Cardinal::West);
-            self.tilt(Cardinal::South);
-            self.tilt(Cardinal::East);
+        Ok(())
+    }
+
+    fn cycle(&mut self, cycles: usize) {
+        *self = self.state_at_cycle(cycles);
+    }
+
+    fn spin(&mut self) {
+        if let Some(segments) = &mut self.segments {
+            segments.spin();
+            return;
         }
+
+        self.tilt(Cardinal::North);
+        self.tilt(Cardinal::West);
+        self.tilt(Cardinal::South);
+        self.tilt(Cardinal::East);
     }
 
     fn tilt(&mut self, dir: Cardinal) {
+        if let Some(segments) = &mut self.segments {
+            segments.slide(dir);
+            return;
+        }
+
         match dir {
             Cardinal::North => self.tilt_helper(true, false),
             Cardinal::South => self.tilt_helper(true, true),
@@ -156,39 +310,266 @@ impl ParabolicReflectorDish {
     }
 }
 
+/// The runs of non-cube cells along every column and row of a platform, for
+/// [TiltStrategy::Segments]. Cells are indexed as `row * m + col`.
+#[derive(Debug)]
+struct RockSlide {
+    n: usize,
+    m: usize,
+    // the segment each cell is in, along columns then rows
+    ids: [Vec<usize>; 2],
+    // the first cell and length of each segment, along columns then rows
+    segments: [Vec<(usize, usize)>; 2],
+}
+
+impl RockSlide {
+    fn new(platform: &Grid<Tile>) -> Self {
+        let (n, m) = (platform.n, platform.m);
+        let mut ids = [vec![0; n * m], vec![0; n * m]];
+        let mut segments: [Vec<(usize, usize)>; 2] = Default::default();
+
+        // (lines, cells per line, strides between lines and between cells)
+        let axes = [(m, n, 1, m), (n, m, m, 1)];
+
+        for (axis, (lines, len, line_stride, cell_stride)) in axes.into_iter().enumerate() {
+            for i in 0..lines {
+                let mut run = None;
+                for j in 0..len {
+                    let c = i * line_stride + j * cell_stride;
+                    if platform.grid[c / m][c % m] == Tile::Cube {
+                        run = None;
+                        continue;
+                    }
+
+                    let id = *run.get_or_insert_with(|| {
+                        segments[axis].push((c, 0));
+                        segments[axis].len() - 1
+                    });
+                    segments[axis][id].1 += 1;
+                    ids[axis][c] = id;
+                }
+            }
+        }
+
+        Self {
+            n,
+            m,
+            ids,
+            segments,
+        }
+    }
+}
+
+/// The round rocks for [TiltStrategy::Segments]. The cube rocks never move,
+/// so the [RockSlide] is worked out once and shared between copies.
+#[derive(Debug, Clone)]
+struct Segments {
+    slide: Arc<RockSlide>,
+    // the cell indices of the round rocks, grouped by the segment they were
+    // last slid into
+    rocks: Vec<usize>,
+    // scratch space for a slide, the rocks in each segment and the segments
+    // with any rocks
+    counts: Vec<usize>,
+    touched: Vec<usize>,
+}
+
+impl Segments {
+    fn new(platform: &Grid<Tile>) -> Self {
+        let slide = RockSlide::new(platform);
+        let rocks = platform
+            .find_all(|t| *t == Tile::Round)
+            .iter()
+            .map(|c| c.row() as usize * platform.m + c.col() as usize)
+            .collect();
+        let most = slide.segments[0].len().max(slide.segments[1].len());
+
+        Self {
+            slide: Arc::new(slide),
+            rocks,
+            counts: vec![0; most],
+            touched: Vec::default(),
+        }
+    }
+
+    /// The load on the north support beams with the round rocks at `rocks`.
+    fn load(&self, rocks: &[usize]) -> usize {
+        rocks.iter().map(|r| self.slide.n - r / self.slide.m).sum()
+    }
+
+    /// Slides the rocks as far as they go towards `dir`.
+    fn slide(&mut self, dir: Cardinal) {
+        let (axis, stride) = match dir {
+            Cardinal::North | Cardinal::South => (0, self.slide.m),
+            Cardinal::West | Cardinal::East => (1, 1),
+        };
+        let towards_start = matches!(dir, Cardinal::North | Cardinal::West);
+
+        for &r in self.rocks.iter() {
+            let id = self.slide.ids[axis][r];
+            if self.counts[id] == 0 {
+                self.touched.push(id);
+            }
+            self.counts[id] += 1;
+        }
+
+        self.rocks.clear();
+        for id in self.touched.drain(..) {
+            let (first, len) = self.slide.segments[axis][id];
+            let count = std::mem::take(&mut self.counts[id]);
+            let offset = if towards_start { 0 } else { len - count };
+            self.rocks
+                .extend((offset..offset + count).map(|k| first + k * stride));
+        }
+    }
+
+    /// A full spin cycle, leaving the rocks sorted.
+    fn spin(&mut self) {
+        for dir in [
+            Cardinal::North,
+            Cardinal::West,
+            Cardinal::South,
+            Cardinal::East,
+        ] {
+            self.slide(dir);
+        }
+        self.rocks.sort_unstable();
+    }
+
+    /// Like [ParabolicReflectorDish::spin_until_repeat], returning the sorted
+    /// rocks of every distinct state along the way, along with the cycle.
+    fn spin_until_repeat(&self) -> (Vec<Vec<usize>>, usize, usize) {
+        let mut current = self.clone();
+        current.rocks.sort_unstable();
+        let mut states = Vec::default();
+        let mut seen = FxHashMap::default();
+
+        loop {
+            if let Some(&start) = seen.get(&current.rocks) {
+                let period = states.len() - start;
+                return (states, start, period);
+            }
+
+            seen.insert(current.rocks.clone(), states.len());
+            states.push(current.rocks.clone());
+            current.spin();
+        }
+    }
+}
+
+/// Moves the round rocks on `platform` to the given cell indices.
+fn set_round_cells(platform: &mut Grid<Tile>, rocks: &[usize]) {
+    for tile in platform.grid.iter_mut().flatten() {
+        if *tile == Tile::Round {
+            *tile = Tile::Empty;
+        }
+    }
+
+    let m = platform.m;
+    for r in rocks {
+        platform.grid[r / m][r % m] = Tile::Round;
+    }
+}
+
+/// The north support load after each spin cycle, see
+/// [ParabolicReflectorDish::loads].
+#[derive(Debug, Clone)]
+pub struct Loads {
+    // the load after each cycle up to the first repeat, starting at zero
+    loads: Vec<usize>,
+    start: usize,
+    period: usize,
+    // the number of cycles for the next item
+    next: usize,
+}
+
+impl Loads {
+    /// The `(start, period)` of the cycle, as from
+    /// [ParabolicReflectorDish::detect_cycle].
+    pub fn detected_cycle(&self) -> (usize, usize) {
+        (self.start, self.period)
+    }
+
+    /// The load after `k` spin cycles.
+    pub fn at(&self, k: usize) -> usize {
+        let k = if k > self.start {
+            self.start + (k - self.start) % self.period
+        } else {
+            k
+        };
+
+        self.loads[k]
+    }
+}
+
+impl Iterator for Loads {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let load = self.at(self.next);
+        self.next += 1;
+        Some(load)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.next = self.next.saturating_add(n);
+        self.next()
+    }
+}
+
+impl fmt::Display for ParabolicReflectorDish {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.platform())
+    }
+}
+
 impl FromStr for ParabolicReflectorDish {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Self {
-            platform: Grid::from_str(s)?,
+            platform: Grid::parse(s)?,
+            segments: None,
         })
     }
 }
 
 impl Problem for ParabolicReflectorDish {
+    const YEAR: usize = 2023;
     const DAY: usize = 14;
     const TITLE: &'static str = "parabolic reflector dish";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
         self.tilt(Cardinal::North);
-        Ok(self.total_load())
+        Ok(self.total_load() as u64)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
         self.cycle(1_000_000_000);
-        Ok(self.total_load())
+        Ok(self.total_load() as u64)
+    }
+
+    fn describe(&self) -> Description {
+        Description::default()
+            .grid(self.platform.n, self.platform.m)
+            .with("round rocks", self.platform.count(|t| *t == Tile::Round))
+            .with("cube rocks", self.platform.count(|t| *t == Tile::Cube))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use aoc_plumbing::Solution;
+    use proptest::prelude::*;
 
     use super::*;
 
@@ -198,6 +579,11 @@ mod tests {
         let input = std::fs::read_to_string("input.txt").expect("Unable to load input");
         let solution = ParabolicReflectorDish::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(108935, 100876));
+
+        let mut segments = ParabolicReflectorDish::instance(&input)
+            .unwrap()
+            .with_strategy(TiltStrategy::Segments);
+        assert_eq!(segments.part_two().unwrap(), 100876);
     }
 
     #[test]
@@ -206,4 +592,140 @@ mod tests {
         let solution = ParabolicReflectorDish::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(136, 64));
     }
+
+    #[test]
+    fn describe() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ParabolicReflectorDish::instance(&input).unwrap();
+        assert_eq!(
+            instance.describe().to_string(),
+            "grid: 10x10\nround rocks: 18\ncube rocks: 17"
+        );
+    }
+
+    #[test]
+    fn state_at_cycle() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ParabolicReflectorDish::instance(&input).unwrap();
+        let (start, period) = instance.detect_cycle();
+        assert_eq!((start, period), (3, 7));
+
+        // repeated queries agree, and do not disturb the original
+        let a = instance.state_at_cycle(1_000_000_000);
+        let b = instance.state_at_cycle(1_000_000_000);
+        assert_eq!(a.platform.grid, b.platform.grid);
+        assert_eq!(a.total_load(), 64);
+        assert_eq!(instance.to_string(), input);
+
+        let mut naive = instance.clone();
+        for k in 0..=start + 2 * period {
+            assert_eq!(
+                instance.state_at_cycle(k).platform.grid,
+                naive.platform.grid
+            );
+            if k >= start {
+                assert_eq!(
+                    instance.state_at_cycle(k + period).platform.grid,
+                    naive.platform.grid
+                );
+            }
+            naive.spin();
+        }
+    }
+
+    #[test]
+    fn loads() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ParabolicReflectorDish::instance(&input).unwrap();
+        let loads = instance.loads();
+        assert_eq!(loads.detected_cycle(), (3, 7));
+        assert_eq!(
+            loads.clone().take(17).collect::<Vec<_>>(),
+            vec![87, 69, 69, 69, 65, 64, 65, 63, 68, 69, 69, 65, 64, 65, 63, 68, 69]
+        );
+        assert_eq!(loads.at(0), instance.total_load());
+        assert_eq!(loads.at(1_000_000_000), 64);
+        assert_eq!(loads.clone().nth(999_999_999), Some(64));
+
+        for k in 0..30 {
+            assert_eq!(loads.at(k), instance.state_at_cycle(k).total_load());
+        }
+    }
+
+    #[test]
+    fn segments_match_grid_walk() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ParabolicReflectorDish::instance(&input).unwrap();
+        let segments = instance.clone().with_strategy(TiltStrategy::Segments);
+
+        assert_eq!(segments.detect_cycle(), (3, 7));
+        assert_eq!(
+            segments.loads().take(20).collect::<Vec<_>>(),
+            instance.loads().take(20).collect::<Vec<_>>()
+        );
+        for k in [0, 1, 2, 3, 10, 1_000_000_000] {
+            assert_eq!(
+                segments.state_at_cycle(k).platform().grid,
+                instance.state_at_cycle(k).platform.grid
+            );
+        }
+
+        // switching back puts the rocks the segments moved onto the grid
+        let mut walked = instance.clone();
+        walked.tilt(Cardinal::West);
+        let mut slid = segments.clone();
+        slid.tilt(Cardinal::West);
+        let slid = slid.with_strategy(TiltStrategy::GridWalk);
+        assert_eq!(slid.platform.grid, walked.platform.grid);
+        assert_eq!(slid.to_string(), walked.to_string());
+
+        let mut solution = segments.clone();
+        assert_eq!(solution.part_one().unwrap(), 136);
+        let mut solution = segments;
+        assert_eq!(solution.part_two().unwrap(), 64);
+    }
+
+    #[cfg(feature = "visualize")]
+    #[test]
+    fn render_frames() {
+        use aoc_common::visualize::TextRenderer;
+
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ParabolicReflectorDish::instance(&input).unwrap();
+        let mut renderer = TextRenderer::new(Vec::new());
+        instance.render_frames(&mut renderer, 2).unwrap();
+
+        let out = String::from_utf8(renderer.into_inner()).unwrap();
+        let frames = out
+            .split("\n\n")
+            .filter(|f| !f.is_empty())
+            .collect::<Vec<_>>();
+        assert_eq!(frames.len(), 9);
+        assert_eq!(
+            frames[8],
+            format!("frame 8\n{}", instance.state_at_cycle(2)).trim_end()
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn segments_tilt_like_grid_walk(
+            rows in prop::collection::vec("[O#.]{7}", 1..8),
+            dir in prop::sample::select(vec![
+                Cardinal::North,
+                Cardinal::West,
+                Cardinal::South,
+                Cardinal::East,
+            ]),
+        ) {
+            let instance = ParabolicReflectorDish::instance(&rows.join("\n")).unwrap();
+            let mut walked = instance.clone();
+            let mut segments = instance.with_strategy(TiltStrategy::Segments);
+            walked.tilt(dir);
+            segments.tilt(dir);
+            prop_assert_eq!(&walked.platform.grid, &segments.platform().grid);
+        }
+    }
+
+    aoc_plumbing::parsing_never_panics!(ParabolicReflectorDish);
 }
@@ -1,93 +1,238 @@
 use std::str::FromStr;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use aoc_plumbing::Problem;
+use rustc_hash::FxHashSet;
+use serde::Serialize;
 
 fn hash(s: &str) -> u8 {
     s.bytes().fold(0, |a, e| a.wrapping_add(e).wrapping_mul(17))
 }
 
-fn handle(token: &str, hashmap: &mut [Vec<(String, u8)>]) -> Result<()> {
-    if token.ends_with('-') {
-        let label = &token[0..token.len() - 1];
-        let key = hash(label);
-        let bucket = &mut hashmap[key as usize];
-        let index = (0..bucket.len()).find(|i| bucket[*i].0 == label);
+/// One step of the initialization sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// `label=focal_length`
+    Insert { label: String, focal_length: u8 },
+    /// `label-`
+    Remove { label: String },
+}
 
-        if let Some(i) = index {
-            bucket.remove(i);
+impl Step {
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Insert { label, .. } | Self::Remove { label } => label,
         }
-    } else {
-        let label = &token[0..token.len() - 2];
-        let key = hash(label);
-        let lens = token[token.len() - 1..].parse::<u8>()?;
-        let bucket = &mut hashmap[key as usize];
-        let index = (0..bucket.len()).find(|i| bucket[*i].0 == label);
-
-        if let Some(i) = index {
-            bucket[i].1 = lens;
+    }
+}
+
+impl FromStr for Step {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(label) = s.strip_suffix('-') {
+            Ok(Self::Remove {
+                label: label.to_owned(),
+            })
+        } else if let Some((label, lens)) = s.split_once('=') {
+            Ok(Self::Insert {
+                label: label.to_owned(),
+                focal_length: lens.parse()?,
+            })
         } else {
-            bucket.push((label.to_owned(), lens));
+            bail!("invalid step {:?}", s);
+        }
+    }
+}
+
+/// The 256 boxes the HASHMAP procedure arranges lenses in. Each box keeps
+/// its lenses in the order they were first inserted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LensBoxes {
+    boxes: Vec<Vec<(String, u8)>>,
+}
+
+impl Default for LensBoxes {
+    fn default() -> Self {
+        Self {
+            boxes: vec![Vec::default(); 256],
+        }
+    }
+}
+
+impl LensBoxes {
+    /// Puts a lens with `focal_length` in the box for `label`, replacing
+    /// the lens with the same label in place if there is one.
+    pub fn insert(&mut self, label: &str, focal_length: u8) {
+        let bucket = &mut self.boxes[hash(label) as usize];
+        match bucket.iter_mut().find(|(l, _)| l == label) {
+            Some(lens) => lens.1 = focal_length,
+            None => bucket.push((label.to_owned(), focal_length)),
+        }
+    }
+
+    /// Takes the lens with `label` out of its box, moving the lenses behind
+    /// it forward, and returns its focal length.
+    pub fn remove(&mut self, label: &str) -> Option<u8> {
+        let bucket = &mut self.boxes[hash(label) as usize];
+        let index = bucket.iter().position(|(l, _)| l == label)?;
+        Some(bucket.remove(index).1)
+    }
+
+    pub fn apply(&mut self, step: &Step) {
+        match step {
+            Step::Insert {
+                label,
+                focal_length,
+            } => self.insert(label, *focal_length),
+            Step::Remove { label } => {
+                self.remove(label);
+            }
         }
     }
 
-    Ok(())
+    /// The focal length of the lens with `label`, if it's in a box.
+    pub fn get(&self, label: &str) -> Option<u8> {
+        self.boxes[hash(label) as usize]
+            .iter()
+            .find(|(l, _)| l == label)
+            .map(|(_, lens)| *lens)
+    }
+
+    /// The labels and focal lengths of the lenses in box `idx`, front to
+    /// back.
+    pub fn lenses(&self, idx: u8) -> &[(String, u8)] {
+        &self.boxes[idx as usize]
+    }
+
+    /// The sum of the focusing power of every lens.
+    pub fn focusing_power(&self) -> usize {
+        self.boxes
+            .iter()
+            .enumerate()
+            .flat_map(|(i, bucket)| {
+                bucket
+                    .iter()
+                    .enumerate()
+                    .map(move |(j, &(_, lens))| (i + 1) * (j + 1) * lens as usize)
+            })
+            .sum()
+    }
+}
+
+/// How the HASH function spread the labels over the boxes. Derives
+/// [Serialize] so it can be dumped as e.g. JSON.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Stats {
+    /// The number of `=` operations.
+    pub inserts: usize,
+    /// The number of `-` operations.
+    pub removals: usize,
+    /// The number of distinct labels that hash to each box.
+    pub labels_per_box: Vec<usize>,
+    /// The most lenses in any one box at the end.
+    pub max_depth: usize,
+    /// The number of boxes with no lenses at the end.
+    pub empty_boxes: usize,
+    /// `occupancy[k]` is the number of boxes with `k` lenses at the end.
+    pub occupancy: Vec<usize>,
 }
 
 #[derive(Debug, Clone)]
 pub struct LensLibrary {
-    part_one: usize,
-    part_two: usize,
+    // the raw steps, which part one hashes as they are
+    tokens: Vec<String>,
+    steps: Vec<Step>,
 }
 
-impl FromStr for LensLibrary {
-    type Err = anyhow::Error;
+impl LensLibrary {
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tokens: Vec<_> = s.trim().split(',').collect();
-        let part_one = tokens.iter().map(|x| hash(x) as usize).sum();
+    /// Runs every step of the initialization sequence.
+    pub fn boxes(&self) -> LensBoxes {
+        let mut boxes = LensBoxes::default();
+        for step in self.steps.iter() {
+            boxes.apply(step);
+        }
+
+        boxes
+    }
 
-        let mut hashmap = vec![Vec::default(); 256];
-        for token in tokens {
-            handle(token, &mut hashmap)?;
+    fn hash_sum(&self) -> usize {
+        self.tokens.iter().map(|x| hash(x) as usize).sum()
+    }
+
+    /// Replays the instructions to work out how the labels were distributed.
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        let mut labels = vec![FxHashSet::default(); 256];
+        let mut boxes = LensBoxes::default();
+
+        for step in self.steps.iter() {
+            match step {
+                Step::Insert { .. } => stats.inserts += 1,
+                Step::Remove { .. } => stats.removals += 1,
+            }
+            labels[hash(step.label()) as usize].insert(step.label());
+            boxes.apply(step);
         }
 
-        let mut part_two = 0;
-        for (i, bucket) in hashmap.iter().enumerate() {
-            part_two += bucket
-                .iter()
-                .enumerate()
-                .map(|(j, &(_, lens))| (i + 1) * (j + 1) * lens as usize)
-                .sum::<usize>();
+        stats.labels_per_box = labels.iter().map(|x| x.len()).collect();
+        stats.max_depth = boxes
+            .boxes
+            .iter()
+            .map(|x| x.len())
+            .max()
+            .unwrap_or_default();
+        stats.empty_boxes = boxes.boxes.iter().filter(|x| x.is_empty()).count();
+        stats.occupancy = vec![0; stats.max_depth + 1];
+        for bucket in boxes.boxes.iter() {
+            stats.occupancy[bucket.len()] += 1;
         }
 
-        Ok(Self { part_one, part_two })
+        stats
+    }
+}
+
+impl FromStr for LensLibrary {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<String> = s.trim().split(',').map(String::from).collect();
+        let steps = tokens
+            .iter()
+            .map(|x| Step::from_str(x))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { tokens, steps })
     }
 }
 
 impl Problem for LensLibrary {
+    const YEAR: usize = 2023;
     const DAY: usize = 15;
     const TITLE: &'static str = "lens library";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.part_one)
+        Ok(self.hash_sum() as u64)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        Ok(self.part_two)
+        Ok(self.boxes().focusing_power() as u64)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::Solution;
 
     #[test]
     #[ignore]
@@ -103,4 +248,65 @@ mod tests {
         let solution = LensLibrary::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(1320, 145));
     }
+
+    #[test]
+    fn stats() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let stats = LensLibrary::instance(&input).unwrap().stats();
+
+        assert_eq!(stats.inserts, 8);
+        assert_eq!(stats.removals, 3);
+        // rn and cm go in box 0, qp in box 1 and the rest in box 3
+        assert_eq!(stats.labels_per_box[0], 2);
+        assert_eq!(stats.labels_per_box[1], 1);
+        assert_eq!(stats.labels_per_box[3], 3);
+        assert_eq!(stats.labels_per_box.iter().sum::<usize>(), 6);
+        assert_eq!(stats.max_depth, 3);
+        assert_eq!(stats.empty_boxes, 254);
+        assert_eq!(stats.occupancy, vec![254, 0, 1, 1]);
+    }
+
+    #[test]
+    fn lens_boxes() {
+        let mut boxes = LensBoxes::default();
+        boxes.insert("rn", 1);
+        boxes.insert("cm", 2);
+        boxes.insert("qp", 3);
+        assert_eq!(boxes.remove("cm"), Some(2));
+        assert_eq!(boxes.remove("cm"), None);
+        boxes.insert("pc", 4);
+        boxes.insert("ot", 9);
+        boxes.insert("ab", 5);
+        boxes.insert("ot", 7);
+        assert_eq!(boxes.remove("pc"), Some(4));
+        boxes.insert("pc", 6);
+
+        assert_eq!(boxes.lenses(0), [("rn".to_string(), 1)]);
+        assert_eq!(
+            boxes
+                .lenses(3)
+                .iter()
+                .map(|(l, lens)| (l.as_str(), *lens))
+                .collect::<Vec<_>>(),
+            vec![("ot", 7), ("ab", 5), ("pc", 6)]
+        );
+        assert_eq!(boxes.get("qp"), Some(3));
+        assert_eq!(boxes.get("cm"), None);
+        assert_eq!(boxes.focusing_power(), 1 + 6 + 28 + 40 + 72);
+
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = LensLibrary::instance(&input).unwrap();
+        assert_eq!(instance.steps().len(), 11);
+        assert_eq!(
+            instance.steps()[1],
+            Step::Remove {
+                label: "cm".to_string()
+            }
+        );
+        assert_eq!(instance.boxes().focusing_power(), 145);
+        assert!(LensLibrary::instance("rn=1,cm").is_err());
+        assert!(LensLibrary::instance("rn=256").is_err());
+    }
+
+    aoc_plumbing::parsing_never_panics!(LensLibrary);
 }
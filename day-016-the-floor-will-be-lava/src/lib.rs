@@ -3,7 +3,7 @@ use std::str::FromStr;
 use anyhow::bail;
 use aoc_common::{
     direction::Cardinal,
-    grid::{Coordinate, Grid},
+    grid::{Coordinate, Grid, Symmetry},
 };
 use aoc_plumbing::Problem;
 use rustc_hash::FxHashSet;
@@ -69,35 +69,210 @@ impl TheFloorWillBeLava {
         total
     }
 
+    /// Returns every possible entry beam: one for each edge tile, pointing
+    /// into the grid.
+    fn edge_starts(&self) -> Vec<(Coordinate, Cardinal)> {
+        let mut starts = Vec::with_capacity(2 * (self.grid.n + self.grid.m));
+
+        for i in 0..self.grid.n {
+            starts.push(((i, 0).into(), Cardinal::East));
+            starts.push(((i, self.grid.m - 1).into(), Cardinal::West));
+        }
+
+        for j in 0..self.grid.m {
+            starts.push(((0, j).into(), Cardinal::South));
+            starts.push(((self.grid.n - 1, j).into(), Cardinal::North));
+        }
+
+        starts
+    }
+
+    /// Returns the symmetries of the contraption. Mirrors flip when the
+    /// contraption is reflected, but not when it is turned around.
+    fn symmetries(&self) -> Vec<Symmetry> {
+        self.grid.symmetries(|sym, tile| {
+            let kind = match (sym, tile.kind) {
+                (Symmetry::HalfTurn, kind) => kind,
+                (_, TileKind::FMirror) => TileKind::BMirror,
+                (_, TileKind::BMirror) => TileKind::FMirror,
+                (_, kind) => kind,
+            };
+            Tile { kind, ..*tile }
+        })
+    }
+
+    /// Like [Self::edge_starts], but leaves out any start that is the image
+    /// of an earlier one under the symmetries of the contraption, since the
+    /// two energize the same number of tiles. Also returns how many starts
+    /// were left out.
+    fn distinct_edge_starts(&self) -> (Vec<(Coordinate, Cardinal)>, usize) {
+        let symmetries = self.symmetries();
+        let mut seen = FxHashSet::default();
+        let mut starts = Vec::default();
+        let mut skipped = 0;
+
+        for start in self.edge_starts() {
+            if !seen.insert(start) {
+                skipped += 1;
+                continue;
+            }
+            starts.push(start);
+
+            // both mirrors together make a half turn, so follow images of
+            // images until nothing new turns up
+            let mut stack = vec![start];
+            while let Some((position, dir)) = stack.pop() {
+                for sym in symmetries.iter() {
+                    let image = (
+                        sym.apply(position, self.grid.n, self.grid.m),
+                        sym.apply_cardinal(&dir),
+                    );
+                    if seen.insert(image) {
+                        stack.push(image);
+                    }
+                }
+            }
+        }
+
+        (starts, skipped)
+    }
+
     fn max_energized(&mut self) -> usize {
+        self.max_energized_with_skipped().0
+    }
+
+    /// Finds the most tiles any entry beam energizes, along with the number
+    /// of entry beams that didn't need to be tried because of symmetry.
+    pub fn max_energized_with_skipped(&mut self) -> (usize, usize) {
         let mut total = 0;
         let mut visited = FxHashSet::default();
+        let (starts, skipped) = self.distinct_edge_starts();
 
-        for i in 0..self.grid.n {
-            self.energize_helper(&(i, 0).into(), &Cardinal::East, &mut visited);
+        for (position, dir) in starts {
+            self.energize_helper(&position, &dir, &mut visited);
             total = total.max(self.total_energized());
             visited.clear();
             self.clear();
+        }
 
-            self.energize_helper(&(i, self.grid.m - 1).into(), &Cardinal::West, &mut visited);
-            total = total.max(self.total_energized());
-            visited.clear();
-            self.clear();
+        (total, skipped)
+    }
+
+    /// Returns which tiles are energized by the beam entering at `position`
+    /// heading `dir`. Entering outside the grid energizes nothing.
+    pub fn energized_from(&mut self, position: Coordinate, dir: Cardinal) -> Grid<bool> {
+        self.energize_helper(&position, &dir, &mut FxHashSet::default());
+
+        let mut energized = Grid::new(self.grid.n, self.grid.m, false);
+        for i in 0..self.grid.n {
+            for j in 0..self.grid.m {
+                let coord = (i, j).into();
+                energized[coord] = self.grid[coord].energized;
+            }
         }
+        self.clear();
 
-        for j in 0..self.grid.m {
-            self.energize_helper(&(0, j).into(), &Cardinal::South, &mut visited);
-            total = total.max(self.total_energized());
-            visited.clear();
-            self.clear();
+        energized
+    }
 
-            self.energize_helper(&(self.grid.n - 1, j).into(), &Cardinal::North, &mut visited);
-            total = total.max(self.total_energized());
-            visited.clear();
-            self.clear();
+    /// Returns the coordinates of the tiles that are not energized by any of
+    /// the possible entry beams, in row-major order.
+    pub fn never_energized(&mut self) -> Vec<Coordinate> {
+        let mut ever = Grid::new(self.grid.n, self.grid.m, false);
+
+        for (position, dir) in self.edge_starts() {
+            let energized = self.energized_from(position, dir);
+            for i in 0..self.grid.n {
+                for j in 0..self.grid.m {
+                    let coord = (i, j).into();
+                    ever[coord] |= energized[coord];
+                }
+            }
         }
 
-        total
+        let mut ret = Vec::default();
+        for i in 0..self.grid.n {
+            for j in 0..self.grid.m {
+                if !ever[(i, j).into()] {
+                    ret.push((i, j).into());
+                }
+            }
+        }
+
+        ret
+    }
+
+    /// Renders the beam entering at `position` heading `dir` spreading
+    /// through the contraption, one step per frame. Energized empty tiles are
+    /// drawn as `#` and the beam fronts as arrows. A final frame shows every
+    /// energized tile as `#`.
+    #[cfg(feature = "visualize")]
+    pub fn render_frames<R: aoc_common::visualize::Renderer>(
+        &self,
+        renderer: &mut R,
+        position: Coordinate,
+        dir: Cardinal,
+    ) -> std::io::Result<()> {
+        use aoc_common::visualize::frame;
+
+        fn outgoing(kind: TileKind, dir: Cardinal) -> Vec<Cardinal> {
+            use Cardinal::*;
+            match (kind, dir) {
+                (TileKind::VSplit, East | West) => vec![North, South],
+                (TileKind::HSplit, North | South) => vec![East, West],
+                (TileKind::FMirror, North) | (TileKind::BMirror, South) => vec![East],
+                (TileKind::FMirror, South) | (TileKind::BMirror, North) => vec![West],
+                (TileKind::FMirror, East) | (TileKind::BMirror, West) => vec![North],
+                (TileKind::FMirror, West) | (TileKind::BMirror, East) => vec![South],
+                _ => vec![dir],
+            }
+        }
+
+        let mut energized = Grid::new(self.grid.n, self.grid.m, false);
+        let mut visited = FxHashSet::default();
+        visited.insert((position, dir));
+        let mut front = vec![(position, dir)];
+
+        while !front.is_empty() {
+            let mut f = frame(&self.grid, |t| match t.kind {
+                TileKind::Empty => '.',
+                TileKind::VSplit => '|',
+                TileKind::HSplit => '-',
+                TileKind::FMirror => '/',
+                TileKind::BMirror => '\\',
+            });
+            for i in 0..self.grid.n {
+                for j in 0..self.grid.m {
+                    let coord = (i, j).into();
+                    if energized[coord] && self.grid[coord].kind == TileKind::Empty {
+                        f[coord] = '#';
+                    }
+                }
+            }
+            for (coord, dir) in front.iter() {
+                f[*coord] = match dir {
+                    Cardinal::North => '^',
+                    Cardinal::East => '>',
+                    Cardinal::South => 'v',
+                    Cardinal::West => '<',
+                };
+            }
+            renderer.render(&f)?;
+
+            let mut next = Vec::default();
+            for (coord, dir) in front {
+                energized[coord] = true;
+                for out in outgoing(self.grid[coord].kind, dir) {
+                    let n = coord.neighbour(&out);
+                    if self.grid.is_in_bounds(n) && visited.insert((n, out)) {
+                        next.push((n, out));
+                    }
+                }
+            }
+            front = next;
+        }
+
+        renderer.render(&frame(&energized, |e| if *e { '#' } else { '.' }))
     }
 
     fn clear(&mut self) {
@@ -188,35 +363,35 @@ impl FromStr for TheFloorWillBeLava {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Self {
-            grid: Grid::from_str(s)?,
+            grid: Grid::parse(s)?,
         })
     }
 }
 
 impl Problem for TheFloorWillBeLava {
+    const YEAR: usize = 2023;
     const DAY: usize = 16;
     const TITLE: &'static str = "the floor will be lava";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
         self.energize();
-        Ok(self.total_energized())
+        Ok(self.total_energized() as u64)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        Ok(self.max_energized())
+        Ok(self.max_energized() as u64)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::Solution;
 
     #[test]
     #[ignore]
@@ -232,4 +407,124 @@ mod tests {
         let solution = TheFloorWillBeLava::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(46, 51));
     }
+
+    #[test]
+    fn symmetric_starts() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let mut instance = TheFloorWillBeLava::instance(&input).unwrap();
+        assert_eq!(instance.max_energized_with_skipped(), (51, 0));
+
+        let input = "\\.|./
+.-...
+.....
+.-...
+/.|.\\";
+        let instance = TheFloorWillBeLava::instance(input).unwrap();
+        assert_eq!(instance.symmetries(), vec![Symmetry::Vertical]);
+
+        let input = "\\.|./
+..-..
+.....
+..-..
+/.|.\\";
+        let mut instance = TheFloorWillBeLava::instance(input).unwrap();
+        assert_eq!(instance.symmetries(), Symmetry::all().to_vec());
+
+        let mut brute = 0;
+        let mut visited = FxHashSet::default();
+        for (position, dir) in instance.edge_starts() {
+            instance.energize_helper(&position, &dir, &mut visited);
+            brute = brute.max(instance.total_energized());
+            visited.clear();
+            instance.clear();
+        }
+
+        // the middle row and column each pair up with their own mirror
+        // image, and the other starts come in fours
+        let (best, skipped) = instance.max_energized_with_skipped();
+        assert_eq!(best, brute);
+        assert_eq!(skipped, 14);
+    }
+
+    #[test]
+    fn never_energized() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let mut instance = TheFloorWillBeLava::instance(&input).unwrap();
+        assert_eq!(instance.never_energized(), vec![]);
+
+        // the center tile is boxed in by mirrors that turn every beam away
+        let input = ".....
+.\\-/.
+.|.|.
+./-\\.
+.....";
+        let mut instance = TheFloorWillBeLava::instance(input).unwrap();
+        assert_eq!(instance.never_energized(), vec![Coordinate::new(2, 2)]);
+    }
+
+    #[test]
+    fn energized_from() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let mut instance = TheFloorWillBeLava::instance(&input).unwrap();
+
+        let energized = instance.energized_from(Coordinate::new(0, 0), Cardinal::East);
+        let rendered = energized
+            .grid
+            .iter()
+            .map(|row| row.iter().map(|e| if *e { '#' } else { '.' }).collect())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            rendered,
+            vec![
+                "######....",
+                ".#...#....",
+                ".#...#####",
+                ".#...##...",
+                ".#...##...",
+                ".#...##...",
+                ".#..####..",
+                "########..",
+                ".#######..",
+                ".#...#.#..",
+            ]
+        );
+
+        // the best start from the puzzle description
+        let energized = instance.energized_from(Coordinate::new(0, 3), Cardinal::South);
+        assert_eq!(energized.grid.iter().flatten().filter(|e| **e).count(), 51);
+
+        // nothing is left energized for the next call
+        assert_eq!(instance.total_energized(), 0);
+        let energized = instance.energized_from(Coordinate::new(-1, 0), Cardinal::South);
+        assert!(energized.grid.iter().flatten().all(|e| !e));
+    }
+
+    #[cfg(feature = "visualize")]
+    #[test]
+    fn render_frames() {
+        use aoc_common::visualize::TextRenderer;
+
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = TheFloorWillBeLava::instance(&input).unwrap();
+        let mut renderer = TextRenderer::new(Vec::new());
+        instance
+            .render_frames(&mut renderer, Coordinate::new(0, 0), Cardinal::East)
+            .unwrap();
+
+        let out = String::from_utf8(renderer.into_inner()).unwrap();
+        let frames = out
+            .split("\n\n")
+            .filter(|f| !f.is_empty())
+            .collect::<Vec<_>>();
+        assert!(frames[0].starts_with("frame 0\n>|...\\...."));
+        assert_eq!(
+            frames[frames.len() - 1]
+                .chars()
+                .filter(|c| *c == '#')
+                .count(),
+            46
+        );
+    }
+
+    aoc_plumbing::parsing_never_panics!(TheFloorWillBeLava);
 }
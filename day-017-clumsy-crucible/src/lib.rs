@@ -1,11 +1,12 @@
 use std::{collections::BinaryHeap, hash::Hash, str::FromStr};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use aoc_common::{
     direction::Cardinal,
     grid::{Coordinate, Grid},
+    heap::MinScored,
 };
-use aoc_plumbing::Problem;
+use aoc_plumbing::{Description, Problem};
 use rustc_hash::FxHashMap;
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
@@ -26,17 +27,30 @@ impl Orientation {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Block {
     value: usize,
+    wall: bool,
+}
+
+impl Block {
+    const WALL: Self = Self {
+        value: 0,
+        wall: true,
+    };
 }
 
 impl TryFrom<char> for Block {
     type Error = anyhow::Error;
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
+        if value == '#' {
+            return Ok(Self::WALL);
+        }
+
         Ok(Self {
             value: value
                 .to_digit(10)
                 .map(|x| x as usize)
                 .ok_or_else(|| anyhow!("invalid block value"))?,
+            wall: false,
         })
     }
 }
@@ -73,140 +87,422 @@ impl Node {
     }
 }
 
-impl PartialOrd for Node {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+/// Counts of the nodes considered during a search, used to gauge how much work
+/// the dominance check saves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct SearchStats {
+    generated: usize,
+    pushed: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct SearchState {
+    acc: FxHashMap<MemoNode, usize>,
+    q: BinaryHeap<MinScored<usize, Node>>,
+    stats: SearchStats,
 }
 
-impl Ord for Node {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.dist.cmp(&self.dist)
+impl SearchState {
+    fn push(&mut self, node: Node) {
+        self.acc.insert(node.into(), node.dist);
+        self.q.push(MinScored(node.dist, node));
+    }
+
+    fn best(&self, node: &MemoNode) -> usize {
+        self.acc.get(node).copied().unwrap_or(usize::MAX)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct ClumsyCrucible {
     grid: Grid<Block>,
+    // row_sums[i][j] is the sum of the first j blocks in row i
+    row_sums: Vec<Vec<usize>>,
+    // col_sums[j][i] is the sum of the first i blocks in column j
+    col_sums: Vec<Vec<usize>>,
+    // reach[i * m + j][dir] is how far we can go from (i, j) in dir before
+    // leaving the grid or running into a wall
+    reach: Vec<[usize; 4]>,
 }
 
 impl ClumsyCrucible {
+    /// Returns the heat lost moving `steps` blocks from `coord` in the given
+    /// direction, excluding the starting block. The caller must ensure the
+    /// destination is in bounds.
+    fn run_cost(&self, coord: Coordinate, dir: &Cardinal, steps: usize) -> usize {
+        let (r, c) = (coord.row() as usize, coord.col() as usize);
+        match dir {
+            Cardinal::East => self.row_sums[r][c + steps + 1] - self.row_sums[r][c + 1],
+            Cardinal::West => self.row_sums[r][c] - self.row_sums[r][c - steps],
+            Cardinal::South => self.col_sums[c][r + steps + 1] - self.col_sums[c][r + 1],
+            Cardinal::North => self.col_sums[c][r] - self.col_sums[c][r - steps],
+        }
+    }
+
+    /// Returns how many steps can be taken from `coord` in the given direction
+    /// before leaving the grid or hitting a wall.
+    fn max_steps(&self, coord: Coordinate, dir: &Cardinal) -> usize {
+        self.reach[coord.row() as usize * self.grid.m + coord.col() as usize][*dir as usize]
+    }
+
+    fn is_wall(&self, coord: Coordinate) -> bool {
+        self.grid[coord].wall
+    }
+
+    /// Whether `coord` is a block in the grid that isn't a wall.
+    fn is_open(&self, coord: Coordinate) -> bool {
+        self.grid.is_in_bounds(coord) && !self.is_wall(coord)
+    }
+
+    fn top_left(&self) -> Coordinate {
+        (0_isize, 0_isize).into()
+    }
+
+    fn bottom_right(&self) -> Coordinate {
+        (self.grid.n - 1, self.grid.m - 1).into()
+    }
+
     fn generate_neighbours_helper(
         &self,
         node: &Node,
         min: usize,
         max: usize,
         dir: &Cardinal,
-        acc: &mut FxHashMap<MemoNode, usize>,
-        q: &mut BinaryHeap<Node>,
+        state: &mut SearchState,
     ) {
         let orientation = node.orientation.opposite();
-        let mut dist = node.dist;
 
-        for i in 1..=max {
+        for i in min..=max.min(self.max_steps(node.coord, dir)) {
             let coord = node.coord.steps(dir, i);
-
-            if !self.grid.is_in_bounds(coord) {
-                break;
-            }
-
-            dist += self.grid[coord].value;
-
-            if i < min {
-                continue;
-            }
+            let dist = node.dist + self.run_cost(node.coord, dir, i);
+            state.stats.generated += 1;
 
             let neighbour = Node::new(dist, orientation, coord);
-            let neighbour_memo = neighbour.into();
-
-            if dist < acc.get(&neighbour_memo).copied().unwrap_or(usize::MAX) {
-                acc.insert(neighbour_memo, neighbour.dist);
-                q.push(neighbour);
+            if dist < state.best(&neighbour.into()) {
+                state.push(neighbour);
+                state.stats.pushed += 1;
             }
         }
     }
 
-    fn generate_neighbours(
-        &self,
-        node: &Node,
-        min: usize,
-        max: usize,
-        acc: &mut FxHashMap<MemoNode, usize>,
-        q: &mut BinaryHeap<Node>,
-    ) {
+    fn generate_neighbours(&self, node: &Node, min: usize, max: usize, state: &mut SearchState) {
         if node.orientation == Orientation::Horizontal {
-            self.generate_neighbours_helper(node, min, max, &Cardinal::North, acc, q);
-            self.generate_neighbours_helper(node, min, max, &Cardinal::South, acc, q);
+            self.generate_neighbours_helper(node, min, max, &Cardinal::North, state);
+            self.generate_neighbours_helper(node, min, max, &Cardinal::South, state);
         } else {
-            self.generate_neighbours_helper(node, min, max, &Cardinal::East, acc, q);
-            self.generate_neighbours_helper(node, min, max, &Cardinal::West, acc, q);
+            self.generate_neighbours_helper(node, min, max, &Cardinal::East, state);
+            self.generate_neighbours_helper(node, min, max, &Cardinal::West, state);
         }
     }
 
-    fn dijkstra(&self, min: usize, max: usize) -> usize {
-        let mut acc: FxHashMap<MemoNode, usize> = FxHashMap::default();
-        let mut q: BinaryHeap<Node> = BinaryHeap::default();
+    /// Finds the minimum heat loss using a [BinaryHeap] as the priority queue.
+    ///
+    /// This is kept as a reference for [Self::dijkstra], which exploits the
+    /// small edge weights with a bucket queue and is noticeably faster.
+    pub fn dijkstra_heap(&self, min: usize, max: usize) -> Option<usize> {
+        self.dijkstra_with_stats(min, max, self.top_left(), self.bottom_right())
+            .0
+    }
 
-        let start = (0_isize, 0_isize).into();
-        let end = (self.grid.n - 1, self.grid.m - 1).into();
+    fn dijkstra_with_stats(
+        &self,
+        min: usize,
+        max: usize,
+        start: Coordinate,
+        end: Coordinate,
+    ) -> (Option<usize>, SearchStats) {
+        let mut state = SearchState::default();
+        let min = min.max(1);
+        if !self.is_open(start) || !self.is_open(end) {
+            return (None, state.stats);
+        }
 
-        let node1 = Node::new(0, Orientation::Horizontal, start);
-        let node2 = Node::new(0, Orientation::Vertical, start);
-        acc.insert(node1.into(), node1.dist);
-        acc.insert(node2.into(), node2.dist);
-        q.push(node1);
-        q.push(node2);
+        state.push(Node::new(0, Orientation::Horizontal, start));
+        state.push(Node::new(0, Orientation::Vertical, start));
 
-        while let Some(node) = q.pop() {
+        while let Some(MinScored(_, node)) = state.q.pop() {
             let coord = node.coord;
             if coord == end {
-                return node.dist;
+                return (Some(node.dist), state.stats);
             }
 
-            if acc.get(&node.into()).copied().unwrap_or(usize::MAX) < node.dist {
+            if state.best(&node.into()) < node.dist {
                 continue;
             }
 
-            self.generate_neighbours(&node, min, max, &mut acc, &mut q);
+            self.generate_neighbours(&node, min, max, &mut state);
         }
 
-        unreachable!()
+        (None, state.stats)
+    }
+
+    /// Finds the minimum heat loss from the top-left block to the
+    /// bottom-right one, with runs of between `min` and `max` blocks. The
+    /// crucible has to move, so a `min` of 0 is the same as 1.
+    ///
+    /// Every block costs 1-9, so a single move never costs more than
+    /// `9 * max`. That lets us use a circular bucket queue with one bucket
+    /// per distance in that window instead of a heap, and a flat distance
+    /// array indexed by `(row * m + col) * 2 + orientation` instead of a map.
+    ///
+    /// Returns `None` if walls cut the bottom-right block off from the
+    /// top-left one.
+    pub fn dijkstra(&self, min: usize, max: usize) -> Option<usize> {
+        self.dijkstra_between(min, max, self.top_left(), self.bottom_right())
+    }
+
+    /// Like [Self::dijkstra], but from `start` to `end`. Returns `None` if
+    /// either of them is a wall or outside the grid.
+    pub fn dijkstra_between(
+        &self,
+        min: usize,
+        max: usize,
+        start: Coordinate,
+        end: Coordinate,
+    ) -> Option<usize> {
+        self.bucket_search(min, max, start, end, None)
+            .map(|(dist, _)| dist)
+    }
+
+    /// Returns the coordinates of every block visited on a path with the
+    /// minimum heat loss, starting with the top-left block and ending with
+    /// the bottom-right one, or `None` if no such path exists.
+    pub fn path(&self, min: usize, max: usize) -> Option<Vec<Coordinate>> {
+        let mut prev = vec![u32::MAX; self.grid.n * self.grid.m * 2];
+        let (_, mut idx) = self.bucket_search(
+            min,
+            max,
+            self.top_left(),
+            self.bottom_right(),
+            Some(&mut prev),
+        )?;
+
+        let m = self.grid.m;
+        let coord = |idx: usize| -> Coordinate { (idx / 2 / m, idx / 2 % m).into() };
+
+        let mut path = vec![coord(idx)];
+        while prev[idx] != u32::MAX {
+            let from = coord(prev[idx] as usize);
+            let to = coord(idx);
+            let dir = from
+                .direction_to(&to)
+                .expect("consecutive nodes are on the same row or column");
+
+            let mut cur = to;
+            while cur != from {
+                cur = cur.neighbour(&dir.opposite());
+                path.push(cur);
+            }
+            idx = prev[idx] as usize;
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    /// Runs the bucket queue search, returning the minimum heat loss and the
+    /// index of the node that reached `end`. If `prev` is given, it's filled
+    /// with the index of the node each node was reached from.
+    fn bucket_search(
+        &self,
+        min: usize,
+        max: usize,
+        start: Coordinate,
+        end: Coordinate,
+        mut prev: Option<&mut Vec<u32>>,
+    ) -> Option<(usize, usize)> {
+        // a run of zero blocks would be free, and land back in the bucket
+        // being drained
+        let min = min.max(1);
+        if !self.is_open(start) || !self.is_open(end) {
+            return None;
+        }
+
+        let m = self.grid.m;
+        let index = |coord: Coordinate, orientation: Orientation| {
+            (coord.row() as usize * m + coord.col() as usize) * 2 + orientation as usize
+        };
+
+        let max_block = self
+            .grid
+            .grid
+            .iter()
+            .flatten()
+            .map(|b| b.value)
+            .max()
+            .unwrap_or_default();
+        let span = max_block * max + 1;
+
+        let mut dist = vec![u32::MAX; self.grid.n * m * 2];
+        let mut buckets: Vec<Vec<u32>> = vec![Vec::new(); span];
+        let mut pending = 0;
+
+        for orientation in [Orientation::Horizontal, Orientation::Vertical] {
+            let idx = index(start, orientation);
+            dist[idx] = 0;
+            buckets[0].push(idx as u32);
+            pending += 1;
+        }
+
+        let mut cur = 0_usize;
+        while pending > 0 {
+            // moves cost at most span - 1, but runs of 0 blocks are free and
+            // push back into the bucket being drained, so keep going until
+            // it stays empty
+            while !buckets[cur % span].is_empty() {
+                let mut bucket = std::mem::take(&mut buckets[cur % span]);
+                pending -= bucket.len();
+
+                for &idx in bucket.iter() {
+                    let idx = idx as usize;
+                    if dist[idx] as usize != cur {
+                        continue;
+                    }
+
+                    let cell = idx / 2;
+                    let coord: Coordinate = (cell / m, cell % m).into();
+                    if coord == end {
+                        return Some((cur, idx));
+                    }
+
+                    let (dirs, orientation) = if idx % 2 == Orientation::Horizontal as usize {
+                        ([Cardinal::North, Cardinal::South], Orientation::Vertical)
+                    } else {
+                        ([Cardinal::East, Cardinal::West], Orientation::Horizontal)
+                    };
+
+                    for dir in dirs.iter() {
+                        for i in min..=max.min(self.max_steps(coord, dir)) {
+                            let next = index(coord.steps(dir, i), orientation);
+                            let d = cur + self.run_cost(coord, dir, i);
+                            if d < dist[next] as usize {
+                                dist[next] = d as u32;
+                                if let Some(prev) = prev.as_deref_mut() {
+                                    prev[next] = idx as u32;
+                                }
+                                buckets[d % span].push(next as u32);
+                                pending += 1;
+                            }
+                        }
+                    }
+                }
+
+                bucket.clear();
+                if buckets[cur % span].is_empty() {
+                    buckets[cur % span] = bucket;
+                }
+            }
+            cur += 1;
+        }
+
+        None
     }
 }
 
 impl FromStr for ClumsyCrucible {
     type Err = anyhow::Error;
 
+    /// Blocks are 0-9 and `#` is a wall. Rows shorter than the longest one
+    /// are padded with walls.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rows = s
+            .lines()
+            .map(|line| line.chars().map(Block::try_from).collect())
+            .collect::<Result<Vec<Vec<Block>>, _>>()?;
+        if rows.is_empty() {
+            bail!("empty grid");
+        }
+        let width = rows.iter().map(|r| r.len()).max().unwrap_or_default();
+        if width == 0 {
+            bail!("empty grid");
+        }
+        for row in rows.iter_mut() {
+            row.resize(width, Block::WALL);
+        }
+        let grid: Grid<Block> = rows.into();
+
+        let mut row_sums = vec![vec![0; grid.m + 1]; grid.n];
+        let mut col_sums = vec![vec![0; grid.n + 1]; grid.m];
+        for i in 0..grid.n {
+            for j in 0..grid.m {
+                let value = grid.grid[i][j].value;
+                row_sums[i][j + 1] = row_sums[i][j] + value;
+                col_sums[j][i + 1] = col_sums[j][i] + value;
+            }
+        }
+
+        let (n, m) = (grid.n, grid.m);
+        let mut reach = vec![[0; 4]; n * m];
+        let open = |i: usize, j: usize| !grid.grid[i][j].wall;
+        for i in 0..n {
+            for j in 0..m {
+                if i > 0 && open(i - 1, j) {
+                    reach[i * m + j][Cardinal::North as usize] =
+                        reach[(i - 1) * m + j][Cardinal::North as usize] + 1;
+                }
+                if j > 0 && open(i, j - 1) {
+                    reach[i * m + j][Cardinal::West as usize] =
+                        reach[i * m + j - 1][Cardinal::West as usize] + 1;
+                }
+            }
+        }
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                if i + 1 < n && open(i + 1, j) {
+                    reach[i * m + j][Cardinal::South as usize] =
+                        reach[(i + 1) * m + j][Cardinal::South as usize] + 1;
+                }
+                if j + 1 < m && open(i, j + 1) {
+                    reach[i * m + j][Cardinal::East as usize] =
+                        reach[i * m + j + 1][Cardinal::East as usize] + 1;
+                }
+            }
+        }
+
         Ok(Self {
-            grid: Grid::from_str(s)?,
+            grid,
+            row_sums,
+            col_sums,
+            reach,
         })
     }
 }
 
 impl Problem for ClumsyCrucible {
+    const YEAR: usize = 2023;
     const DAY: usize = 17;
     const TITLE: &'static str = "clumsy crucible";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.dijkstra(1, 3))
+        self.dijkstra(1, 3)
+            .map(|x| x as u64)
+            .ok_or_else(|| anyhow!("no path to the bottom-right block"))
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        Ok(self.dijkstra(4, 10))
+        self.dijkstra(4, 10)
+            .map(|x| x as u64)
+            .ok_or_else(|| anyhow!("no path to the bottom-right block"))
+    }
+
+    fn describe(&self) -> Description {
+        let blocks = self.grid.grid.iter().flatten().filter(|b| !b.wall);
+        Description::default()
+            .grid(self.grid.n, self.grid.m)
+            .with("walls", self.grid.count(|b| b.wall))
+            .range_of("heat loss", blocks.map(|b| b.value))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::Solution;
 
     #[test]
     #[ignore]
@@ -222,4 +518,230 @@ mod tests {
         let solution = ClumsyCrucible::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(102, 94));
     }
+
+    #[test]
+    fn describe() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let description = ClumsyCrucible::describe_input(&input).unwrap();
+        assert_eq!(
+            description.to_string(),
+            "lines: 13\nblank lines: 0\nlongest line: 13\ngrid: 13x13\nwalls: 0\nheat loss range: 1..=9"
+        );
+
+        let instance = ClumsyCrucible::instance("12#\n3").unwrap();
+        assert_eq!(instance.describe().get("walls"), Some(&3.into()));
+    }
+
+    #[test]
+    fn run_cost() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ClumsyCrucible::instance(&input).unwrap();
+        let coord = Coordinate::new(1, 1);
+        assert_eq!(instance.run_cost(coord, &Cardinal::East, 3), 1 + 5 + 4);
+        assert_eq!(instance.run_cost(coord, &Cardinal::West, 1), 3);
+        assert_eq!(instance.run_cost(coord, &Cardinal::South, 2), 2 + 4);
+        assert_eq!(instance.run_cost(coord, &Cardinal::North, 1), 4);
+        assert_eq!(instance.max_steps(coord, &Cardinal::East), 11);
+        assert_eq!(instance.max_steps(coord, &Cardinal::North), 1);
+    }
+
+    #[test]
+    fn bucket_queue_matches_heap() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ClumsyCrucible::instance(&input).unwrap();
+
+        for (min, max) in [(1, 3), (4, 10), (1, 1), (2, 5)] {
+            assert_eq!(
+                instance.dijkstra(min, max),
+                instance.dijkstra_heap(min, max)
+            );
+        }
+    }
+
+    #[test]
+    fn zero_blocks() {
+        // free runs land back in the bucket being drained
+        for input in ["10\n11", "100\n001", "00\n00", "0000\n9090\n0000"] {
+            let instance = ClumsyCrucible::instance(input).unwrap();
+            for (min, max) in [(1, 3), (1, 1), (2, 5)] {
+                assert_eq!(
+                    instance.dijkstra(min, max),
+                    instance.dijkstra_heap(min, max),
+                    "{:?} with {}..={}",
+                    input,
+                    min,
+                    max
+                );
+            }
+        }
+        assert_eq!(
+            ClumsyCrucible::instance("10\n11").unwrap().dijkstra(1, 3),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn variants() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ClumsyCrucible::instance(&input).unwrap();
+        let (start, end) = (instance.top_left(), instance.bottom_right());
+
+        assert_eq!(instance.dijkstra(1, 3), Some(102));
+        assert_eq!(instance.dijkstra(4, 10), Some(94));
+        assert_eq!(instance.dijkstra(0, 3), instance.dijkstra(1, 3));
+        assert_eq!(instance.dijkstra(3, 2), None);
+        assert_eq!(instance.dijkstra_between(1, 3, start, end), Some(102));
+
+        // the other way round skips the first block but pays for the last
+        let back = instance.dijkstra_between(1, 3, end, start);
+        assert_eq!(back, instance.dijkstra_with_stats(1, 3, end, start).0);
+        assert_eq!(instance.dijkstra_between(4, 10, end, end), Some(0));
+
+        for (min, max) in [(1, 1), (2, 7), (5, 12), (1, 20)] {
+            for (start, end) in [
+                (Coordinate::new(3, 4), Coordinate::new(9, 1)),
+                (Coordinate::new(12, 0), Coordinate::new(0, 12)),
+                (Coordinate::new(6, 6), Coordinate::new(6, 7)),
+            ] {
+                assert_eq!(
+                    instance.dijkstra_between(min, max, start, end),
+                    instance.dijkstra_with_stats(min, max, start, end).0,
+                    "{}..={} from {:?} to {:?}",
+                    min,
+                    max,
+                    start,
+                    end
+                );
+            }
+        }
+
+        let outside = Coordinate::new(13, 0);
+        assert_eq!(instance.dijkstra_between(1, 3, start, outside), None);
+        assert_eq!(
+            instance.dijkstra_between(1, 3, Coordinate::new(-1, 0), end),
+            None
+        );
+    }
+
+    #[test]
+    fn path() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ClumsyCrucible::instance(&input).unwrap();
+
+        for (min, max) in [(1, 3), (4, 10)] {
+            let path = instance.path(min, max).unwrap();
+            assert_eq!(path[0], Coordinate::new(0, 0));
+            assert_eq!(
+                path[path.len() - 1],
+                Coordinate::new(instance.grid.n as isize - 1, instance.grid.m as isize - 1)
+            );
+
+            let heat_loss: usize = path[1..].iter().map(|c| instance.grid[*c].value).sum();
+            assert_eq!(Some(heat_loss), instance.dijkstra(min, max));
+
+            // split the path into runs in a single direction
+            let dirs = path
+                .windows(2)
+                .map(|w| {
+                    assert_eq!(w[0].manhattan_distance(&w[1]), 1);
+                    Cardinal::all()
+                        .into_iter()
+                        .find(|d| w[0].neighbour(d) == w[1])
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            let mut runs = Vec::new();
+            for dir in dirs {
+                match runs.last_mut() {
+                    Some((d, len)) if *d == dir => *len += 1,
+                    Some((d, _)) => {
+                        assert_ne!(*d, dir.opposite());
+                        runs.push((dir, 1));
+                    }
+                    None => runs.push((dir, 1)),
+                }
+            }
+            assert!(runs.iter().all(|(_, len)| (min..=max).contains(len)));
+        }
+    }
+
+    #[test]
+    fn walls() {
+        let input = "11#
+#1#
+#11";
+        let mut instance = ClumsyCrucible::instance(input).unwrap();
+        assert_eq!(instance.dijkstra(1, 3), Some(4));
+        assert_eq!(instance.dijkstra_heap(1, 3), Some(4));
+        assert_eq!(
+            instance.path(1, 3).unwrap(),
+            vec![
+                Coordinate::new(0, 0),
+                Coordinate::new(0, 1),
+                Coordinate::new(1, 1),
+                Coordinate::new(2, 1),
+                Coordinate::new(2, 2),
+            ]
+        );
+        // the turns are too close together for the ultra crucible
+        assert!(instance.part_two().is_err());
+        assert_eq!(instance.dijkstra_heap(4, 10), None);
+
+        let blocked = "1#\n#1";
+        assert_eq!(
+            ClumsyCrucible::instance(blocked).unwrap().dijkstra(1, 3),
+            None
+        );
+        let walled_in = "#1\n11";
+        assert_eq!(
+            ClumsyCrucible::instance(walled_in).unwrap().dijkstra(1, 3),
+            None
+        );
+
+        assert!(ClumsyCrucible::instance("12\n3x").is_err());
+        assert!(ClumsyCrucible::instance("").is_err());
+    }
+
+    #[test]
+    fn ragged_rows() {
+        let input = "1111
+1
+11111";
+        let instance = ClumsyCrucible::instance(input).unwrap();
+        assert_eq!((instance.grid.n, instance.grid.m), (3, 5));
+        assert!(instance.is_wall(Coordinate::new(0, 4)));
+        assert!(instance.is_wall(Coordinate::new(1, 1)));
+        assert_eq!(
+            instance.max_steps(Coordinate::new(0, 0), &Cardinal::East),
+            3
+        );
+        assert_eq!(
+            instance.max_steps(Coordinate::new(1, 0), &Cardinal::East),
+            0
+        );
+
+        // the only way round is down the left and then four along the bottom
+        assert_eq!(instance.dijkstra(1, 3), None);
+        assert_eq!(instance.dijkstra(1, 4), Some(6));
+        assert_eq!(instance.dijkstra_heap(1, 4), Some(6));
+    }
+
+    #[test]
+    fn dominated_nodes_not_pushed() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ClumsyCrucible::instance(&input).unwrap();
+
+        for (min, max) in [(1, 3), (4, 10)] {
+            let (_, stats) = instance.dijkstra_with_stats(
+                min,
+                max,
+                instance.top_left(),
+                instance.bottom_right(),
+            );
+            assert!(stats.pushed > 0);
+            assert!(stats.pushed < stats.generated);
+        }
+    }
+
+    aoc_plumbing::parsing_never_panics!(ClumsyCrucible);
 }
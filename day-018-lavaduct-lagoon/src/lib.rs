@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use anyhow::bail;
+use anyhow::{anyhow, bail, Result};
 use aoc_common::{direction::Cardinal, grid::Coordinate};
 use aoc_plumbing::Problem;
 
@@ -33,12 +33,19 @@ impl FromStr for Plan {
         };
 
         if let Some(x) = tokens.next() {
-            let hex_length = usize::from_str_radix(&x[2..7], 16)?;
-            let hex_dir = match x.as_bytes()[7] {
-                b'0' => Cardinal::East,
-                b'1' => Cardinal::South,
-                b'2' => Cardinal::West,
-                b'3' => Cardinal::North,
+            // the colour is `(#` followed by 5 hex digits of length and 1
+            // of direction
+            let code = x
+                .strip_prefix("(#")
+                .and_then(|x| x.strip_suffix(')'))
+                .filter(|x| x.len() == 6 && x.is_ascii())
+                .ok_or_else(|| anyhow!("invalid colour {:?}", x))?;
+            let hex_length = usize::from_str_radix(&code[..5], 16)?;
+            let hex_dir = match &code[5..] {
+                "0" => Cardinal::East,
+                "1" => Cardinal::South,
+                "2" => Cardinal::West,
+                "3" => Cardinal::North,
                 _ => bail!("invalid plan"),
             };
 
@@ -60,75 +67,126 @@ pub struct LavaductLagoon {
 }
 
 impl LavaductLagoon {
-    fn hex_area(&self) -> usize {
-        let mut prev_point = Coordinate::from((0_isize, 0_isize));
-        let mut prev_dir = self.plans[self.plans.len() - 1].hex_dir;
-        let mut area = 0;
-        let mut perimeter = 0;
-        let mut left_turns = 0;
-        let mut right_turns = 0;
-
-        for plan in &self.plans {
-            if prev_dir.right() == plan.hex_dir {
-                right_turns += 1;
-            } else if prev_dir.left() == plan.hex_dir {
-                left_turns += 1;
-            } else {
-                // first plan and last plan form a straight side, not a corner
-                // in this case, we add 1 more to the perimeter
-                perimeter += 1;
-            }
-
-            let p = prev_point.steps(&plan.hex_dir, plan.hex_length);
-            area += prev_point.x() * p.y() - prev_point.y() * p.x();
-            perimeter += plan.hex_length - 1;
-
-            prev_dir = plan.hex_dir;
-            prev_point = p;
-        }
+    fn hex_area(&self) -> Result<u64> {
+        dig_area(&self.plans, |plan| (plan.hex_dir, plan.hex_length))
+    }
 
-        // positive means counterclockwise winding, negative means clockwise winding
-        if area > 0 {
-            (area as usize * 2 + perimeter * 2 + left_turns * 3 + right_turns) / 4
-        } else {
-            (-area as usize * 2 + perimeter * 2 + right_turns * 3 + left_turns) / 4
-        }
+    /// Returns the area dug out by following the plans, or an error if the
+    /// area does not fit in a `u64` (or an intermediate overflows `i128`).
+    pub fn area_checked(&self) -> Result<u64> {
+        dig_area(&self.plans, |plan| (plan.dir, plan.length))
     }
 
-    fn area(&self) -> usize {
-        let mut prev_point = Coordinate::from((0_isize, 0_isize));
-        let mut prev_dir = self.plans[self.plans.len() - 1].dir;
-        let mut area = 0;
-        let mut perimeter = 0;
-        let mut left_turns = 0;
-        let mut right_turns = 0;
-
-        for plan in &self.plans {
-            if prev_dir.right() == plan.dir {
-                right_turns += 1;
-            } else if prev_dir.left() == plan.dir {
-                left_turns += 1;
-            } else {
-                // first plan and last plan form a straight side, not a corner
-                // in this case, we add 1 more to the perimeter
-                perimeter += 1;
-            }
-
-            let p = prev_point.steps(&plan.dir, plan.length);
-            area += prev_point.x() * p.y() - prev_point.y() * p.x();
-            perimeter += plan.length - 1;
-
-            prev_dir = plan.dir;
-            prev_point = p;
-        }
+    /// Returns the corners of the trench as `(row, col)` coordinates, starting
+    /// at the origin and following the plans in order. The polygon is closed,
+    /// so the last plan leads back to the first vertex.
+    pub fn vertices(&self) -> Result<Vec<Coordinate>> {
+        vertices(&self.plans, |plan| (plan.dir, plan.length))
+    }
+
+    /// Like [Self::vertices], but for the plans decoded from the colours.
+    pub fn hex_vertices(&self) -> Result<Vec<Coordinate>> {
+        vertices(&self.plans, |plan| (plan.hex_dir, plan.hex_length))
+    }
+}
+
+fn vertices(plans: &[Plan], step: impl Fn(&Plan) -> (Cardinal, usize)) -> Result<Vec<Coordinate>> {
+    let mut ret = Vec::with_capacity(plans.len());
+    let mut cur = Coordinate::new(0, 0);
+
+    for plan in plans {
+        ret.push(cur);
+        let (dir, length) = step(plan);
+        let length = isize::try_from(length)?;
+        let (dx, dy) = delta(dir);
+        // x is the column and y points up, so rows go the other way
+        cur = length
+            .checked_mul(-dy as isize)
+            .and_then(|dr| cur.row().checked_add(dr))
+            .zip(
+                length
+                    .checked_mul(dx as isize)
+                    .and_then(|dc| cur.col().checked_add(dc)),
+            )
+            .map(|(r, c)| Coordinate::new(r, c))
+            .ok_or_else(|| anyhow!("vertex out of range"))?;
+    }
+
+    Ok(ret)
+}
+
+fn delta(dir: Cardinal) -> (i128, i128) {
+    match dir {
+        Cardinal::North => (0, 1),
+        Cardinal::South => (0, -1),
+        Cardinal::East => (1, 0),
+        Cardinal::West => (-1, 0),
+    }
+}
 
-        // positive means counterclockwise winding, negative means clockwise winding
-        if area > 0 {
-            (area as usize * 2 + perimeter * 2 + left_turns * 3 + right_turns) / 4
+/// Shoelace plus perimeter area of the trench described by `plans`, using
+/// `step` to pick which direction and length to follow for each plan.
+///
+/// The accumulation is done in `i128` so that plans with lengths in the
+/// billions don't overflow, with the result converted back at the end.
+fn dig_area(plans: &[Plan], step: impl Fn(&Plan) -> (Cardinal, usize)) -> Result<u64> {
+    let overflow = || anyhow!("lagoon area overflowed");
+
+    let Some(last) = plans.last() else {
+        return Ok(0);
+    };
+
+    let (mut prev_x, mut prev_y) = (0_i128, 0_i128);
+    let mut prev_dir = step(last).0;
+    let mut area: i128 = 0;
+    let mut perimeter: i128 = 0;
+    let mut left_turns: i128 = 0;
+    let mut right_turns: i128 = 0;
+
+    for plan in plans {
+        let (dir, length) = step(plan);
+        if prev_dir.right() == dir {
+            right_turns += 1;
+        } else if prev_dir.left() == dir {
+            left_turns += 1;
         } else {
-            (-area as usize * 2 + perimeter * 2 + right_turns * 3 + left_turns) / 4
+            // first plan and last plan form a straight side, not a corner
+            // in this case, we add 1 more to the perimeter
+            perimeter += 1;
         }
+
+        let length = i128::try_from(length)?;
+        let (dx, dy) = delta(dir);
+        let x = prev_x.checked_add(dx * length).ok_or_else(overflow)?;
+        let y = prev_y.checked_add(dy * length).ok_or_else(overflow)?;
+
+        let cross = prev_x
+            .checked_mul(y)
+            .zip(prev_y.checked_mul(x))
+            .and_then(|(a, b)| a.checked_sub(b))
+            .ok_or_else(overflow)?;
+        area = area.checked_add(cross).ok_or_else(overflow)?;
+        perimeter = perimeter.checked_add(length - 1).ok_or_else(overflow)?;
+
+        prev_dir = dir;
+        (prev_x, prev_y) = (x, y);
     }
+
+    // positive means counterclockwise winding, negative means clockwise winding
+    let (outer, inner) = if area > 0 {
+        (left_turns, right_turns)
+    } else {
+        (right_turns, left_turns)
+    };
+
+    let total = area
+        .checked_abs()
+        .and_then(|a| a.checked_mul(2))
+        .and_then(|a| a.checked_add(perimeter.checked_mul(2)?))
+        .and_then(|a| a.checked_add(outer * 3 + inner))
+        .ok_or_else(overflow)?;
+
+    u64::try_from(total / 4).map_err(|_| overflow())
 }
 
 impl FromStr for LavaductLagoon {
@@ -144,28 +202,28 @@ impl FromStr for LavaductLagoon {
 }
 
 impl Problem for LavaductLagoon {
+    const YEAR: usize = 2023;
     const DAY: usize = 18;
     const TITLE: &'static str = "lavaduct lagoon";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.area())
+        self.area_checked()
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        Ok(self.hex_area())
+        self.hex_area()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::Solution;
 
     #[test]
     #[ignore]
@@ -181,4 +239,57 @@ mod tests {
         let solution = LavaductLagoon::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(62, 952408144115));
     }
+
+    #[test]
+    fn area_near_i64_boundary() {
+        let length = i64::MAX as usize - 807;
+        let input =
+            format!("R {length} (#000000)\nD 1 (#000000)\nL {length} (#000000)\nU 1 (#000000)");
+        let lagoon = LavaductLagoon::instance(&input).unwrap();
+        assert_eq!(lagoon.area_checked().unwrap(), (length as u64 + 1) * 2);
+
+        let input =
+            format!("R {length} (#000000)\nD 5 (#000000)\nL {length} (#000000)\nU 5 (#000000)");
+        let lagoon = LavaductLagoon::instance(&input).unwrap();
+        assert!(lagoon.area_checked().is_err());
+    }
+
+    /// Shoelace plus Pick's theorem over the vertices, to cross-check the
+    /// turn counting in `dig_area`.
+    fn area_from_vertices(vertices: &[Coordinate]) -> usize {
+        let n = vertices.len();
+        let (mut twice_area, mut perimeter) = (0_i128, 0_i128);
+        for i in 0..n {
+            let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+            twice_area += a.col() as i128 * b.row() as i128 - b.col() as i128 * a.row() as i128;
+            perimeter += a.manhattan_distance(&b) as i128;
+        }
+
+        (twice_area.abs() / 2 + perimeter / 2 + 1) as usize
+    }
+
+    #[test]
+    fn vertices() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let lagoon = LavaductLagoon::instance(&input).unwrap();
+
+        let vertices = lagoon.vertices().unwrap();
+        assert_eq!(vertices.len(), 14);
+        assert_eq!(
+            vertices[..4],
+            [
+                Coordinate::new(0, 0),
+                Coordinate::new(0, 6),
+                Coordinate::new(5, 6),
+                Coordinate::new(5, 4)
+            ]
+        );
+        assert_eq!(area_from_vertices(&vertices), 62);
+
+        let vertices = lagoon.hex_vertices().unwrap();
+        assert_eq!(vertices[1], Coordinate::new(0, 461937));
+        assert_eq!(area_from_vertices(&vertices), 952408144115);
+    }
+
+    aoc_plumbing::parsing_never_panics!(LavaductLagoon);
 }
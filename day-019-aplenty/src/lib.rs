@@ -1,8 +1,9 @@
-use std::{collections::VecDeque, str::FromStr};
+use std::str::FromStr;
 
 use anyhow::{anyhow, bail};
-use aoc_common::interval::Interval;
-use aoc_plumbing::Problem;
+use aoc_common::geometry::HyperRect;
+use aoc_plumbing::{scan::Scanner, sections::sections, Description, Problem};
+use rayon::prelude::*;
 use rustc_hash::FxHashMap;
 
 #[derive(Debug, Clone)]
@@ -13,6 +14,17 @@ enum Attribute {
     S,
 }
 
+impl Attribute {
+    fn index(&self) -> usize {
+        match self {
+            Self::X => 0,
+            Self::M => 1,
+            Self::A => 2,
+            Self::S => 3,
+        }
+    }
+}
+
 impl FromStr for Attribute {
     type Err = anyhow::Error;
 
@@ -27,16 +39,16 @@ impl FromStr for Attribute {
     }
 }
 
-#[derive(Debug, Clone)]
-struct Part {
-    x: isize,
-    m: isize,
-    a: isize,
-    s: isize,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    pub x: u16,
+    pub m: u16,
+    pub a: u16,
+    pub s: u16,
 }
 
 impl Part {
-    fn get(&self, attribute: &Attribute) -> isize {
+    fn get(&self, attribute: &Attribute) -> u16 {
         match attribute {
             Attribute::X => self.x,
             Attribute::M => self.m,
@@ -45,102 +57,59 @@ impl Part {
         }
     }
 
-    fn rating(&self) -> isize {
-        self.x + self.m + self.a + self.s
+    fn rating(&self) -> u64 {
+        [self.x, self.m, self.a, self.s].map(u64::from).iter().sum()
     }
 }
 
-impl FromStr for Part {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut tokens = s[1..s.len() - 1].split(',');
-
-        let x = tokens
-            .next()
-            .and_then(|t| t[2..].parse().ok())
-            .ok_or_else(|| anyhow!("invalid part"))?;
-        let m = tokens
-            .next()
-            .and_then(|t| t[2..].parse().ok())
-            .ok_or_else(|| anyhow!("invalid part"))?;
-        let a = tokens
-            .next()
-            .and_then(|t| t[2..].parse().ok())
-            .ok_or_else(|| anyhow!("invalid part"))?;
-        let s = tokens
-            .next()
-            .and_then(|t| t[2..].parse().ok())
-            .ok_or_else(|| anyhow!("invalid part"))?;
+impl Part {
+    /// Scans a part such as `{x=787,m=2655,a=1222,s=2876}`.
+    fn scan(scanner: &mut Scanner) -> anyhow::Result<Self> {
+        scanner.expect_str("{x=")?;
+        let x = scanner.uint()?;
+        scanner.expect_str(",m=")?;
+        let m = scanner.uint()?;
+        scanner.expect_str(",a=")?;
+        let a = scanner.uint()?;
+        scanner.expect_str(",s=")?;
+        let s = scanner.uint()?;
+        scanner.expect(b'}')?;
 
         Ok(Self { x, m, a, s })
     }
 }
 
-#[derive(Debug, Clone, Default)]
-struct Ratings {
-    x: Interval,
-    m: Interval,
-    a: Interval,
-    s: Interval,
-}
+/// The ranges of each attribute, indexed by `Attribute::index`.
+type Ratings = HyperRect<u16, 4>;
 
-impl Ratings {
-    fn new(min: isize, max: isize) -> Self {
-        Self {
-            x: Interval::new(min, max + 1),
-            m: Interval::new(min, max + 1),
-            a: Interval::new(min, max + 1),
-            s: Interval::new(min, max + 1),
-        }
-    }
+/// Maps workflow names to their index in `Aplenty::workflows`.
+type Names = FxHashMap<String, usize>;
 
-    fn get(&self, attribute: &Attribute) -> &Interval {
-        match attribute {
-            Attribute::X => &self.x,
-            Attribute::M => &self.m,
-            Attribute::A => &self.a,
-            Attribute::S => &self.s,
-        }
-    }
-
-    fn set(&mut self, attribute: &Attribute, interval: Interval) {
-        match attribute {
-            Attribute::X => self.x = interval,
-            Attribute::M => self.m = interval,
-            Attribute::A => self.a = interval,
-            Attribute::S => self.s = interval,
-        }
-    }
-
-    fn combinations(&self) -> usize {
-        self.x.len() * self.m.len() * self.a.len() * self.s.len()
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Action {
     Reject,
     Accept,
-    Workflow(String),
+    Workflow(usize),
 }
 
-impl FromStr for Action {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl Action {
+    fn parse(s: &str, names: &Names) -> anyhow::Result<Self> {
         Ok(match s {
             "R" => Self::Reject,
             "A" => Self::Accept,
-            _ => Self::Workflow(s.to_owned()),
+            _ => Self::Workflow(
+                *names
+                    .get(s)
+                    .ok_or_else(|| anyhow!("unknown workflow: {}", s))?,
+            ),
         })
     }
 }
 
 #[derive(Debug, Clone)]
 enum Condition {
-    LessThan(Attribute, isize),
-    GreaterThan(Attribute, isize),
+    LessThan(Attribute, u16),
+    GreaterThan(Attribute, u16),
 }
 
 impl Condition {
@@ -153,62 +122,15 @@ impl Condition {
 
     fn apply_ratings(&self, ratings: Ratings) -> (Option<Ratings>, Option<Ratings>) {
         match self {
-            Self::LessThan(a, x) => {
-                if ratings.get(a).less_than(*x) {
-                    (Some(ratings), None)
-                } else if ratings.get(a).greater_than(*x) {
-                    (None, Some(ratings))
-                } else {
-                    // safe to unwrap because we know interval contains x
-                    let (left, right) = ratings.get(a).split(*x).unwrap();
-                    if left.is_empty() {
-                        (None, Some(ratings))
-                    } else {
-                        let mut matched = ratings.clone();
-                        let mut unmatched = ratings;
-                        matched.set(a, left);
-                        unmatched.set(a, right);
-                        (Some(matched), Some(unmatched))
-                    }
-                }
-            }
+            Self::LessThan(a, x) => ratings.split(a.index(), *x),
             Self::GreaterThan(a, x) => {
-                if ratings.get(a).less_than(*x) {
-                    (None, Some(ratings))
-                } else if ratings.get(a).greater_than(*x) {
-                    (Some(ratings), None)
-                } else {
-                    // safe to unwrap because we know interval contains x
-                    let (left, right) = ratings.get(a).split(*x + 1).unwrap();
-                    if left.is_empty() {
-                        (Some(ratings), None)
-                    } else {
-                        let mut matched = ratings.clone();
-                        let mut unmatched = ratings;
-                        matched.set(a, right);
-                        unmatched.set(a, left);
-                        (Some(matched), Some(unmatched))
-                    }
-                }
+                let (unmatched, matched) = ratings.split(a.index(), x.saturating_add(1));
+                (matched, unmatched)
             }
         }
     }
 }
 
-impl FromStr for Condition {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((a, n)) = s.split_once('>') {
-            Ok(Self::GreaterThan(Attribute::from_str(a)?, n.parse()?))
-        } else if let Some((a, n)) = s.split_once('<') {
-            Ok(Self::LessThan(Attribute::from_str(a)?, n.parse()?))
-        } else {
-            bail!("invalid condition")
-        }
-    }
-}
-
 #[derive(Debug, Clone)]
 enum Rule {
     Conditional(Condition, Action),
@@ -220,12 +142,12 @@ impl Rule {
         match self {
             Self::Conditional(c, a) => {
                 if c.apply(part) {
-                    Some(a.clone())
+                    Some(*a)
                 } else {
                     None
                 }
             }
-            Self::Unconditional(a) => Some(a.clone()),
+            Self::Unconditional(a) => Some(*a),
         }
     }
 
@@ -233,32 +155,54 @@ impl Rule {
         match self {
             Self::Conditional(c, a) => {
                 let (matched, unmatched) = c.apply_ratings(ratings);
-                (matched, unmatched, a.clone())
+                (matched, unmatched, *a)
             }
-            Self::Unconditional(a) => (Some(ratings), None, a.clone()),
+            Self::Unconditional(a) => (Some(ratings), None, *a),
         }
     }
 }
 
-impl FromStr for Rule {
-    type Err = anyhow::Error;
+impl Rule {
+    fn parse(s: &str, names: &Names) -> anyhow::Result<Self> {
+        let mut scanner = Scanner::new(s);
+        let rule = Self::scan(&mut scanner, names)?;
+        if !scanner.is_empty() {
+            bail!("invalid rule: {}", s);
+        }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((a, b)) = s.split_once(':') {
-            Ok(Self::Conditional(
-                Condition::from_str(a)?,
-                Action::from_str(b)?,
-            ))
-        } else {
-            Ok(Self::Unconditional(Action::from_str(s)?))
+        Ok(rule)
+    }
+
+    /// Scans a rule such as `a<2006:qkq` or `rfg`.
+    fn scan(s: &mut Scanner, names: &Names) -> anyhow::Result<Self> {
+        let name = s.take_while(|b| b.is_ascii_alphabetic());
+        if !matches!(s.peek(), Some(b'<' | b'>')) {
+            return Ok(Self::Unconditional(Action::parse(name, names)?));
+        }
+
+        let attribute = Attribute::from_str(name)?;
+        let less_than = s.eat(b'<');
+        if !less_than {
+            s.expect(b'>')?;
         }
+        let x = s.uint()?;
+        s.expect(b':')?;
+        let action = Action::parse(s.take_while(|b| b.is_ascii_alphabetic()), names)?;
+
+        let condition = if less_than {
+            Condition::LessThan(attribute, x)
+        } else {
+            Condition::GreaterThan(attribute, x)
+        };
+        Ok(Self::Conditional(condition, action))
     }
 }
 
 #[derive(Debug, Clone)]
 struct Workflow {
-    name: String,
     rules: Vec<Rule>,
+    /// The generation in which this workflow was last modified.
+    version: u64,
 }
 
 impl Workflow {
@@ -292,103 +236,456 @@ impl Workflow {
     }
 }
 
-impl FromStr for Workflow {
-    type Err = anyhow::Error;
+impl Workflow {
+    /// Splits a workflow line into its name and the unparsed rules.
+    fn split(s: &str) -> anyhow::Result<(&str, &str)> {
+        s.strip_suffix('}')
+            .and_then(|x| x.split_once('{'))
+            .ok_or_else(|| anyhow!("invalid workflow"))
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((a, b)) = s[0..s.len() - 1].split_once('{') {
-            let name = a.to_owned();
-            let rules = b
-                .split(',')
-                .map(Rule::from_str)
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(Self { name, rules })
-        } else {
-            bail!("invalid workflow")
+    fn parse(s: &str, names: &Names) -> anyhow::Result<Self> {
+        let mut scanner = Scanner::new(s);
+        scanner.take_while(|b| b.is_ascii_alphabetic());
+        scanner.expect(b'{')?;
+
+        let mut rules = Vec::default();
+        loop {
+            rules.push(Rule::scan(&mut scanner, names)?);
+            if scanner.eat(b'}') {
+                break;
+            }
+            scanner.expect(b',')?;
+        }
+
+        if !scanner.is_empty() {
+            bail!("invalid workflow: {}", s);
+        }
+
+        Ok(Self { rules, version: 0 })
+    }
+
+    /// Drops the rules no part can reach, turns a rule that catches every
+    /// part left into an unconditional one, and drops conditional rules at
+    /// the end that do the same as the final rule. Returns how many rules
+    /// were removed.
+    fn simplify(&mut self) -> usize {
+        let len = self.rules.len();
+        let mut kept = Vec::with_capacity(len);
+        let mut cur = Some(Ratings::cube(1, 4001));
+
+        for rule in self.rules.drain(..) {
+            let Some(ratings) = cur else {
+                break;
+            };
+
+            let (matched, unmatched, action) = rule.apply_ratings(ratings);
+            cur = unmatched;
+            match (matched, &cur) {
+                (None, _) => (),
+                (Some(_), None) => kept.push(Rule::Unconditional(action)),
+                (Some(_), Some(_)) => kept.push(rule),
+            }
+        }
+
+        while let [.., Rule::Conditional(_, a), Rule::Unconditional(b)] = kept[..] {
+            if a != b {
+                break;
+            }
+            kept.remove(kept.len() - 2);
+        }
+
+        self.rules = kept;
+        len - self.rules.len()
+    }
+
+    /// Where this workflow sends every part, if it doesn't look at them.
+    fn jump(&self) -> Option<Action> {
+        match self.rules[..] {
+            [Rule::Unconditional(action)] => Some(action),
+            _ => None,
         }
     }
+
+    /// The workflows this workflow may send a part to.
+    fn children(&self) -> impl Iterator<Item = usize> + '_ {
+        self.rules.iter().filter_map(|r| match r {
+            Rule::Conditional(_, Action::Workflow(idx))
+            | Rule::Unconditional(Action::Workflow(idx)) => Some(*idx),
+            _ => None,
+        })
+    }
+}
+
+/// The regions of the full ratings cube that are accepted when starting at a
+/// given workflow, along with the version they were computed for.
+#[derive(Debug, Clone)]
+struct Regions {
+    version: u64,
+    accepted: Vec<Ratings>,
+}
+
+/// What [Aplenty::optimize] changed and found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OptimizationReport {
+    /// The number of rules removed, either because no part could reach them
+    /// or because they did the same as the rule after them.
+    pub removed_rules: usize,
+    /// The sorted names of the workflows that send every part to the same
+    /// place, which the rules leading to them now skip.
+    pub bypassed: Vec<String>,
+    /// The sorted names of the workflows that can't be reached from `in`
+    /// once optimized.
+    pub unreachable: Vec<String>,
 }
 
+// sorting parts is cheap, so only bother with threads for big lists
+const PARALLEL_THRESHOLD: usize = 10_000;
+
 #[derive(Debug, Clone)]
 pub struct Aplenty {
-    workflows: FxHashMap<String, Workflow>,
+    workflows: Vec<Workflow>,
+    names: Names,
+    start: usize,
     parts: Vec<Part>,
+    generation: u64,
+    regions: Vec<Option<Regions>>,
 }
 
 impl Aplenty {
-    fn sort(&self) -> isize {
-        let mut ret = 0;
-
-        for part in &self.parts {
-            let mut cur = &self.workflows["in"];
-
-            loop {
-                match cur.apply(part) {
-                    Action::Workflow(label) => cur = &self.workflows[&label],
-                    Action::Reject => break,
-                    Action::Accept => {
-                        ret += part.rating();
-                        break;
+    fn accepted_rating(&self, part: &Part) -> u64 {
+        let mut cur = &self.workflows[self.start];
+
+        loop {
+            match cur.apply(part) {
+                Action::Workflow(idx) => cur = &self.workflows[idx],
+                Action::Reject => return 0,
+                Action::Accept => return part.rating(),
+            }
+        }
+    }
+
+    fn sort(&self) -> u64 {
+        self.sort_parts(&self.parts)
+    }
+
+    /// Runs `parts` through the workflows, returning the sum of the ratings
+    /// of the accepted parts. This reuses the parsed workflows, so several
+    /// lists of parts (see `parse_parts`) can be checked against them.
+    pub fn sort_parts(&self, parts: &[Part]) -> u64 {
+        if parts.len() > PARALLEL_THRESHOLD {
+            self.sort_parallel(parts)
+        } else {
+            self.sort_sequential(parts)
+        }
+    }
+
+    fn sort_sequential(&self, parts: &[Part]) -> u64 {
+        parts.iter().map(|p| self.accepted_rating(p)).sum()
+    }
+
+    fn sort_parallel(&self, parts: &[Part]) -> u64 {
+        parts.par_iter().map(|p| self.accepted_rating(p)).sum()
+    }
+
+    /// Replaces rule `idx` of the named workflow with the given rule, e.g.
+    /// `a<2006:qkq`.
+    ///
+    /// Only the regions of this workflow and the workflows that lead to it are
+    /// recomputed on the next call to `combinations`. Changes that would leave
+    /// the workflow without a final unconditional rule, or that would
+    /// introduce a cycle, are rejected.
+    pub fn set_rule(&mut self, workflow: &str, idx: usize, rule: &str) -> anyhow::Result<()> {
+        let w = *self
+            .names
+            .get(workflow)
+            .ok_or_else(|| anyhow!("unknown workflow: {}", workflow))?;
+        let rule = Rule::parse(rule, &self.names)?;
+        let len = self.workflows[w].rules.len();
+
+        if idx >= len {
+            bail!("workflow {} only has {} rules", workflow, len);
+        }
+
+        if idx == len - 1 && matches!(rule, Rule::Conditional(..)) {
+            bail!("the last rule of a workflow must be unconditional");
+        }
+
+        if let Rule::Conditional(_, Action::Workflow(target))
+        | Rule::Unconditional(Action::Workflow(target)) = rule
+        {
+            if self.reaches(target, w) {
+                bail!("rule would introduce a cycle");
+            }
+        }
+
+        self.generation += 1;
+        self.workflows[w].rules[idx] = rule;
+        self.workflows[w].version = self.generation;
+
+        Ok(())
+    }
+
+    /// Simplifies the workflows without changing what happens to any part:
+    /// rules that can never apply are removed, and rules that lead to a
+    /// workflow that sends everything to the same place go straight there.
+    ///
+    /// Workflows are never removed, even if nothing leads to them any more,
+    /// so they can still be edited by name with [Aplenty::set_rule].
+    pub fn optimize(&mut self) -> OptimizationReport {
+        let mut report = OptimizationReport::default();
+        let mut changed = vec![false; self.workflows.len()];
+        let mut bypassed = vec![false; self.workflows.len()];
+
+        // bypassing a workflow can leave rules that do the same thing next
+        // to each other, which can turn another workflow into a jump
+        loop {
+            let mut progress = false;
+
+            for (idx, workflow) in self.workflows.iter_mut().enumerate() {
+                let removed = workflow.simplify();
+                if removed > 0 {
+                    report.removed_rules += removed;
+                    changed[idx] = true;
+                    progress = true;
+                }
+            }
+
+            let jumps: Vec<Option<Action>> = self.workflows.iter().map(|w| w.jump()).collect();
+            for (idx, workflow) in self.workflows.iter_mut().enumerate() {
+                for rule in workflow.rules.iter_mut() {
+                    let (Rule::Conditional(_, action) | Rule::Unconditional(action)) = rule;
+                    let resolved = resolve_jumps(&jumps, *action, &mut bypassed);
+                    if resolved != *action {
+                        *action = resolved;
+                        changed[idx] = true;
+                        progress = true;
                     }
                 }
             }
+
+            if !progress {
+                break;
+            }
         }
 
-        ret
+        if changed.iter().any(|x| *x) {
+            self.generation += 1;
+            for (workflow, _) in self.workflows.iter_mut().zip(changed).filter(|(_, c)| *c) {
+                workflow.version = self.generation;
+            }
+        }
+
+        let mut reachable = vec![false; self.workflows.len()];
+        let mut stack = vec![self.start];
+        while let Some(cur) = stack.pop() {
+            if !reachable[cur] {
+                reachable[cur] = true;
+                stack.extend(self.workflows[cur].children());
+            }
+        }
+
+        for (name, &idx) in self.names.iter() {
+            if bypassed[idx] {
+                report.bypassed.push(name.clone());
+            }
+            if !reachable[idx] {
+                report.unreachable.push(name.clone());
+            }
+        }
+        report.bypassed.sort();
+        report.unreachable.sort();
+
+        report
     }
 
-    fn combinations(&self) -> usize {
-        let mut ret = 0;
-        let mut q = VecDeque::default();
-        q.push_back((Ratings::new(1, 4000), Action::Workflow("in".to_owned())));
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut seen = vec![false; self.workflows.len()];
+        let mut stack = vec![from];
+
+        while let Some(cur) = stack.pop() {
+            if cur == to {
+                return true;
+            }
+
+            if !seen[cur] {
+                seen[cur] = true;
+                stack.extend(self.workflows[cur].children());
+            }
+        }
+
+        false
+    }
+
+    /// The most recent modification of this workflow or anything reachable
+    /// from it. Cached regions are valid as long as this hasn't changed.
+    fn effective_version(&self, idx: usize, memo: &mut [Option<u64>]) -> u64 {
+        if let Some(v) = memo[idx] {
+            return v;
+        }
+
+        let workflow = &self.workflows[idx];
+        let v = workflow
+            .children()
+            .map(|c| self.effective_version(c, memo))
+            .fold(workflow.version, u64::max);
+        memo[idx] = Some(v);
+
+        v
+    }
 
-        while let Some((ratings, action)) = q.pop_front() {
+    fn update_regions(&mut self, idx: usize, versions: &mut [Option<u64>]) {
+        let version = self.effective_version(idx, versions);
+        if self.regions[idx]
+            .as_ref()
+            .is_some_and(|r| r.version == version)
+        {
+            return;
+        }
+
+        let splits = self.workflows[idx].apply_ratings(Ratings::cube(1, 4001));
+        for (_, action) in &splits {
+            if let Action::Workflow(child) = action {
+                self.update_regions(*child, versions);
+            }
+        }
+
+        let mut accepted = Vec::default();
+        for (ratings, action) in splits {
             match action {
                 Action::Reject => (),
-                Action::Accept => ret += ratings.combinations(),
-                Action::Workflow(label) => q.extend(self.workflows[&label].apply_ratings(ratings)),
+                Action::Accept => accepted.push(ratings),
+                Action::Workflow(child) => {
+                    let child = self.regions[child]
+                        .as_ref()
+                        .expect("child regions were just computed");
+                    accepted.extend(
+                        child
+                            .accepted
+                            .iter()
+                            .filter_map(|r| r.intersection(&ratings)),
+                    );
+                }
             }
         }
 
-        ret
+        self.regions[idx] = Some(Regions { version, accepted });
+    }
+
+    fn combinations(&mut self) -> u64 {
+        let mut versions = vec![None; self.workflows.len()];
+        self.update_regions(self.start, &mut versions);
+
+        self.regions[self.start]
+            .as_ref()
+            .map(|r| r.accepted.iter().map(|x| x.volume()).sum())
+            .unwrap_or_default()
+    }
+}
+
+/// Follows `action` through workflows that send every part to the same
+/// place, given by `jumps`, marking them in `bypassed`. A cycle of such
+/// workflows would never finish sorting a part, so it's left alone.
+fn resolve_jumps(jumps: &[Option<Action>], action: Action, bypassed: &mut [bool]) -> Action {
+    let mut cur = action;
+    let mut path = Vec::default();
+
+    while let Action::Workflow(idx) = cur {
+        let Some(next) = jumps[idx] else {
+            break;
+        };
+
+        if path.len() > jumps.len() {
+            return action;
+        }
+        path.push(idx);
+        cur = next;
     }
+
+    for idx in path {
+        bypassed[idx] = true;
+    }
+
+    cur
+}
+
+/// Parses a list of parts, one per line, such as `{x=787,m=2655,a=1222,s=2876}`.
+pub fn parse_parts(s: &str) -> anyhow::Result<Vec<Part>> {
+    let mut parts = Vec::default();
+    let mut scanner = Scanner::new(s);
+    scanner.skip_whitespace();
+    while !scanner.is_empty() {
+        parts.push(Part::scan(&mut scanner)?);
+        scanner.eat(b'\r');
+        if !scanner.is_empty() {
+            scanner.expect(b'\n')?;
+        }
+        scanner.skip_whitespace();
+    }
+
+    Ok(parts)
 }
 
 impl FromStr for Aplenty {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((a, b)) = s.split_once("\n\n") {
-            let list = a
-                .lines()
-                .map(Workflow::from_str)
-                .collect::<Result<Vec<_>, _>>()?;
-
-            let mut workflows = FxHashMap::default();
-            for w in list {
-                workflows.insert(w.name.clone(), w);
-            }
+        let (a, b) = match sections(s)[..] {
+            [a, b] => (a, b),
+            // no parts is fine for part two
+            [a] => (a, ""),
+            _ => bail!("expected workflows and parts separated by a blank line"),
+        };
 
-            let parts = b
-                .lines()
-                .map(Part::from_str)
-                .collect::<Result<Vec<_>, _>>()?;
-
-            Ok(Self { workflows, parts })
-        } else {
-            bail!("invalid input")
+        // resolve every workflow name to an index up front, so that rules
+        // can refer to workflows by index rather than by name
+        let mut names = Names::default();
+        for (i, line) in a.lines().enumerate() {
+            let (name, _) = Workflow::split(line)?;
+            names.insert(name.to_string(), i);
         }
+
+        let start = *names
+            .get("in")
+            .ok_or_else(|| anyhow!("missing workflow: in"))?;
+
+        // the two sections don't depend on each other
+        let (workflows, parts) = rayon::join(
+            || {
+                a.lines()
+                    .collect::<Vec<_>>()
+                    .into_par_iter()
+                    .map(|x| Workflow::parse(x, &names))
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()
+            },
+            || parse_parts(b),
+        );
+        let (workflows, parts) = (workflows?, parts?);
+
+        let regions = vec![None; workflows.len()];
+
+        Ok(Self {
+            workflows,
+            names,
+            start,
+            parts,
+            generation: 0,
+            regions,
+        })
     }
 }
 
 impl Problem for Aplenty {
+    const YEAR: usize = 2023;
     const DAY: usize = 19;
     const TITLE: &'static str = "aplenty";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = isize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
         Ok(self.sort())
@@ -397,20 +694,65 @@ impl Problem for Aplenty {
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
         Ok(self.combinations())
     }
+
+    fn describe(&self) -> Description {
+        Description::default()
+            .with("workflows", self.workflows.len())
+            .with(
+                "rules",
+                self.workflows.iter().map(|w| w.rules.len()).sum::<usize>(),
+            )
+            .with("parts", self.parts.len())
+            .range_of("x", self.parts.iter().map(|p| p.x))
+            .range_of("m", self.parts.iter().map(|p| p.m))
+            .range_of("a", self.parts.iter().map(|p| p.a))
+            .range_of("s", self.parts.iter().map(|p| p.s))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::VecDeque;
+
+    use aoc_common::interval::Interval;
     use aoc_plumbing::Solution;
+    use aoc_testing::{day19::Workflows, Generator};
+    use proptest::prelude::*;
 
     use super::*;
 
+    impl Aplenty {
+        /// Walks the whole workflow graph from scratch.
+        fn combinations_naive(&self) -> u64 {
+            let mut ret = 0;
+            let mut q = VecDeque::default();
+            q.push_back((Ratings::cube(1, 4001), Action::Workflow(self.start)));
+
+            while let Some((ratings, action)) = q.pop_front() {
+                match action {
+                    Action::Reject => (),
+                    Action::Accept => ret += ratings.volume(),
+                    Action::Workflow(idx) => q.extend(self.workflows[idx].apply_ratings(ratings)),
+                }
+            }
+
+            ret
+        }
+    }
+
     #[test]
     #[ignore]
     fn full_dataset() {
         let input = std::fs::read_to_string("input.txt").expect("Unable to load input");
         let solution = Aplenty::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(446935, 141882534122898));
+
+        let mut instance = Aplenty::instance(&input).unwrap();
+        instance.optimize();
+        assert_eq!(
+            Solution::new(instance.part_one().unwrap(), instance.part_two().unwrap()),
+            solution
+        );
     }
 
     #[test]
@@ -419,4 +761,288 @@ mod tests {
         let solution = Aplenty::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(19114, 167409079868000));
     }
+
+    #[test]
+    fn parallel_matches_sequential() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = Aplenty::instance(&input).unwrap();
+        assert_eq!(instance.sort_parallel(&instance.parts), 19114);
+        assert_eq!(instance.sort_sequential(&instance.parts), 19114);
+    }
+
+    #[test]
+    fn workflow_names_resolved() {
+        let input = "in{x<10:two,one}
+one{A}
+two{m>5:R,in}
+
+{x=1,m=2,a=3,s=4}";
+        let instance = Aplenty::instance(input).unwrap();
+        assert_eq!(instance.start, 0);
+        assert_eq!(instance.workflows[1].rules.len(), 1);
+        assert!(matches!(
+            instance.workflows[0].rules[0],
+            Rule::Conditional(_, Action::Workflow(2))
+        ));
+        assert!(matches!(
+            instance.workflows[2].rules[1],
+            Rule::Unconditional(Action::Workflow(0))
+        ));
+    }
+
+    #[test]
+    fn set_rule_recomputes_affected() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let mut instance = Aplenty::instance(&input).unwrap();
+        assert_eq!(instance.combinations(), 167409079868000);
+
+        let qkq = instance.names["qkq"];
+        let crn = instance.names["crn"];
+        let pv = instance.names["pv"];
+        let start = instance.start;
+
+        instance.set_rule("crn", 0, "x>1000:A").unwrap();
+        let expected = instance.combinations_naive();
+        assert_eq!(instance.combinations(), expected);
+        assert_ne!(expected, 167409079868000);
+
+        // crn and everything leading to it were recomputed, unrelated
+        // workflows were not
+        assert_eq!(instance.regions[crn].as_ref().unwrap().version, 1);
+        assert_eq!(instance.regions[qkq].as_ref().unwrap().version, 1);
+        assert_eq!(instance.regions[start].as_ref().unwrap().version, 1);
+        assert_eq!(instance.regions[pv].as_ref().unwrap().version, 0);
+
+        instance.set_rule("in", 1, "A").unwrap();
+        assert_eq!(instance.combinations(), instance.combinations_naive());
+    }
+
+    #[test]
+    fn optimize() {
+        let input = "in{s<1351:px,qqz}
+px{a<2006:jmp,m>2090:A,m>2100:R,rfg}
+jmp{chain}
+chain{x>5000:R,R}
+qqz{s>2770:A,s>2800:R,m<1801:R,s<2000:px,R}
+rfg{s<537:gd,x>2440:R,A}
+gd{a>3333:R,R}
+lost{A}
+orphan{x<10:lost,R}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
+        let mut instance = Aplenty::instance(input).unwrap();
+        let expected = Solution::new(instance.sort(), instance.combinations_naive());
+        assert_eq!(instance.combinations(), expected.part_two);
+
+        let report = instance.optimize();
+        assert_eq!(
+            report,
+            OptimizationReport {
+                // x>5000 in chain, which no part passes, m>2100 in px and
+                // s>2800 in qqz, which earlier rules catch everything for,
+                // and the rule in gd that does the same as its fallback
+                removed_rules: 4,
+                bypassed: vec!["chain", "gd", "jmp", "lost"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                unreachable: vec!["chain", "gd", "jmp", "lost", "orphan"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+            }
+        );
+        // px sent a<2006 to R via jmp and chain, and rfg sent s<537 to R
+        // via gd
+        assert!(matches!(
+            instance.workflows[instance.names["px"]].rules[..],
+            [
+                Rule::Conditional(_, Action::Reject),
+                Rule::Conditional(_, Action::Accept),
+                Rule::Unconditional(Action::Workflow(_))
+            ]
+        ));
+
+        // the cached regions are invalidated, and nothing changes
+        assert_eq!(
+            Solution::new(instance.sort(), instance.combinations()),
+            expected
+        );
+        assert_eq!(instance.combinations_naive(), expected.part_two);
+
+        // a second pass has nothing left to do
+        let report = instance.optimize();
+        assert_eq!(report.removed_rules, 0);
+
+        // jumps that go round in circles are left alone
+        let mut looping = Aplenty::instance("in{x<10:a,A}\na{b}\nb{a}\n\n").unwrap();
+        let report = looping.optimize();
+        assert!(report.bypassed.is_empty());
+        assert!(report.unreachable.is_empty());
+    }
+
+    #[test]
+    fn optimize_generated() {
+        for seed in 0..20 {
+            let input = Workflows {
+                workflows: 60,
+                max_rules: 4,
+                parts: 50,
+            }
+            .generate_seeded(seed);
+            let mut instance = Aplenty::instance(&input).unwrap();
+            let expected = (instance.sort(), instance.combinations());
+            instance.optimize();
+            assert_eq!((instance.sort(), instance.combinations()), expected);
+            assert_eq!(instance.combinations_naive(), expected.1);
+        }
+    }
+
+    #[test]
+    fn set_rule_rejects_invalid() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let mut instance = Aplenty::instance(&input).unwrap();
+
+        assert!(instance.set_rule("foo", 0, "A").is_err());
+        assert!(instance.set_rule("crn", 5, "A").is_err());
+        assert!(instance.set_rule("crn", 1, "x>10:A").is_err());
+        assert!(instance.set_rule("crn", 0, "x>10:in").is_err());
+        assert!(instance.set_rule("crn", 0, "x>10:bar").is_err());
+        assert!(instance.set_rule("crn", 0, "x>10:A,R").is_err());
+        assert!(instance.set_rule("crn", 0, "q>10:A").is_err());
+        assert_eq!(instance.combinations(), 167409079868000);
+    }
+
+    #[test]
+    fn sort_parts() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = Aplenty::instance(&input).unwrap();
+        assert_eq!(instance.sort_parts(&instance.parts), 19114);
+        assert_eq!(instance.sort_parts(&[]), 0);
+
+        let parts =
+            parse_parts("{x=787,m=2655,a=1222,s=2876}\n{x=1679,m=44,a=2067,s=496}").unwrap();
+        assert_eq!(
+            parts[1],
+            Part {
+                x: 1679,
+                m: 44,
+                a: 2067,
+                s: 496
+            }
+        );
+        assert_eq!(instance.sort_parts(&parts), 7540);
+    }
+
+    #[test]
+    fn malformed_parts() {
+        let workflows = "in{x<10:R,A}\n\n";
+        assert!(Aplenty::instance(&format!("{workflows}{{x=1,m=2,a=3,s=4}}\r\n")).is_ok());
+
+        let err = Aplenty::instance(&format!("{workflows}{{x=1,m=2,s=4}}")).unwrap_err();
+        assert_eq!(err.to_string(), "expected \",a=\" at byte 8, found ','");
+        assert!(Aplenty::instance(&format!("{workflows}{{x=1,m=2,a=3,s=4}}{{x=1")).is_err());
+    }
+
+    #[test]
+    fn blank_lines() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let spaced = input.replace("\n\n", "\n\n \n\n").replace('\n', "\r\n");
+        assert_eq!(
+            Aplenty::solve(&spaced).unwrap(),
+            Aplenty::solve(&input).unwrap()
+        );
+
+        let workflows = input.split("\n\n").next().unwrap();
+        let mut instance = Aplenty::instance(workflows).unwrap();
+        assert_eq!(instance.part_one().unwrap(), 0);
+        assert_eq!(instance.part_two().unwrap(), 167409079868000);
+
+        assert!(Aplenty::instance("").is_err());
+        assert!(Aplenty::instance(&format!("{}\n\nin{{A}}", input)).is_err());
+    }
+
+    #[test]
+    fn unknown_workflow() {
+        let input = "in{x<10:foo,A}
+
+{x=1,m=2,a=3,s=4}";
+        assert!(Aplenty::instance(input).is_err());
+    }
+
+    fn attribute() -> impl Strategy<Value = Attribute> {
+        prop_oneof![
+            Just(Attribute::X),
+            Just(Attribute::M),
+            Just(Attribute::A),
+            Just(Attribute::S),
+        ]
+    }
+
+    /// Boxes that may start at 0 or reach past 4000, with every bound
+    /// tried against thresholds on either side of it.
+    fn ratings() -> impl Strategy<Value = Ratings> {
+        prop::array::uniform4((0..4002_u16, 0..4002_u16)).prop_map(|bounds| {
+            Ratings::new(bounds.map(|(a, b)| Interval::new(a.min(b), a.max(b) + 1)))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn condition_splits_ratings(
+            ratings in ratings(),
+            attribute in attribute(),
+            x in prop_oneof![0..4003_u16, Just(u16::MAX)],
+            less_than in any::<bool>(),
+            samples in prop::array::uniform4(any::<prop::sample::Index>()),
+        ) {
+            let dim = attribute.index();
+            let condition = if less_than {
+                Condition::LessThan(attribute, x)
+            } else {
+                Condition::GreaterThan(attribute, x)
+            };
+            let (matched, unmatched) = condition.apply_ratings(ratings);
+
+            // matched and unmatched don't overlap and cover the original
+            let volume = |r: &Option<Ratings>| r.map(|r| r.volume()).unwrap_or_default();
+            prop_assert_eq!(volume(&matched) + volume(&unmatched), ratings.volume());
+            if let (Some(m), Some(u)) = (matched, unmatched) {
+                prop_assert_eq!(m.intersection(&u), None);
+            }
+            for r in matched.iter().chain(unmatched.iter()) {
+                prop_assert!(!r.is_empty());
+                prop_assert_eq!(r.intersection(&ratings), Some(*r));
+            }
+
+            // and agree with the condition on the part itself, in particular
+            // at the threshold and either side of it
+            let interval = ratings.get(dim);
+            let sample = samples[dim].index(interval.len() as usize);
+            for value in [interval.start() + sample as u16, x.saturating_sub(1), x, x.saturating_add(1)] {
+                if !interval.contains(value) {
+                    continue;
+                }
+                let mut rating = [0; 4];
+                for (i, r) in rating.iter_mut().enumerate() {
+                    *r = if i == dim {
+                        value
+                    } else {
+                        ratings.get(i).start() + samples[i].index(ratings.get(i).len() as usize) as u16
+                    };
+                }
+                let point = Ratings::new(rating.map(|v| Interval::new(v, v + 1)));
+                let part = Part { x: rating[0], m: rating[1], a: rating[2], s: rating[3] };
+                let inside = |r: &Option<Ratings>| r.is_some_and(|r| r.intersection(&point).is_some());
+                prop_assert_eq!(inside(&matched), condition.apply(&part));
+                prop_assert_eq!(inside(&unmatched), !condition.apply(&part));
+            }
+        }
+    }
+
+    aoc_plumbing::parsing_never_panics!(Aplenty);
 }
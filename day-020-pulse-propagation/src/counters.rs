@@ -0,0 +1,203 @@
+//! Counting pulses without simulating them, for inputs built out of binary
+//! counters.
+//!
+//! Every real input has the same shape: the broadcaster starts a handful of
+//! chains of flip-flops, each of which counts button presses in binary. Some
+//! of the bits of each chain feed a hub conjunction, which resets the chain
+//! once the counter reaches the value `N` spelled out by those bits, and also
+//! feeds an inverter that leads (usually through one more conjunction) to
+//! `rx`.
+//!
+//! Until a counter reaches `N`, a press that takes it from `c` to `c + 1`
+//! turns off the trailing ones of `c` and turns on the bit above them, and
+//! every pulse the hub sees makes it send a high pulse. So over the first `R`
+//! presses, bit `i` turns off `R >> (i + 1)` times and on
+//! `(R >> i) - (R >> (i + 1))` times, and the pulses can be counted per bit.
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::modules::Module;
+
+/// A binary counter hanging off the broadcaster.
+#[derive(Debug, Clone, Default)]
+struct Counter {
+    /// The number of outputs of each bit, least significant first.
+    outputs: Vec<usize>,
+    /// Whether each bit feeds the hub.
+    feeds_hub: Vec<bool>,
+    /// The high and low pulses that follow from the hub receiving a pulse,
+    /// before the counter reaches its reset value.
+    hub_pulses: (usize, usize),
+    /// The value at which the hub fires a low pulse and resets the counter.
+    reset_at: usize,
+}
+
+impl Counter {
+    /// The high and low pulses sent by this counter over the first `presses`
+    /// presses, or `None` if the counter would reach its reset value.
+    fn pulses(&self, presses: usize) -> Option<(usize, usize)> {
+        if presses >= self.reset_at {
+            return None;
+        }
+
+        let (mut highs, mut lows) = (0, 0);
+        for (i, (&outputs, &feeds_hub)) in self.outputs.iter().zip(&self.feeds_hub).enumerate() {
+            let toggled = presses.checked_shr(i as u32).unwrap_or_default();
+            let off = presses.checked_shr(i as u32 + 1).unwrap_or_default();
+            let on = toggled - off;
+
+            lows += outputs * off;
+            highs += outputs * on;
+
+            if feeds_hub {
+                highs += self.hub_pulses.0 * toggled;
+                lows += self.hub_pulses.1 * toggled;
+            }
+        }
+
+        Some((highs, lows))
+    }
+}
+
+/// Counts the high and low pulses sent over the first `presses` presses,
+/// starting from the initial state. Returns `None` if the modules aren't
+/// laid out as counters, or if any counter would be reset within `presses`.
+pub(crate) fn count_pulses(
+    modules: &FxHashMap<u16, Module>,
+    presses: usize,
+) -> Option<(usize, usize)> {
+    let mut inputs: FxHashMap<u16, Vec<u16>> = FxHashMap::default();
+    for (&id, module) in modules {
+        for &target in module.outputs() {
+            inputs.entry(target).or_default().push(id);
+        }
+    }
+
+    let broadcaster = modules.get(&Module::BROADCASTER_ID)?;
+    // the button and the broadcaster
+    let mut lows = presses * (1 + broadcaster.outputs().len());
+    let mut highs = 0;
+    let mut seen = FxHashSet::default();
+
+    for &start in broadcaster.outputs() {
+        let counter = counter(modules, &inputs, start, &mut seen)?;
+        let (h, l) = counter.pulses(presses)?;
+        highs += h;
+        lows += l;
+    }
+
+    Some((highs, lows))
+}
+
+/// Follows the chain of flip-flops starting at `start`, checking that it is
+/// a counter that doesn't interact with anything else.
+fn counter(
+    modules: &FxHashMap<u16, Module>,
+    inputs: &FxHashMap<u16, Vec<u16>>,
+    start: u16,
+    seen: &mut FxHashSet<u16>,
+) -> Option<Counter> {
+    let is_flip_flop = |id: &u16| matches!(modules.get(id), Some(Module::FlipFlop(_)));
+    let is_conjunction = |id: &u16| matches!(modules.get(id), Some(Module::Conjunction(_)));
+
+    let mut bits = Vec::default();
+    let mut hub = None;
+    let mut cur = Some(start);
+
+    while let Some(id) = cur {
+        if !is_flip_flop(&id) || !seen.insert(id) {
+            return None;
+        }
+
+        let outputs = modules[&id].outputs();
+        if outputs
+            .iter()
+            .any(|x| !is_flip_flop(x) && !is_conjunction(x))
+        {
+            return None;
+        }
+
+        let mut next = outputs.iter().filter(|x| is_flip_flop(x));
+        cur = next.next().copied();
+        if next.next().is_some() {
+            return None;
+        }
+
+        for conjunction in outputs.iter().filter(|x| is_conjunction(x)) {
+            if *hub.get_or_insert(*conjunction) != *conjunction {
+                return None;
+            }
+        }
+
+        bits.push(id);
+    }
+
+    let hub = hub?;
+    if !seen.insert(hub) {
+        return None;
+    }
+
+    // besides the hub, each bit is only driven by the one below it (or the
+    // broadcaster, for the first)
+    for (i, id) in bits.iter().enumerate() {
+        let below = if i == 0 {
+            Module::BROADCASTER_ID
+        } else {
+            bits[i - 1]
+        };
+        if inputs[id].iter().any(|x| *x != below && *x != hub) {
+            return None;
+        }
+    }
+
+    if inputs[&hub].iter().any(|x| !bits.contains(x)) {
+        return None;
+    }
+
+    // the hub sends a high pulse to each of its outputs. The bits ignore
+    // them, and each inverter turns them into low pulses, which make any
+    // conjunction after it send high pulses
+    let hub_outputs = modules[&hub].outputs();
+    let mut hub_pulses = (hub_outputs.len(), 0);
+    for target in hub_outputs {
+        if bits.contains(target) {
+            continue;
+        }
+
+        if !is_conjunction(target) || inputs[target].len() != 1 || !seen.insert(*target) {
+            return None;
+        }
+
+        for next in modules[target].outputs() {
+            hub_pulses.1 += 1;
+            match modules.get(next) {
+                None => (),
+                Some(Module::Conjunction(_)) => {
+                    let outputs = modules[next].outputs();
+                    if outputs.iter().any(|x| modules.contains_key(x)) {
+                        return None;
+                    }
+                    hub_pulses.0 += outputs.len();
+                }
+                Some(_) => return None,
+            }
+        }
+    }
+
+    let feeds_hub: Vec<bool> = bits
+        .iter()
+        .map(|id| modules[id].outputs().contains(&hub))
+        .collect();
+    let reset_at = feeds_hub
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| **f)
+        .map(|(i, _)| 1_usize.checked_shl(i as u32))
+        .sum::<Option<usize>>()?;
+
+    Some(Counter {
+        outputs: bits.iter().map(|id| modules[id].outputs().len()).collect(),
+        feeds_hub,
+        hub_pulses,
+        reset_at,
+    })
+}
@@ -7,6 +7,7 @@ use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::modules::Module;
 
+mod counters;
 mod modules;
 
 #[derive(Debug, Clone)]
@@ -33,7 +34,7 @@ pub struct PulsePropagation {
 }
 
 impl PulsePropagation {
-    fn min_presses(&mut self) -> usize {
+    fn min_presses(&mut self) -> u64 {
         let mut round = 0;
         let mut ret = 1;
         let mut seen = FxHashSet::default();
@@ -74,7 +75,23 @@ impl PulsePropagation {
         ret
     }
 
-    fn simulate(&mut self, rounds: usize) -> Result<usize> {
+    /// Returns the product of the number of high and low pulses sent over
+    /// `rounds` button presses, starting from the initial state. Inputs made
+    /// of binary counters (which every real input is) are counted without
+    /// simulating, as long as none of the counters reset within `rounds`.
+    /// Anything else falls back to `simulate`.
+    pub fn pulses(&mut self, rounds: usize) -> Result<u64> {
+        if let Some((highs, lows)) = counters::count_pulses(&self.modules, rounds) {
+            return Ok(highs as u64 * lows as u64);
+        }
+
+        self.reset();
+        self.simulate(rounds)
+    }
+
+    /// Like `pulses`, but always simulates each press, carrying on from the
+    /// current state.
+    pub fn simulate(&mut self, rounds: usize) -> Result<u64> {
         let mut highs = 0;
         let mut lows = 0;
 
@@ -84,7 +101,7 @@ impl PulsePropagation {
             lows += result.1;
         }
 
-        Ok(highs * lows)
+        Ok(highs as u64 * lows as u64)
     }
 
     fn simulate_one(&mut self) -> Result<(usize, usize)> {
@@ -161,16 +178,17 @@ impl FromStr for PulsePropagation {
 }
 
 impl Problem for PulsePropagation {
+    const YEAR: usize = 2023;
     const DAY: usize = 20;
     const TITLE: &'static str = "pulse propagation";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        self.simulate(1000)
+        self.pulses(1000)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
@@ -181,9 +199,55 @@ impl Problem for PulsePropagation {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use aoc_plumbing::Solution;
 
-    use super::*;
+    /// Builds an input made of one binary counter of `bits` bits for each
+    /// of the given reset values, laid out like the real inputs.
+    fn counters_input(resets: &[usize], bits: usize) -> String {
+        let label = |chain: usize, bit: usize| {
+            format!(
+                "{}{}",
+                (b'a' + chain as u8) as char,
+                (b'a' + bit as u8) as char
+            )
+        };
+        let mut lines = Vec::default();
+
+        let starts: Vec<String> = (0..resets.len()).map(|c| label(c, 0)).collect();
+        lines.push(format!("broadcaster -> {}", starts.join(", ")));
+
+        for (c, &reset) in resets.iter().enumerate() {
+            let hub = format!("q{}", c);
+            let mut hub_outputs = vec![format!("v{}", c)];
+            for i in 0..bits {
+                let mut outputs = Vec::default();
+                if i + 1 < bits {
+                    outputs.push(label(c, i + 1));
+                }
+                if reset >> i & 1 == 1 {
+                    outputs.push(hub.clone());
+                }
+                if i == 0 || reset >> i & 1 == 0 {
+                    hub_outputs.push(label(c, i));
+                }
+                lines.push(format!("%{} -> {}", label(c, i), outputs.join(", ")));
+            }
+            lines.push(format!("&{} -> {}", hub, hub_outputs.join(", ")));
+            lines.push(format!("&v{} -> zz", c));
+        }
+        lines.push("&zz -> rx".to_string());
+
+        lines.join("\n")
+    }
+
+    /// The high and low pulses from simulating `rounds` presses.
+    fn simulate_counts(mut instance: PulsePropagation, rounds: usize) -> (usize, usize) {
+        (0..rounds).fold((0, 0), |(h, l), _| {
+            let (dh, dl) = instance.simulate_one().unwrap();
+            (h + dh, l + dl)
+        })
+    }
 
     #[test]
     #[ignore]
@@ -191,6 +255,12 @@ mod tests {
         let input = std::fs::read_to_string("input.txt").expect("Unable to load input");
         let solution = PulsePropagation::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(836127690, 240914003753369));
+
+        let instance = PulsePropagation::instance(&input).unwrap();
+        assert_eq!(
+            counters::count_pulses(&instance.modules, 1000),
+            Some(simulate_counts(instance, 1000))
+        );
     }
 
     #[test]
@@ -200,6 +270,43 @@ mod tests {
         assert_eq!(instance.part_one().unwrap(), 32000000);
     }
 
+    #[test]
+    fn counters_match_simulation() {
+        let resets = [37, 45, 61];
+        let input = counters_input(&resets, 6);
+        let instance = PulsePropagation::instance(&input).unwrap();
+
+        for rounds in 0..37 {
+            assert_eq!(
+                counters::count_pulses(&instance.modules, rounds),
+                Some(simulate_counts(instance.clone(), rounds)),
+                "rounds = {rounds}"
+            );
+        }
+
+        // the first counter resets on the 37th press, so that has to be
+        // simulated
+        assert_eq!(counters::count_pulses(&instance.modules, 37), None);
+        let mut fresh = instance.clone();
+        let (h, l) = simulate_counts(instance, 100);
+        assert_eq!(fresh.pulses(100).unwrap(), (h * l) as u64);
+    }
+
+    #[test]
+    fn irregular_graphs_are_simulated() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = PulsePropagation::instance(&input).unwrap();
+        assert_eq!(counters::count_pulses(&instance.modules, 10), None);
+
+        // a counter whose hub also feeds an unrelated flip-flop
+        let input = format!("{}\n%zy -> rx", counters_input(&[37], 6))
+            .replace("&q0 -> v0", "&q0 -> zy, v0");
+        let mut instance = PulsePropagation::instance(&input).unwrap();
+        assert_eq!(counters::count_pulses(&instance.modules, 10), None);
+        let (h, l) = simulate_counts(instance.clone(), 10);
+        assert_eq!(instance.pulses(10).unwrap(), (h * l) as u64);
+    }
+
     #[test]
     fn example_two() {
         let input = "broadcaster -> a
@@ -210,4 +317,6 @@ mod tests {
         let mut instance = PulsePropagation::instance(input).unwrap();
         assert_eq!(instance.part_one().unwrap(), 11687500);
     }
+
+    aoc_plumbing::parsing_never_panics!(PulsePropagation);
 }
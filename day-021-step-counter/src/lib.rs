@@ -2,7 +2,8 @@ use std::{collections::VecDeque, str::FromStr};
 
 use anyhow::bail;
 use aoc_common::grid::{Coordinate, Grid};
-use aoc_plumbing::Problem;
+use aoc_plumbing::{Description, Problem};
+use rustc_hash::FxHashSet;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Tile {
@@ -28,29 +29,51 @@ enum Parity {
     Even,
 }
 
+impl Parity {
+    fn of(steps: usize) -> Self {
+        if steps.is_multiple_of(2) {
+            Self::Even
+        } else {
+            Self::Odd
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StepCounter {
     grid: Grid<Tile>,
 }
 
 impl StepCounter {
-    fn step_counter(&self, steps: usize) -> usize {
+    fn step_counter(&self, steps: usize) -> u64 {
         // we are making a bunch of assumptions here:
         // - all inputs have the same size and are square
         // - start is always in the middle
         // - the edges and the middle row and column does not have rocks
+        // - steps is r more than a (non-zero) multiple of n
         //
         // this solution follows the logic behind this [diagram](https://raw.githubusercontent.com/Manitary/advent-of-code/c44838423066b3c8d446f0d94f2a19d675f2b6dc/2023/python/day21.png)
         let n = self.grid.n;
         let r = n / 2;
-        let x = steps / n;
+        let x = (steps / n) as u64;
+        let bfs = |start: Coordinate, steps, parity| self.bfs(start, steps, parity) as u64;
         let mut ret = 0;
 
+        // a region k regions away from the start is offset by k * n steps, so
+        // which parity we count in it flips with k. Working through the
+        // distances, the x * x regions and the outer corners want the parity
+        // of r + 1, and the rest want the parity of r.
+        let (even, odd) = if r % 2 == 1 {
+            (Parity::Even, Parity::Odd)
+        } else {
+            (Parity::Odd, Parity::Even)
+        };
+
         // add regions that are completely reachable
         let even_regions = x * x;
         let odd_regions = (x - 1) * (x - 1);
-        ret += self.bfs(self.start(), steps, Parity::Even) * even_regions;
-        ret += self.bfs(self.start(), steps, Parity::Odd) * odd_regions;
+        ret += bfs(self.start(), steps, even) * even_regions;
+        ret += bfs(self.start(), steps, odd) * odd_regions;
 
         // add the 4 cardinal regions
         //
@@ -58,30 +81,30 @@ impl StepCounter {
         // region on an odd tile, which means we must flip the parity to even from the perspective
         // of the start tile.
         let parity = Parity::Even;
-        ret += self.bfs((n - 1, r).into(), n - 1, parity); // s
-        ret += self.bfs((0, r).into(), n - 1, parity); // n
-        ret += self.bfs((r, 0).into(), n - 1, parity); // w
-        ret += self.bfs((r, n - 1).into(), n - 1, parity); // e
+        ret += bfs((n - 1, r).into(), n - 1, parity); // s
+        ret += bfs((0, r).into(), n - 1, parity); // n
+        ret += bfs((r, 0).into(), n - 1, parity); // w
+        ret += bfs((r, n - 1).into(), n - 1, parity); // e
 
         // add all the "sides" of the diamond
-        let outer_parity = Parity::Even;
-        let inner_parity = Parity::Odd;
+        let outer_parity = even;
+        let inner_parity = odd;
 
         // ne
-        ret += self.bfs((0, n - 1).into(), r - 1, outer_parity) * x;
-        ret += self.bfs((0, n - 1).into(), n + r - 1, inner_parity) * (x - 1);
+        ret += bfs((0, n - 1).into(), r - 1, outer_parity) * x;
+        ret += bfs((0, n - 1).into(), n + r - 1, inner_parity) * (x - 1);
 
         // se
-        ret += self.bfs((n - 1, n - 1).into(), r - 1, outer_parity) * x;
-        ret += self.bfs((n - 1, n - 1).into(), n + r - 1, inner_parity) * (x - 1);
+        ret += bfs((n - 1, n - 1).into(), r - 1, outer_parity) * x;
+        ret += bfs((n - 1, n - 1).into(), n + r - 1, inner_parity) * (x - 1);
 
         // nw
-        ret += self.bfs((0isize, 0isize).into(), r - 1, outer_parity) * x;
-        ret += self.bfs((0isize, 0isize).into(), n + r - 1, inner_parity) * (x - 1);
+        ret += bfs((0isize, 0isize).into(), r - 1, outer_parity) * x;
+        ret += bfs((0isize, 0isize).into(), n + r - 1, inner_parity) * (x - 1);
 
         // sw
-        ret += self.bfs((n - 1, 0).into(), r - 1, outer_parity) * x;
-        ret += self.bfs((n - 1, 0).into(), n + r - 1, inner_parity) * (x - 1);
+        ret += bfs((n - 1, 0).into(), r - 1, outer_parity) * x;
+        ret += bfs((n - 1, 0).into(), n + r - 1, inner_parity) * (x - 1);
 
         ret
     }
@@ -115,6 +138,86 @@ impl StepCounter {
         ret
     }
 
+    /// Counts the plots reachable in exactly `steps` steps from the start,
+    /// without leaving the map. Since the elf can always step back and forth,
+    /// these are the plots within `steps` steps with the same parity as
+    /// `steps`. If the start is a rock there is nowhere to stand, so this is
+    /// 0.
+    pub fn reachable_exactly(&self, steps: usize) -> usize {
+        let start = self.start();
+        if self.grid.get(start) != Some(Tile::Garden) {
+            return 0;
+        }
+
+        self.bfs(start, steps, Parity::of(steps))
+    }
+
+    /// Counts the plots reachable in exactly `steps` steps on the infinitely
+    /// tiled map with a plain BFS. This makes none of the assumptions that
+    /// `step_counter` does, but is only practical for small step counts, so it
+    /// is mostly useful for checking against.
+    pub fn reachable_tiled(&self, steps: usize) -> usize {
+        let mut ret = 0;
+        let mut visited = FxHashSet::default();
+        let mut q = VecDeque::default();
+        q.push_back((self.start(), 0));
+        visited.insert(self.start());
+
+        while let Some((coord, dist)) = q.pop_front() {
+            if dist % 2 == steps % 2 {
+                ret += 1;
+            }
+
+            if dist == steps {
+                continue;
+            }
+
+            for n in coord.cardinal_neighbours() {
+                if self.grid.get_wrapping(n) != Tile::Rock && visited.insert(n) {
+                    q.push_back((n, dist + 1));
+                }
+            }
+        }
+
+        ret
+    }
+
+    /// Renders the plots reachable in exactly `k` steps (within a single
+    /// copy of the map) as `O`, one frame for each `k` from 0 to `steps`.
+    #[cfg(feature = "visualize")]
+    pub fn render_frames<R: aoc_common::visualize::Renderer>(
+        &self,
+        renderer: &mut R,
+        steps: usize,
+    ) -> std::io::Result<()> {
+        use aoc_common::visualize::frame;
+
+        let base = frame(&self.grid, |t| match t {
+            Tile::Garden => '.',
+            Tile::Rock => '#',
+        });
+        let mut frontier = FxHashSet::default();
+        frontier.insert(self.start());
+
+        for k in 0..=steps {
+            let mut f = base.clone();
+            for coord in frontier.iter() {
+                f[*coord] = 'O';
+            }
+            renderer.render(&f)?;
+
+            if k < steps {
+                frontier = frontier
+                    .iter()
+                    .flat_map(|c| c.cardinal_neighbours())
+                    .filter(|n| self.grid.get(*n) == Some(Tile::Garden))
+                    .collect();
+            }
+        }
+
+        Ok(())
+    }
+
     fn start(&self) -> Coordinate {
         let r = self.grid.n / 2;
         (r, r).into()
@@ -125,35 +228,72 @@ impl FromStr for StepCounter {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let grid = Grid::from_str(s)?;
+        let grid = Grid::parse(s)?;
         Ok(Self { grid })
     }
 }
 
 impl Problem for StepCounter {
+    const YEAR: usize = 2023;
     const DAY: usize = 21;
     const TITLE: &'static str = "step counter";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.bfs(self.start(), 64, Parity::Even))
+        Ok(self.reachable_exactly(64) as u64)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
         Ok(self.step_counter(26501365))
     }
+
+    fn describe(&self) -> Description {
+        Description::default()
+            .grid(self.grid.n, self.grid.m)
+            .with("garden plots", self.grid.count(|t| *t == Tile::Garden))
+            .with("rocks", self.grid.count(|t| *t == Tile::Rock))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use aoc_plumbing::Solution;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
 
     use super::*;
 
+    /// Generates an `n` by `n` map (`n` odd) that satisfies the assumptions
+    /// made by `step_counter`: the start is in the middle, and the border and
+    /// the middle row and column are clear of rocks. Every other tile is a
+    /// rock with the given probability.
+    fn generate(n: usize, density: f64, seed: u64) -> String {
+        assert!(n % 2 == 1);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let r = n / 2;
+        let mut out = String::with_capacity(n * (n + 1));
+
+        for row in 0..n {
+            for col in 0..n {
+                let clear = row == 0 || col == 0 || row == n - 1 || col == n - 1;
+                let clear = clear || row == r || col == r;
+                if row == r && col == r {
+                    out.push('S');
+                } else if !clear && rng.gen_bool(density) {
+                    out.push('#');
+                } else {
+                    out.push('.');
+                }
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
     #[test]
     #[ignore]
     fn full_dataset() {
@@ -166,6 +306,74 @@ mod tests {
     fn example() {
         let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
         let instance = StepCounter::instance(&input).unwrap();
-        assert_eq!(instance.bfs(instance.start(), 6, Parity::Even), 16);
+        assert_eq!(instance.reachable_exactly(6), 16);
+
+        for (steps, expected) in [(6, 16), (10, 50), (50, 1594), (100, 6536)] {
+            assert_eq!(instance.reachable_tiled(steps) as u64, expected);
+        }
+    }
+
+    #[test]
+    fn describe() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = StepCounter::instance(&input).unwrap();
+        assert_eq!(
+            instance.describe().to_string(),
+            "grid: 11x11\ngarden plots: 81\nrocks: 40"
+        );
+    }
+
+    #[test]
+    fn reachable_exactly() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = StepCounter::instance(&input).unwrap();
+        assert_eq!(instance.reachable_exactly(0), 1);
+        assert_eq!(instance.reachable_exactly(1), 2);
+        assert_eq!(instance.reachable_exactly(2), 4);
+        assert_eq!(instance.reachable_exactly(3), 6);
+
+        let instance = StepCounter::instance("...\n.#.\n...\n").unwrap();
+        assert_eq!(instance.reachable_exactly(0), 0);
+        assert_eq!(instance.reachable_exactly(4), 0);
+    }
+
+    #[test]
+    fn synthetic_matches_brute_force() {
+        for (n, density) in [(9, 0.1), (11, 0.1), (13, 0.2), (15, 0.15), (21, 0.25)] {
+            for seed in 0..3 {
+                let input = generate(n, density, seed);
+                let instance = StepCounter::instance(&input).unwrap();
+                for x in 1..=4 {
+                    let steps = x * n + n / 2;
+                    assert_eq!(
+                        instance.step_counter(steps),
+                        instance.reachable_tiled(steps) as u64,
+                        "n = {n}, density = {density}, seed = {seed}, steps = {steps}\n{input}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "visualize")]
+    #[test]
+    fn render_frames() {
+        use aoc_common::visualize::TextRenderer;
+
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = StepCounter::instance(&input).unwrap();
+        let mut renderer = TextRenderer::new(Vec::new());
+        instance.render_frames(&mut renderer, 6).unwrap();
+
+        let out = String::from_utf8(renderer.into_inner()).unwrap();
+        let frames = out
+            .split("\n\n")
+            .filter(|f| !f.is_empty())
+            .collect::<Vec<_>>();
+        assert_eq!(frames.len(), 7);
+        assert_eq!(frames[0].chars().filter(|c| *c == 'O').count(), 1);
+        assert_eq!(frames[6].chars().filter(|c| *c == 'O').count(), 16);
     }
+
+    aoc_plumbing::parsing_never_panics!(StepCounter);
 }
@@ -1,9 +1,10 @@
-use std::{collections::VecDeque, str::FromStr};
+use std::{
+    ops::{Range, RangeInclusive},
+    str::FromStr,
+};
 
-use anyhow::bail;
 use aoc_common::algebra::{Point2, Point3};
-use aoc_plumbing::Problem;
-use rayon::prelude::*;
+use aoc_plumbing::{scan::Scanner, Description, Problem};
 use rustc_hash::{FxHashMap, FxHashSet};
 
 #[derive(Debug, Clone)]
@@ -27,6 +28,13 @@ impl Slab {
         self.b.z -= drop_by;
     }
 
+    fn footprint(&self) -> (Range<i64>, Range<i64>) {
+        (
+            self.a.x.min(self.b.x)..self.a.x.max(self.b.x) + 1,
+            self.a.y.min(self.b.y)..self.a.y.max(self.b.y) + 1,
+        )
+    }
+
     fn points(&self) -> Vec<Point3<i64>> {
         let (min_x, max_x) = (self.a.x.min(self.b.x), self.a.x.max(self.b.x));
         let (min_y, max_y) = (self.a.y.min(self.b.y), self.a.y.max(self.b.y));
@@ -45,25 +53,217 @@ impl Slab {
     }
 }
 
-impl FromStr for Slab {
-    type Err = anyhow::Error;
+impl Slab {
+    fn scan(s: &mut Scanner) -> anyhow::Result<Self> {
+        let a = scan_point(s)?;
+        s.expect(b'~')?;
+        let b = scan_point(s)?;
+        Ok(Self { a, b })
+    }
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((l, r)) = s.split_once('~') {
-            let a_coords = l
-                .split(',')
-                .map(|x| x.parse())
-                .collect::<Result<Vec<_>, _>>()?;
-            let a = Point3::new(a_coords[0], a_coords[1], a_coords[2]);
-            let b_coords = r
-                .split(',')
-                .map(|x| x.parse())
-                .collect::<Result<Vec<_>, _>>()?;
-            let b = Point3::new(b_coords[0], b_coords[1], b_coords[2]);
-            Ok(Self { a, b })
-        } else {
-            bail!("invalid slab")
+fn scan_point(s: &mut Scanner) -> anyhow::Result<Point3<i64>> {
+    let x = s.int()?;
+    s.expect(b',')?;
+    let y = s.int()?;
+    s.expect(b',')?;
+    let z = s.int()?;
+    Ok(Point3::new(x, y, z))
+}
+
+/// Dominator tree over the support graph, rooted at the ground (node 0). Slab
+/// `i` is node `i + 1`. Nodes must be inserted in topological order, which is
+/// guaranteed by settling the slabs from the bottom up.
+#[derive(Debug, Clone)]
+struct DominatorTree {
+    depth: Vec<usize>,
+    // ancestors[k][v] is the 2^k-th ancestor of v
+    ancestors: Vec<Vec<usize>>,
+}
+
+impl DominatorTree {
+    fn new(n: usize) -> Self {
+        let levels = (usize::BITS - n.leading_zeros()).max(1) as usize;
+        Self {
+            depth: vec![0; n],
+            ancestors: vec![vec![0; n]; levels],
+        }
+    }
+
+    fn insert(&mut self, v: usize, idom: usize) {
+        self.depth[v] = self.depth[idom] + 1;
+        self.ancestors[0][v] = idom;
+        for k in 1..self.ancestors.len() {
+            self.ancestors[k][v] = self.ancestors[k - 1][self.ancestors[k - 1][v]];
+        }
+    }
+
+    fn lca(&self, mut a: usize, mut b: usize) -> usize {
+        if self.depth[a] < self.depth[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        for k in (0..self.ancestors.len()).rev() {
+            if self.depth[a] - self.depth[b] >= 1 << k {
+                a = self.ancestors[k][a];
+            }
+        }
+
+        if a == b {
+            return a;
+        }
+
+        for k in (0..self.ancestors.len()).rev() {
+            if self.ancestors[k][a] != self.ancestors[k][b] {
+                a = self.ancestors[k][a];
+                b = self.ancestors[k][b];
+            }
         }
+
+        self.ancestors[0][a]
+    }
+
+    /// Returns the size of the subtree rooted at every node. Because parents
+    /// always precede their children, a single reverse pass suffices.
+    fn subtree_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![1; self.depth.len()];
+        for v in (1..sizes.len()).rev() {
+            sizes[self.ancestors[0][v]] += sizes[v];
+        }
+        sizes
+    }
+}
+
+/// Segment tree over one axis where every update raises a range to at least
+/// some value. Because values only ever go up, nothing needs to be pushed
+/// down: each node keeps the max of everything below it, and the max of the
+/// updates that covered it entirely.
+#[derive(Debug, Clone)]
+struct MaxTree {
+    len: usize,
+    max: Vec<i64>,
+    tag: Vec<i64>,
+}
+
+impl MaxTree {
+    fn new(len: usize) -> Self {
+        Self {
+            len,
+            max: vec![0; 4 * len.max(1)],
+            tag: vec![0; 4 * len.max(1)],
+        }
+    }
+
+    fn raise(&mut self, range: &Range<usize>, value: i64) {
+        self.raise_helper(0, 0..self.len, range, value);
+    }
+
+    fn raise_helper(&mut self, node: usize, span: Range<usize>, range: &Range<usize>, value: i64) {
+        if range.end <= span.start || span.end <= range.start {
+            return;
+        }
+
+        self.max[node] = self.max[node].max(value);
+        if range.start <= span.start && span.end <= range.end {
+            self.tag[node] = self.tag[node].max(value);
+            return;
+        }
+
+        let mid = (span.start + span.end) / 2;
+        self.raise_helper(2 * node + 1, span.start..mid, range, value);
+        self.raise_helper(2 * node + 2, mid..span.end, range, value);
+    }
+
+    fn max(&self, range: &Range<usize>) -> i64 {
+        self.max_helper(0, 0..self.len, range)
+    }
+
+    fn max_helper(&self, node: usize, span: Range<usize>, range: &Range<usize>) -> i64 {
+        if range.end <= span.start || span.end <= range.start {
+            return 0;
+        }
+
+        if range.start <= span.start && span.end <= range.end {
+            return self.max[node];
+        }
+
+        let mid = (span.start + span.end) / 2;
+        self.tag[node]
+            .max(self.max_helper(2 * node + 1, span.start..mid, range))
+            .max(self.max_helper(2 * node + 2, mid..span.end, range))
+    }
+}
+
+/// The same idea as [MaxTree] one level up: a segment tree over x where every
+/// node holds two [MaxTree]s over y, so raising or querying a rectangle
+/// visits O(log x) nodes, each costing O(log y).
+#[derive(Debug, Clone)]
+struct MaxTree2D {
+    len: usize,
+    max: Vec<MaxTree>,
+    tag: Vec<MaxTree>,
+}
+
+impl MaxTree2D {
+    fn new(x_len: usize, y_len: usize) -> Self {
+        Self {
+            len: x_len,
+            max: vec![MaxTree::new(y_len); 4 * x_len.max(1)],
+            tag: vec![MaxTree::new(y_len); 4 * x_len.max(1)],
+        }
+    }
+
+    fn raise(&mut self, x: &Range<usize>, y: &Range<usize>, value: i64) {
+        self.raise_helper(0, 0..self.len, x, y, value);
+    }
+
+    fn raise_helper(
+        &mut self,
+        node: usize,
+        span: Range<usize>,
+        x: &Range<usize>,
+        y: &Range<usize>,
+        value: i64,
+    ) {
+        if x.end <= span.start || span.end <= x.start {
+            return;
+        }
+
+        self.max[node].raise(y, value);
+        if x.start <= span.start && span.end <= x.end {
+            self.tag[node].raise(y, value);
+            return;
+        }
+
+        let mid = (span.start + span.end) / 2;
+        self.raise_helper(2 * node + 1, span.start..mid, x, y, value);
+        self.raise_helper(2 * node + 2, mid..span.end, x, y, value);
+    }
+
+    fn max(&self, x: &Range<usize>, y: &Range<usize>) -> i64 {
+        self.max_helper(0, 0..self.len, x, y)
+    }
+
+    fn max_helper(
+        &self,
+        node: usize,
+        span: Range<usize>,
+        x: &Range<usize>,
+        y: &Range<usize>,
+    ) -> i64 {
+        if x.end <= span.start || span.end <= x.start {
+            return 0;
+        }
+
+        if x.start <= span.start && span.end <= x.end {
+            return self.max[node].max(y);
+        }
+
+        let mid = (span.start + span.end) / 2;
+        self.tag[node]
+            .max(y)
+            .max(self.max_helper(2 * node + 1, span.start..mid, x, y))
+            .max(self.max_helper(2 * node + 2, mid..span.end, x, y))
     }
 }
 
@@ -73,10 +273,62 @@ pub struct SandSlabs {
     supports: FxHashMap<usize, FxHashSet<usize>>,
     supported_by: FxHashMap<usize, FxHashSet<usize>>,
     cant_remove: FxHashSet<usize>,
+    settled: bool,
 }
 
+/// Slabs are identified by their position in the input once sorted by their
+/// bottom, with ties kept in input order. This is also the order they settle
+/// in, so a slab only ever rests on slabs with smaller ids.
 impl SandSlabs {
+    /// The lowest and highest z each slab occupies once everything has
+    /// settled.
+    pub fn settled_z(&mut self) -> Vec<RangeInclusive<i64>> {
+        self.ensure_settled();
+        self.slabs.iter().map(|s| s.bottom()..=s.top()).collect()
+    }
+
+    /// The sorted ids of the slabs that rest on the ground once everything
+    /// has settled.
+    pub fn on_ground(&mut self) -> Vec<usize> {
+        self.ensure_settled();
+        (0..self.slabs.len())
+            .filter(|i| self.supported_by[i].is_empty())
+            .collect()
+    }
+
+    /// For every slab, the sorted ids of the slabs resting directly on it.
+    pub fn supports(&mut self) -> Vec<Vec<usize>> {
+        self.ensure_settled();
+        adjacency(&self.supports, self.slabs.len())
+    }
+
+    /// For every slab, the sorted ids of the slabs it rests directly on.
+    pub fn supported_by(&mut self) -> Vec<Vec<usize>> {
+        self.ensure_settled();
+        adjacency(&self.supported_by, self.slabs.len())
+    }
+
+    fn ensure_settled(&mut self) {
+        if !self.settled {
+            self.settle();
+        }
+    }
+
     fn disintegratable(&mut self) -> usize {
+        self.ensure_settled();
+
+        for v in self.supported_by.values() {
+            if v.len() == 1 {
+                self.cant_remove.extend(v);
+            }
+        }
+
+        self.slabs.len() - self.cant_remove.len()
+    }
+
+    /// Drops every slab as far as it will go and records which slabs rest on
+    /// which, keeping a map of the top slab in every column.
+    fn settle(&mut self) {
         let mut heightmap: FxHashMap<Point2<i64>, (i64, usize)> = FxHashMap::default();
 
         for (i, slab) in self.slabs.iter_mut().enumerate() {
@@ -115,59 +367,124 @@ impl SandSlabs {
             }
         }
 
-        for v in self.supported_by.values() {
-            if v.len() == 1 {
-                self.cant_remove.extend(v);
-            }
-        }
-
-        self.slabs.len() - self.cant_remove.len()
+        self.settled = true;
     }
 
-    fn remove(&self) -> usize {
-        self.cant_remove
-            .par_iter()
-            .map(|x| self.remove_one(*x))
-            .sum()
-    }
+    /// Does the same as settling the slabs for part one, but finds the height
+    /// each slab lands at with a 2D segment tree over the footprint of the
+    /// whole stack. A hash map of the slab on top of each column is still
+    /// used to find what it rests on. The slabs are already sorted by their
+    /// bottom, so this is a sweep up through the stack.
+    ///
+    /// The tree is built over only the x and y values where some footprint
+    /// starts or ends, so its size depends on the number of slabs rather than
+    /// the area they cover. It's still about two to three times slower than
+    /// the heightmap on the generated stacks of 1,000 to 100,000 slabs in the
+    /// experimental benchmarks. Both parts use the result if this is called
+    /// first.
+    pub fn settle_sweepline(&mut self) {
+        let mut xs = Vec::with_capacity(2 * self.slabs.len());
+        let mut ys = Vec::with_capacity(2 * self.slabs.len());
+        for slab in self.slabs.iter() {
+            let (x, y) = slab.footprint();
+            xs.extend([x.start, x.end]);
+            ys.extend([y.start, y.end]);
+        }
+        for v in [&mut xs, &mut ys] {
+            v.sort_unstable();
+            v.dedup();
+        }
+        let compress = |r: Range<i64>, values: &[i64]| {
+            values.partition_point(|&v| v < r.start)..values.partition_point(|&v| v < r.end)
+        };
+
+        let mut tree = MaxTree2D::new(xs.len().saturating_sub(1), ys.len().saturating_sub(1));
+        // the slab on top of every column with anything in it
+        let mut owner: FxHashMap<Point2<i64>, usize> = FxHashMap::default();
+
+        for i in 0..self.slabs.len() {
+            let (x, y) = self.slabs[i].footprint();
+            let (cx, cy) = (compress(x.clone(), &xs), compress(y.clone(), &ys));
+
+            let z = tree.max(&cx, &cy);
+            let mut below = FxHashSet::default();
+            if z > 0 {
+                for col in x.clone() {
+                    for row in y.clone() {
+                        if let Some(&id) = owner.get(&Point2::new(col, row)) {
+                            if self.slabs[id].top() == z {
+                                below.insert(id);
+                            }
+                        }
+                    }
+                }
+            }
 
-    fn remove_one(&self, to_remove: usize) -> usize {
-        let mut supported_by = self.supported_by.clone();
-        let mut q = VecDeque::default();
-        let mut ret = 0;
+            self.supports.insert(i, FxHashSet::default());
+            for id in below.iter() {
+                self.supports.entry(*id).or_default().insert(i);
+            }
+            self.supported_by.insert(i, below);
 
-        q.push_back(to_remove);
+            self.slabs[i].drop(z);
+            tree.raise(&cx, &cy, self.slabs[i].top());
+            for col in x {
+                for row in y.clone() {
+                    owner.insert(Point2::new(col, row), i);
+                }
+            }
+        }
 
-        while let Some(n) = q.pop_front() {
-            // for each node m with an edge e from n to m
-            for m in &self.supports[&n] {
-                // remove edge e from the graph
-                supported_by.entry(*m).and_modify(|x| {
-                    x.remove(&n);
-                });
+        self.settled = true;
+    }
 
-                // if m has no other incoming edges then insert m into q
-                if supported_by[m].is_empty() {
-                    q.push_back(*m);
+    /// Returns the total number of slabs that would fall, summed over
+    /// removing each slab on its own, in near-linear time. A slab falls when
+    /// another is removed iff the removed slab dominates it in the support
+    /// graph rooted at the ground, so the number of slabs that fall is the
+    /// size of the removed slab's dominator subtree, minus itself.
+    fn remove_dominators(&self) -> usize {
+        let mut tree = DominatorTree::new(self.slabs.len() + 1);
 
-                    // also track this brick as fallen
-                    ret += 1;
-                }
-            }
+        for i in 0..self.slabs.len() {
+            let idom = self.supported_by[&i]
+                .iter()
+                .map(|&s| s + 1)
+                .reduce(|a, b| tree.lca(a, b))
+                .unwrap_or_default();
+            tree.insert(i + 1, idom);
         }
 
-        ret
+        tree.subtree_sizes()[1..].iter().map(|x| x - 1).sum()
     }
 }
 
+fn adjacency(edges: &FxHashMap<usize, FxHashSet<usize>>, n: usize) -> Vec<Vec<usize>> {
+    (0..n)
+        .map(|i| {
+            let mut v: Vec<usize> = edges[&i].iter().copied().collect();
+            v.sort_unstable();
+            v
+        })
+        .collect()
+}
+
 impl FromStr for SandSlabs {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut slabs = s
-            .lines()
-            .map(Slab::from_str)
-            .collect::<Result<Vec<_>, _>>()?;
+        let mut scanner = Scanner::new(s);
+        let mut slabs = Vec::default();
+        scanner.skip_whitespace();
+        while !scanner.is_empty() {
+            slabs.push(Slab::scan(&mut scanner)?);
+            // one slab per line
+            scanner.eat(b'\r');
+            if !scanner.is_empty() {
+                scanner.expect(b'\n')?;
+            }
+            scanner.skip_whitespace();
+        }
         slabs.sort_by_key(|x| x.bottom());
 
         Ok(Self {
@@ -175,34 +492,93 @@ impl FromStr for SandSlabs {
             supports: FxHashMap::default(),
             supported_by: FxHashMap::default(),
             cant_remove: FxHashSet::default(),
+            settled: false,
         })
     }
 }
 
 impl Problem for SandSlabs {
+    const YEAR: usize = 2023;
     const DAY: usize = 22;
     const TITLE: &'static str = "sand slabs";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.disintegratable())
+        Ok(self.disintegratable() as u64)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        Ok(self.remove())
+        Ok(self.remove_dominators() as u64)
+    }
+
+    fn describe(&self) -> Description {
+        let ends = || self.slabs.iter().flat_map(|s| [s.a, s.b]);
+        Description::default()
+            .with("slabs", self.slabs.len())
+            .range_of("x", ends().map(|p| p.x))
+            .range_of("y", ends().map(|p| p.y))
+            .range_of("z", ends().map(|p| p.z))
+            .range_of(
+                "length",
+                self.slabs.iter().map(|s| {
+                    (s.b.x - s.a.x).abs() + (s.b.y - s.a.y).abs() + (s.b.z - s.a.z).abs() + 1
+                }),
+            )
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::VecDeque;
+
     use aoc_plumbing::Solution;
+    use rayon::prelude::*;
 
     use super::*;
 
+    impl SandSlabs {
+        /// Reference implementation that simulates the chain reaction for
+        /// every critical slab. Used to validate `remove_dominators`.
+        fn remove(&self) -> usize {
+            self.cant_remove
+                .par_iter()
+                .map(|x| self.remove_one(*x))
+                .sum()
+        }
+
+        fn remove_one(&self, to_remove: usize) -> usize {
+            let mut supported_by = self.supported_by.clone();
+            let mut q = VecDeque::default();
+            let mut ret = 0;
+
+            q.push_back(to_remove);
+
+            while let Some(n) = q.pop_front() {
+                // for each node m with an edge e from n to m
+                for m in &self.supports[&n] {
+                    // remove edge e from the graph
+                    supported_by.entry(*m).and_modify(|x| {
+                        x.remove(&n);
+                    });
+
+                    // if m has no other incoming edges then insert m into q
+                    if supported_by[m].is_empty() {
+                        q.push_back(*m);
+
+                        // also track this brick as fallen
+                        ret += 1;
+                    }
+                }
+            }
+
+            ret
+        }
+    }
+
     #[test]
     #[ignore]
     fn full_dataset() {
@@ -217,4 +593,119 @@ mod tests {
         let solution = SandSlabs::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(5, 7));
     }
+
+    #[test]
+    fn settled_stack() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let mut instance = SandSlabs::instance(&input).unwrap();
+
+        assert_eq!(
+            instance.settled_z(),
+            vec![1..=1, 2..=2, 2..=2, 3..=3, 3..=3, 4..=4, 5..=6]
+        );
+        assert_eq!(instance.on_ground(), vec![0]);
+        assert_eq!(
+            instance.supports(),
+            vec![
+                vec![1, 2],
+                vec![3, 4],
+                vec![3, 4],
+                vec![5],
+                vec![5],
+                vec![6],
+                vec![]
+            ]
+        );
+        assert_eq!(
+            instance.supported_by(),
+            vec![
+                vec![],
+                vec![0],
+                vec![0],
+                vec![1, 2],
+                vec![1, 2],
+                vec![3, 4],
+                vec![5]
+            ]
+        );
+        // querying doesn't get in the way of solving
+        assert_eq!(instance.part_one().unwrap(), 5);
+        assert_eq!(instance.part_two().unwrap(), 7);
+    }
+
+    #[test]
+    fn parsing() {
+        let instance = SandSlabs::instance("1,0,1~1,2,1\r\n0,0,2~2,0,2\n\n").unwrap();
+        assert_eq!(instance.slabs.len(), 2);
+
+        let err = SandSlabs::instance("1,0,1~1,2\n").unwrap_err();
+        assert_eq!(err.to_string(), "expected ',' at byte 9, found '\\n'");
+        assert!(SandSlabs::instance("1,0,1~1,2,1 0,0,2~2,0,2").is_err());
+    }
+
+    #[test]
+    fn max_tree_2d() {
+        let mut tree = MaxTree2D::new(5, 4);
+        tree.raise(&(1..3), &(0..4), 2);
+        tree.raise(&(2..5), &(1..2), 5);
+        tree.raise(&(0..1), &(3..4), 1);
+
+        assert_eq!(tree.max(&(0..5), &(0..4)), 5);
+        assert_eq!(tree.max(&(0..2), &(0..4)), 2);
+        assert_eq!(tree.max(&(0..1), &(0..4)), 1);
+        assert_eq!(tree.max(&(0..1), &(0..3)), 0);
+        assert_eq!(tree.max(&(4..5), &(1..2)), 5);
+        assert_eq!(tree.max(&(3..5), &(2..4)), 0);
+        assert_eq!(tree.max(&(2..3), &(3..4)), 2);
+    }
+
+    fn check_sweepline(input: &str) {
+        let mut expected = SandSlabs::instance(input).unwrap();
+        expected.settle();
+        let mut instance = SandSlabs::instance(input).unwrap();
+        instance.settle_sweepline();
+
+        for (a, b) in expected.slabs.iter().zip(instance.slabs.iter()) {
+            assert_eq!((a.a, a.b), (b.a, b.b));
+        }
+        assert_eq!(instance.supports, expected.supports);
+        assert_eq!(instance.supported_by, expected.supported_by);
+        assert_eq!(instance.part_one().unwrap(), expected.part_one().unwrap());
+        assert_eq!(instance.part_two().unwrap(), expected.part_two().unwrap());
+    }
+
+    #[test]
+    fn sweepline_matches_heightmap() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        check_sweepline(&input);
+    }
+
+    #[test]
+    fn sweepline_far_apart() {
+        // the tree only covers the footprint boundaries, not the area between
+        check_sweepline(
+            "0,0,1~0,1,1\n\
+             1000000000,-1000000000,1~1000000000,-1000000000,3\n\
+             0,1,2~2,1,2\n\
+             1000000000,-1000000000,5~1000000002,-1000000000,5",
+        );
+        check_sweepline("");
+    }
+
+    #[test]
+    #[ignore]
+    fn sweepline_full_dataset() {
+        let input = std::fs::read_to_string("input.txt").expect("Unable to load input");
+        check_sweepline(&input);
+    }
+
+    #[test]
+    fn dominators_match_naive() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let mut instance = SandSlabs::instance(&input).unwrap();
+        instance.disintegratable();
+        assert_eq!(instance.remove_dominators(), instance.remove());
+    }
+
+    aoc_plumbing::parsing_never_panics!(SandSlabs);
 }
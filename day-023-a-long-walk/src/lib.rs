@@ -7,7 +7,7 @@ use aoc_common::{
     grid::{Coordinate, Grid},
 };
 use aoc_plumbing::Problem;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Tile {
@@ -59,6 +59,98 @@ fn visit(idx: usize, visited: u64) -> u64 {
 
 type Graph = Vec<Node>;
 
+/// The visited set is a `u64`, so that is as many vertices as we can handle.
+const MAX_VERTICES: usize = 64;
+
+/// Junctions in the maze never have more than four exits.
+const MAX_DEGREE: usize = 4;
+
+/// The contracted graph as fixed-size arrays, so that the search doesn't
+/// chase pointers through `Vec`s.
+#[derive(Debug, Clone)]
+struct Matrix {
+    /// `weights[u][v]` is the length of the edge between `u` and `v`, or zero
+    /// if there is none.
+    weights: [[u32; MAX_VERTICES]; MAX_VERTICES],
+    neighbours: [[u8; MAX_DEGREE]; MAX_VERTICES],
+    degree: [u8; MAX_VERTICES],
+}
+
+impl Matrix {
+    fn from_graph(graph: &Graph) -> Option<Box<Self>> {
+        if graph.len() > MAX_VERTICES {
+            return None;
+        }
+
+        let mut matrix = Box::new(Self {
+            weights: [[0; MAX_VERTICES]; MAX_VERTICES],
+            neighbours: [[0; MAX_DEGREE]; MAX_VERTICES],
+            degree: [0; MAX_VERTICES],
+        });
+
+        // the neighbour lists contain every edge twice, so dedupe them here
+        for node in graph {
+            for &(v, cost) in &node.neighbours {
+                let cost = u32::try_from(cost).ok()?;
+                let u = node.idx;
+
+                if matrix.weights[u][v] == 0 {
+                    let d = matrix.degree[u] as usize;
+                    if d == MAX_DEGREE {
+                        return None;
+                    }
+
+                    matrix.neighbours[u][d] = v as u8;
+                    matrix.degree[u] += 1;
+                }
+
+                matrix.weights[u][v] = matrix.weights[u][v].max(cost);
+            }
+        }
+
+        Some(matrix)
+    }
+
+    fn longest_path(&self, start: usize, end: usize, visited: u64) -> Option<u32> {
+        if start == end {
+            return Some(0);
+        }
+
+        let visited = visit(start, visited);
+        let mut best = None;
+
+        for &v in &self.neighbours[start][..self.degree[start] as usize] {
+            let v = v as usize;
+            if is_visited(v, visited) {
+                continue;
+            }
+
+            if let Some(x) = self.longest_path(v, end, visited) {
+                let total = x + self.weights[start][v];
+                best = Some(best.map_or(total, |b: u32| b.max(total)));
+            }
+        }
+
+        best
+    }
+}
+
+/// A longest walk from the start to the end, as the junctions it passes
+/// through and the lengths of the corridors between them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LongestWalk {
+    pub length: usize,
+    /// The junctions along the walk, starting at the start and finishing at
+    /// the end.
+    pub junctions: Vec<Coordinate>,
+    /// `segments[i]` is the length of the corridor from `junctions[i]` to
+    /// `junctions[i + 1]`.
+    pub segments: Vec<usize>,
+    /// Whether this is the only walk of this length. If not, this is the
+    /// first one found.
+    pub unique: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct ALongWalk {
     grid: Grid<Tile>,
@@ -159,6 +251,186 @@ impl ALongWalk {
             .max()
     }
 
+    /// An experimental alternative to `longest_path_flat` that searches an
+    /// adjacency matrix of the contracted graph. Returns `None` if there is no
+    /// path, or if the graph is too large to fit in the matrix.
+    pub fn longest_path_matrix(&self, start_depth: usize) -> Option<usize> {
+        let graph = self.build_graph();
+        let matrix = Matrix::from_graph(&graph)?;
+        if matrix.degree[1] == 0 {
+            // nothing leads to the end
+            return None;
+        }
+        let penultimate = matrix.neighbours[1][0] as usize;
+        let last_cost = matrix.weights[1][penultimate];
+
+        let mut cur = vec![(0usize, 0u32, 0u64)];
+        let mut next = Vec::default();
+
+        for _ in 0..start_depth {
+            for (u, cost, visited) in cur.drain(..) {
+                if u == penultimate {
+                    next.push((u, cost, visited));
+                    continue;
+                }
+
+                let visited = visit(u, visited);
+                for &v in &matrix.neighbours[u][..matrix.degree[u] as usize] {
+                    let v = v as usize;
+                    if !is_visited(v, visited) {
+                        next.push((v, cost + matrix.weights[u][v], visited));
+                    }
+                }
+            }
+
+            std::mem::swap(&mut cur, &mut next);
+        }
+
+        cur.into_par_iter()
+            .filter_map(|(u, cost, visited)| {
+                matrix
+                    .longest_path(u, penultimate, visited)
+                    .map(|x| (x + cost + last_cost) as usize)
+            })
+            .max()
+    }
+
+    /// Finds the longest path (ignoring slopes) through the junctions,
+    /// returning its length and the coordinates of the junctions along it,
+    /// from the start to the end. This is a plain sequential search, so it is
+    /// a fair bit slower than part two.
+    pub fn longest_path_junctions(&self) -> Option<(usize, Vec<Coordinate>)> {
+        self.longest_walk(false)
+            .map(|walk| (walk.length, walk.junctions))
+    }
+
+    /// Finds a longest walk from the start to the end, only going down
+    /// slopes if `slopes` is set (part one) or treating them as paths
+    /// otherwise (part two), and whether any other walk is as long. Walks
+    /// are told apart by the tiles they visit, so two corridors of the same
+    /// length between the same junctions give two walks. This is a plain
+    /// sequential search, so it is slower than solving either part.
+    pub fn longest_walk(&self, slopes: bool) -> Option<LongestWalk> {
+        let graph = self.build_corridors(slopes);
+        let mut search = WalkSearch {
+            graph: &graph,
+            visited: vec![false; graph.len()],
+            path: vec![(0, 0)],
+            best: None,
+            ties: 0,
+        };
+        search.visited[0] = true;
+        search.search(0, 0);
+
+        let ties = search.ties;
+        search.best.map(|(length, path)| LongestWalk {
+            length,
+            junctions: path.iter().map(|&(u, _)| graph[u].coord).collect(),
+            segments: path[1..].iter().map(|&(_, cost)| cost).collect(),
+            unique: ties == 0,
+        })
+    }
+
+    /// Builds the junction graph by walking every corridor out of every
+    /// junction, so that each corridor appears once per direction it can be
+    /// walked in. With `slopes`, a slope can only be left downhill, which
+    /// makes some corridors one way.
+    fn build_corridors(&self, slopes: bool) -> Graph {
+        let mut graph = self.find_vertices();
+        let coords_to_ids = FxHashMap::from_iter(graph.iter().map(|x| (x.coord, x.idx)));
+
+        let is_open = |c: Coordinate| self.grid.is_in_bounds(c) && self.grid[c] != Tile::Wall;
+        // where we're allowed to go from `coord`, having come from `prev`
+        let exits = |coord: Coordinate, prev: Option<Coordinate>| -> Vec<Coordinate> {
+            match self.grid[coord] {
+                Tile::Slope(d) if slopes => vec![coord.neighbour(&d)],
+                _ => coord.cardinal_neighbours().to_vec(),
+            }
+            .into_iter()
+            .filter(|&n| is_open(n) && Some(n) != prev)
+            .collect()
+        };
+
+        for node in graph.iter_mut() {
+            let start = node.coord;
+            for first in exits(start, None) {
+                let (mut prev, mut cur, mut dist) = (start, first, 1);
+                loop {
+                    if let Some(&v) = coords_to_ids.get(&cur) {
+                        node.neighbours.push((v, dist));
+                        break;
+                    }
+
+                    // anything that isn't a junction has at most one way on
+                    match exits(cur, Some(prev))[..] {
+                        [next] => {
+                            (prev, cur) = (cur, next);
+                            dist += 1;
+                        }
+                        _ => break,
+                    }
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Renders the junction graph in Graphviz DOT format. Each junction is
+    /// labeled with its coordinate and each edge with the length of the
+    /// corridor between them. The start and end are highlighted, and if
+    /// `path` (a sequence of junction coordinates, as returned by
+    /// `longest_path_junctions`) is given, its edges are coloured.
+    pub fn to_dot(&self, path: Option<&[Coordinate]>) -> String {
+        let graph = self.build_graph();
+        let on_path: FxHashSet<(Coordinate, Coordinate)> = path
+            .unwrap_or_default()
+            .windows(2)
+            .flat_map(|w| [(w[0], w[1]), (w[1], w[0])])
+            .collect();
+
+        let mut out = String::from("graph junctions {\n");
+        for node in &graph {
+            let style = match node.idx {
+                0 => ", shape=doublecircle, style=filled, fillcolor=palegreen",
+                1 => ", shape=doublecircle, style=filled, fillcolor=lightcoral",
+                _ => "",
+            };
+            out.push_str(&format!(
+                "    n{} [label=\"{:?}\"{}];\n",
+                node.idx, node.coord, style
+            ));
+        }
+
+        // the neighbour lists have every edge more than once
+        let mut edges: Vec<(usize, usize, usize)> = graph
+            .iter()
+            .flat_map(|node| {
+                node.neighbours
+                    .iter()
+                    .filter(move |(v, _)| node.idx < *v)
+                    .map(move |&(v, cost)| (node.idx, v, cost))
+            })
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+
+        for (u, v, cost) in edges {
+            let style = if on_path.contains(&(graph[u].coord, graph[v].coord)) {
+                ", color=red, penwidth=3"
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "    n{} -- n{} [label=\"{}\"{}];\n",
+                u, v, cost, style
+            ));
+        }
+        out.push_str("}\n");
+
+        out
+    }
+
     fn longest_path_flat_helper(
         start: usize,
         end: usize,
@@ -222,23 +494,62 @@ impl ALongWalk {
     }
 }
 
+/// The state of the search in `ALongWalk::longest_walk`.
+struct WalkSearch<'a> {
+    graph: &'a Graph,
+    visited: Vec<bool>,
+    /// The junctions on the current walk, with the length of the corridor
+    /// leading to each.
+    path: Vec<(usize, usize)>,
+    best: Option<(usize, Vec<(usize, usize)>)>,
+    /// How many other walks are as long as `best`.
+    ties: usize,
+}
+
+impl WalkSearch<'_> {
+    fn search(&mut self, u: usize, len: usize) {
+        if u == 1 {
+            match &self.best {
+                Some((b, _)) if len < *b => (),
+                Some((b, _)) if len == *b => self.ties += 1,
+                _ => {
+                    self.best = Some((len, self.path.clone()));
+                    self.ties = 0;
+                }
+            }
+            return;
+        }
+
+        for &(v, cost) in &self.graph[u].neighbours {
+            if !self.visited[v] {
+                self.visited[v] = true;
+                self.path.push((v, cost));
+                self.search(v, len + cost);
+                self.path.pop();
+                self.visited[v] = false;
+            }
+        }
+    }
+}
+
 impl FromStr for ALongWalk {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let grid = Grid::from_str(s)?;
+        let grid = Grid::parse(s)?;
         Ok(Self { grid })
     }
 }
 
 impl Problem for ALongWalk {
+    const YEAR: usize = 2023;
     const DAY: usize = 23;
     const TITLE: &'static str = "a long walk";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = usize;
+    type P1 = u64;
+    type P2 = u64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
         self.longest_path(
@@ -246,20 +557,21 @@ impl Problem for ALongWalk {
             (self.grid.n - 1, self.grid.m - 2).into(),
             &mut Grid::new(self.grid.n, self.grid.m, false),
         )
+        .map(|x| x as u64)
         .ok_or_else(|| anyhow!("no path found"))
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
         self.longest_path_flat(10)
+            .map(|x| x as u64)
             .ok_or_else(|| anyhow!("no path found"))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::Solution;
 
     #[test]
     #[ignore]
@@ -267,6 +579,14 @@ mod tests {
         let input = std::fs::read_to_string("input.txt").expect("Unable to load input");
         let solution = ALongWalk::solve(&input).unwrap();
         assert_eq!(solution, Solution::new(2250, 6470));
+
+        let instance = ALongWalk::instance(&input).unwrap();
+        assert_eq!(instance.longest_path_matrix(10), Some(6470));
+
+        let walk = instance.longest_walk(true).unwrap();
+        assert_eq!(walk.length, 2250);
+        assert_eq!(walk.segments.iter().sum::<usize>(), 2250);
+        assert_eq!(instance.longest_walk(false).map(|w| w.length), Some(6470));
     }
 
     #[test]
@@ -276,4 +596,105 @@ mod tests {
         assert_eq!(instance.part_one().unwrap(), 94);
         assert_eq!(instance.longest_path_flat(3).unwrap(), 154);
     }
+
+    #[test]
+    fn junction_path_and_dot() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ALongWalk::instance(&input).unwrap();
+        let (len, path) = instance.longest_path_junctions().unwrap();
+        assert_eq!(len, 154);
+        assert_eq!(path.first(), Some(&Coordinate::new(0, 1)));
+        assert_eq!(path.last(), Some(&Coordinate::new(22, 21)));
+        assert_eq!(path.len(), 9);
+
+        let dot = instance.to_dot(None);
+        assert!(dot.starts_with("graph junctions {\n"));
+        assert!(dot.contains("    n0 [label=\"(0, 1)\", shape=doublecircle"));
+        assert!(dot.contains("    n1 [label=\"(22, 21)\", shape=doublecircle"));
+        assert_eq!(dot.matches(" [label=").count(), 9 + 12);
+        assert!(!dot.contains("color=red"));
+
+        let dot = instance.to_dot(Some(&path));
+        assert_eq!(dot.matches("color=red").count(), path.len() - 1);
+    }
+
+    #[test]
+    fn longest_walks() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ALongWalk::instance(&input).unwrap();
+
+        for (slopes, length) in [(true, 94), (false, 154)] {
+            let walk = instance.longest_walk(slopes).unwrap();
+            assert_eq!(walk.length, length);
+            assert_eq!(walk.segments.iter().sum::<usize>(), length);
+            assert_eq!(walk.segments.len() + 1, walk.junctions.len());
+            assert_eq!(walk.junctions.first(), Some(&Coordinate::new(0, 1)));
+            assert_eq!(walk.junctions.last(), Some(&Coordinate::new(22, 21)));
+            assert!(walk.unique);
+        }
+
+        // the same length either way round the middle block
+        let input = "\
+#.#####
+#.....#
+#.###.#
+#.....#
+#####.#";
+        let instance = ALongWalk::instance(input).unwrap();
+        let walk = instance.longest_walk(false).unwrap();
+        assert_eq!(walk.length, 8);
+        assert_eq!(walk.segments, vec![1, 6, 1]);
+        assert!(!walk.unique);
+
+        // but the slope only allows one of them
+        let input = "\
+#.#####
+#.....#
+#.###.#
+#.<...#
+#####.#";
+        let instance = ALongWalk::instance(input).unwrap();
+        assert!(!instance.longest_walk(false).unwrap().unique);
+        let walk = instance.longest_walk(true).unwrap();
+        assert_eq!(walk.length, 8);
+        assert_eq!(
+            walk.junctions,
+            vec![
+                Coordinate::new(0, 1),
+                Coordinate::new(1, 1),
+                Coordinate::new(3, 5),
+                Coordinate::new(4, 5)
+            ]
+        );
+        assert!(walk.unique);
+
+        // and this one leads nowhere
+        let input = "\
+#.#####
+#.....#
+#.#####
+#<....#
+#####.#";
+        let instance = ALongWalk::instance(input).unwrap();
+        assert_eq!(instance.longest_walk(true), None);
+        assert_eq!(instance.longest_walk(false).unwrap().length, 8);
+    }
+
+    #[test]
+    fn matrix_matches_flat() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = ALongWalk::instance(&input).unwrap();
+        for depth in 0..6 {
+            assert_eq!(instance.longest_path_matrix(depth), Some(154));
+        }
+
+        // the end junction has no corridors at all
+        let instance = ALongWalk::instance("#.###\n#...#\n#####\n###.#").unwrap();
+        assert_eq!(instance.longest_walk(false), None);
+        for depth in 0..4 {
+            assert_eq!(instance.longest_path_matrix(depth), None);
+        }
+    }
+
+    aoc_plumbing::parsing_never_panics!(ALongWalk);
 }
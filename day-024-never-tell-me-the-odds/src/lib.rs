@@ -1,80 +1,95 @@
 use std::str::FromStr;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use aoc_common::{
     algebra::{Point3, Ray, Vector3},
-    geometry::IntersectRay,
+    geometry::{line_intersection, Plane},
 };
-use aoc_plumbing::Problem;
-use nalgebra::{Matrix6, Vector6};
+use aoc_plumbing::{scan::Scanner, Description, Problem};
+
+/// The test area for part one, on both the X and Y axes.
+pub const DEFAULT_BOUNDS: (f64, f64) = (200000000000000.0, 400000000000000.0);
 
 #[derive(Debug, Clone)]
 pub struct NeverTellMeTheOdds {
     rays: Vec<Ray<i64>>,
+    bounds: (f64, f64),
 }
 
 impl NeverTellMeTheOdds {
-    fn determine_rock(&self) -> Result<i64> {
-        let (p1, v1) = (self.rays[0].origin, self.rays[0].dir);
-        let (p2, v2) = (self.rays[1].origin, self.rays[1].dir);
-        let (p3, v3) = (self.rays[2].origin, self.rays[2].dir);
-
-        let a = Matrix6::new(
-            0.0,
-            (v2.z - v1.z) as f64,
-            (v1.y - v2.y) as f64,
-            0.0,
-            (p1.z - p2.z) as f64,
-            (p2.y - p1.y) as f64,
-            0.0,
-            (v3.z - v1.z) as f64,
-            (v1.y - v3.y) as f64,
-            0.0,
-            (p1.z - p3.z) as f64,
-            (p3.y - p1.y) as f64,
-            (v1.z - v2.z) as f64,
-            0.0,
-            (v2.x - v1.x) as f64,
-            (p2.z - p1.z) as f64,
-            0.0,
-            (p1.x - p2.x) as f64,
-            (v1.z - v3.z) as f64,
-            0.0,
-            (v3.x - v1.x) as f64,
-            (p3.z - p1.z) as f64,
-            0.0,
-            (p1.x - p3.x) as f64,
-            (v2.y - v1.y) as f64,
-            (v1.x - v2.x) as f64,
-            0.0,
-            (p1.y - p2.y) as f64,
-            (p2.x - p1.x) as f64,
-            0.0,
-            (v3.y - v1.y) as f64,
-            (v1.x - v3.x) as f64,
-            0.0,
-            (p1.y - p3.y) as f64,
-            (p3.x - p1.x) as f64,
-            0.0,
-        );
+    /// Replaces the test area part one counts crossings in, which is
+    /// [DEFAULT_BOUNDS] unless set. The example uses `7.0..=27.0`.
+    pub fn with_bounds(mut self, min: f64, max: f64) -> Self {
+        self.bounds = (min, max);
+        self
+    }
 
-        let b = Vector6::new(
-            (p1.z * v1.y + p2.y * v2.z - p1.y * v1.z - p2.z * v2.y) as f64,
-            (p1.z * v1.y + p3.y * v3.z - p1.y * v1.z - p3.z * v3.y) as f64,
-            (p1.x * v1.z + p2.z * v2.x - p1.z * v1.x - p2.x * v2.z) as f64,
-            (p1.x * v1.z + p3.z * v3.x - p1.z * v1.x - p3.x * v3.z) as f64,
-            (p1.y * v1.x + p2.x * v2.y - p1.x * v1.y - p2.y * v2.x) as f64,
-            (p1.y * v1.x + p3.x * v3.y - p1.x * v1.y - p3.y * v3.x) as f64,
-        );
+    /// Returns the starting position and velocity of a rock that hits every
+    /// hailstone, or an error naming the first hailstone it would miss.
+    ///
+    /// Works in the reference frame of the first hailstone, in which the rock
+    /// must pass through the origin. The rock's line therefore lies in both the
+    /// plane containing the origin and the second hailstone's line, and the
+    /// plane containing the origin and the third hailstone's line. Crossing
+    /// each of those two hailstones with the other's plane yields two points
+    /// (and times) on the rock's path, which is enough to recover it.
+    pub fn solve_rock(&self) -> Result<(Point3<i64>, Vector3<i64>)> {
+        if self.rays.len() < 3 {
+            bail!("need at least three hailstones");
+        }
+
+        let h1 = widen(&self.rays[0]);
+        let relative = |ray: &Ray<i64>| {
+            let h = widen(ray);
+            Ray::new(Point3::origin() + (h.origin - h1.origin), h.dir - h1.dir)
+        };
+        let h2 = relative(&self.rays[1]);
+        let h3 = relative(&self.rays[2]);
+
+        let p2 = Plane::from_ray_and_point(&h2, Point3::origin());
+        let p3 = Plane::from_ray_and_point(&h3, Point3::origin());
+        if p2.is_degenerate() || p3.is_degenerate() {
+            bail!("hailstone passes through the reference hailstone");
+        }
+
+        let t2 = p3
+            .intersect_time(&h2)
+            .ok_or_else(|| anyhow!("hailstone parallel to plane"))?;
+        let t3 = p2
+            .intersect_time(&h3)
+            .ok_or_else(|| anyhow!("hailstone parallel to plane"))?;
+        if t2 == t3 {
+            bail!("rock collides with two hailstones at the same time");
+        }
+
+        let q2 = h2.origin + h2.dir * t2;
+        let q3 = h3.origin + h3.dir * t3;
+        let v = (q3 - q2) / (t3 - t2);
+        let p = h1.origin + ((q2 - v * t2) - Point3::origin());
+        let v = v + h1.dir;
+
+        for (idx, ray) in self.rays.iter().enumerate() {
+            collision_time(&widen(ray), p, v)
+                .ok_or_else(|| anyhow!("rock misses hailstone {}", idx))?;
+        }
+
+        let narrow = |x: i128| i64::try_from(x).map_err(|_| anyhow!("rock overflows i64"));
+        Ok((
+            Point3::new(narrow(p.x)?, narrow(p.y)?, narrow(p.z)?),
+            Vector3::new(narrow(v.x)?, narrow(v.y)?, narrow(v.z)?),
+        ))
+    }
 
-        let x = a
-            .try_inverse()
-            .ok_or_else(|| anyhow!("matrix not invertible"))?
-            * b;
-        Ok(x[0].round() as i64 + x[1].round() as i64 + x[2].round() as i64)
+    fn determine_rock(&self) -> Result<i64> {
+        let (p, _) = self.solve_rock()?;
+        p.x.checked_add(p.y)
+            .and_then(|s| s.checked_add(p.z))
+            .ok_or_else(|| anyhow!("rock position overflows"))
     }
 
-    fn intersections_2d(&self, min: f64, max: f64) -> usize {
+    /// Counts the pairs of hailstones whose paths cross (ignoring Z) in the
+    /// future of both, within `min..=max` on both the X and Y axes.
+    pub fn intersections_2d(&self, min: f64, max: f64) -> usize {
         let mut ret = 0;
 
         for i in 0..self.rays.len() {
@@ -90,9 +105,16 @@ impl NeverTellMeTheOdds {
                     Vector3::new(d2.x as f64, d2.y as f64, 0.0),
                 );
 
-                if let Some(s) = a.intersect(&b) {
-                    let p = s.position;
-                    if p.x >= min && p.x <= max && p.y >= min && p.y <= max {
+                // only crossings in the future of both hailstones count
+                if let Some(x) = line_intersection(&a, &b) {
+                    let p = x.position;
+                    if x.t >= 0.0
+                        && x.s >= 0.0
+                        && p.x >= min
+                        && p.x <= max
+                        && p.y >= min
+                        && p.y <= max
+                    {
                         ret += 1
                     }
                 }
@@ -103,59 +125,129 @@ impl NeverTellMeTheOdds {
     }
 }
 
+/// Returns the non-negative integer time at which a rock thrown from `p`
+/// with velocity `v` hits the hailstone, if it does.
+fn collision_time(hail: &Ray<i128>, p: Point3<i128>, v: Vector3<i128>) -> Option<i128> {
+    // p + v * t = o + d * t, so (p - o) = (d - v) * t on every axis
+    let offset = p - hail.origin;
+    let closing = hail.dir - v;
+    let mut time = None;
+
+    for (num, den) in [
+        (offset.x, closing.x),
+        (offset.y, closing.y),
+        (offset.z, closing.z),
+    ] {
+        if den == 0 {
+            if num != 0 {
+                return None;
+            }
+            continue;
+        }
+
+        if num % den != 0 {
+            return None;
+        }
+
+        let t = num / den;
+        if t < 0 || time.is_some_and(|x| x != t) {
+            return None;
+        }
+        time = Some(t);
+    }
+
+    // a rock on the same path as the hailstone collides immediately
+    Some(time.unwrap_or_default())
+}
+
+fn widen(ray: &Ray<i64>) -> Ray<i128> {
+    let (o, d) = (ray.origin, ray.dir);
+    Ray::new(
+        Point3::new(o.x.into(), o.y.into(), o.z.into()),
+        Vector3::new(d.x.into(), d.y.into(), d.z.into()),
+    )
+}
+
+/// Reads three comma separated integers, allowing spaces around them.
+fn scan_triple(s: &mut Scanner) -> Result<(i64, i64, i64)> {
+    s.skip_spaces();
+    let x = s.int()?;
+    s.skip_spaces();
+    s.expect(b',')?;
+    s.skip_spaces();
+    let y = s.int()?;
+    s.skip_spaces();
+    s.expect(b',')?;
+    s.skip_spaces();
+    let z = s.int()?;
+    s.skip_spaces();
+    Ok((x, y, z))
+}
+
 impl FromStr for NeverTellMeTheOdds {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut rays = Vec::default();
-        for line in s.lines() {
-            if let Some((left, right)) = line.split_once('@') {
-                let p: Vec<i64> = left
-                    .trim()
-                    .split(',')
-                    .map(|x| x.trim().parse())
-                    .collect::<Result<Vec<_>, _>>()?;
-
-                let v: Vec<i64> = right
-                    .trim()
-                    .split(',')
-                    .map(|x| x.trim().parse())
-                    .collect::<Result<Vec<_>, _>>()?;
-
-                rays.push(Ray::new(
-                    Point3::new(p[0], p[1], p[2]),
-                    Vector3::new(v[0], v[1], v[2]),
-                ));
+        let mut s = Scanner::new(s);
+        s.skip_whitespace();
+        while !s.is_empty() {
+            let (px, py, pz) = scan_triple(&mut s)?;
+            s.expect(b'@')?;
+            let (vx, vy, vz) = scan_triple(&mut s)?;
+            s.eat(b'\r');
+            if !s.is_empty() {
+                s.expect(b'\n')?;
             }
+            s.skip_whitespace();
+
+            rays.push(Ray::new(Point3::new(px, py, pz), Vector3::new(vx, vy, vz)));
         }
 
-        Ok(Self { rays })
+        Ok(Self {
+            rays,
+            bounds: DEFAULT_BOUNDS,
+        })
     }
 }
 
 impl Problem for NeverTellMeTheOdds {
+    const YEAR: usize = 2023;
     const DAY: usize = 24;
     const TITLE: &'static str = "never tell me the odds";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
+    type P1 = u64;
     type P2 = i64;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
-        Ok(self.intersections_2d(200000000000000.0, 400000000000000.0))
+        let (min, max) = self.bounds;
+        Ok(self.intersections_2d(min, max) as u64)
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
         self.determine_rock()
     }
+
+    fn describe(&self) -> Description {
+        let origins = || self.rays.iter().map(|r| r.origin);
+        let dirs = || self.rays.iter().map(|r| r.dir);
+        Description::default()
+            .with("hailstones", self.rays.len())
+            .range_of("px", origins().map(|p| p.x))
+            .range_of("py", origins().map(|p| p.y))
+            .range_of("pz", origins().map(|p| p.z))
+            .range_of("vx", dirs().map(|v| v.x))
+            .range_of("vy", dirs().map(|v| v.y))
+            .range_of("vz", dirs().map(|v| v.z))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::Solution;
 
     #[test]
     #[ignore]
@@ -168,8 +260,48 @@ mod tests {
     #[test]
     fn example() {
         let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let mut instance = NeverTellMeTheOdds::instance(&input)
+            .unwrap()
+            .with_bounds(7.0, 27.0);
+        assert_eq!(instance.part_one().unwrap(), 2);
+        assert_eq!(instance.part_two().unwrap(), 47);
+
+        // none of the example's crossings are in the default test area, but
+        // all five future crossings are within -100..=100
         let mut instance = NeverTellMeTheOdds::instance(&input).unwrap();
-        assert_eq!(instance.intersections_2d(7.0, 27.0), 2);
-        assert_eq!(instance.part_two().unwrap(), 47)
+        assert_eq!(instance.part_one().unwrap(), 0);
+        assert_eq!(instance.intersections_2d(7.0, 12.0), 0);
+        assert_eq!(instance.intersections_2d(-100.0, 100.0), 5);
     }
+
+    #[test]
+    fn parsing() {
+        let instance = NeverTellMeTheOdds::instance("19, 13, 30 @ -2,  1, -2\r\n\n").unwrap();
+        assert_eq!(instance.rays[0].origin, Point3::new(19, 13, 30));
+        assert_eq!(instance.rays[0].dir, Vector3::new(-2, 1, -2));
+
+        let err = NeverTellMeTheOdds::instance("19, 13, 30 -2, 1, -2").unwrap_err();
+        assert_eq!(err.to_string(), "expected '@' at byte 11, found '-'");
+    }
+
+    #[test]
+    fn solve_rock() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = NeverTellMeTheOdds::instance(&input).unwrap();
+        assert_eq!(
+            instance.solve_rock().unwrap(),
+            (Point3::new(24, 13, 10), Vector3::new(-3, 1, 2))
+        );
+
+        // the first three hailstones still determine a rock, but it can't
+        // reach the extra one
+        let input = format!("{}\n1, 1, 1 @ 0, 0, 0", input.trim_end());
+        let instance = NeverTellMeTheOdds::instance(&input).unwrap();
+        assert_eq!(
+            instance.solve_rock().unwrap_err().to_string(),
+            "rock misses hailstone 5"
+        );
+    }
+
+    aoc_plumbing::parsing_never_panics!(NeverTellMeTheOdds);
 }
@@ -1,23 +1,40 @@
-use std::{collections::VecDeque, str::FromStr};
+use std::{
+    collections::{BinaryHeap, VecDeque},
+    str::FromStr,
+};
 
 use anyhow::{anyhow, Result};
-use aoc_plumbing::Problem;
-use rand::{seq::SliceRandom, thread_rng};
+use aoc_common::{graph::UnGraph, union_find::UnionFind};
+use aoc_plumbing::{Description, OptionalAnswer, Problem};
+use rand::{rngs::StdRng, seq::SliceRandom, thread_rng, Rng, SeedableRng};
 use rustc_hash::{FxHashMap, FxHashSet};
 
-type Graph = FxHashMap<u16, Vec<u16>>;
+type Graph = UnGraph<String>;
+
+// each attempt has around a 50% chance of success, so if we haven't found the
+// cut after this many, the cut is very likely not 3 and we should fall back
+const MAX_ATTEMPTS: usize = 32;
+
+/// A cut splitting the graph in two.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut {
+    /// The edges in the cut, each ordered by label, sorted.
+    pub edges: Vec<(String, String)>,
+    /// The sorted labels of the vertices in each of the two groups.
+    pub components: (Vec<String>, Vec<String>),
+}
 
 #[derive(Debug, Clone)]
 pub struct Snowverload {
     graph: Graph,
-    vertices: Vec<u16>,
+    vertices: Vec<usize>,
 }
 
 impl Snowverload {
     fn min_cut(&self) -> Option<usize> {
         let mut rng = thread_rng();
 
-        loop {
+        for _ in 0..MAX_ATTEMPTS {
             // randomly choose source and sink until we find a pair where the max flow between
             // them is 3. the groups are reasonably evenly distributed so that we have around 50%
             // chance of choosing a correct pair
@@ -28,30 +45,282 @@ impl Snowverload {
                 return Some(result);
             }
         }
+
+        self.min_cut_exact().map(|(_, product)| product)
+    }
+
+    /// Deterministically finds the global minimum cut with Stoer-Wagner,
+    /// returning the number of edges in the cut and the product of the sizes
+    /// of the two resulting groups. Unlike the randomized approach used for
+    /// part one, this works for any cut size.
+    pub fn min_cut_exact(&self) -> Option<(usize, usize)> {
+        let (weight, side) = self.stoer_wagner()?;
+        Some((weight, side.len() * (self.vertices.len() - side.len())))
+    }
+
+    /// Returns the edges of the minimum cut and the two groups of vertices it
+    /// separates.
+    pub fn cut(&self) -> Option<Cut> {
+        let (_, side) = self.stoer_wagner()?;
+        let side: FxHashSet<usize> = side.into_iter().collect();
+
+        let mut edges = Vec::default();
+        for &v in &side {
+            for u in self.graph.neighbours(v) {
+                if !side.contains(u) {
+                    let (a, b) = (self.graph.label(v).clone(), self.graph.label(*u).clone());
+                    edges.push(if a < b { (a, b) } else { (b, a) });
+                }
+            }
+        }
+        edges.sort();
+
+        let mut left = Vec::default();
+        let mut right = Vec::default();
+        for v in &self.vertices {
+            if side.contains(v) {
+                left.push(self.graph.label(*v).clone());
+            } else {
+                right.push(self.graph.label(*v).clone());
+            }
+        }
+        left.sort();
+        right.sort();
+
+        Some(Cut {
+            edges,
+            components: (left, right),
+        })
+    }
+
+    /// Finds a cut of three edges Girvan-Newman style: approximate the edge
+    /// betweenness from `samples` BFS trees rooted at random vertices, remove
+    /// the most central edge, and repeat until three edges are gone. Returns
+    /// the product of the sizes of the two groups, or `None` if removing
+    /// those edges didn't split the graph in two.
+    ///
+    /// The roots are drawn from an RNG seeded with `seed`, so the result is
+    /// deterministic. When the groups are of similar size, 10 or 20 samples
+    /// is plenty, as every root on the other side sends half of its tree
+    /// through the cut.
+    pub fn min_cut_betweenness(&self, samples: usize, seed: u64) -> Option<usize> {
+        let n = self.graph.len();
+        if n < 2 {
+            return None;
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut removed: FxHashSet<(usize, usize)> = FxHashSet::default();
+        let mut centrality: FxHashMap<(usize, usize), usize> = FxHashMap::default();
+        let mut order = Vec::with_capacity(n);
+        let mut parent = vec![usize::MAX; n];
+        let mut subtree = vec![0; n];
+
+        for _ in 0..3 {
+            centrality.clear();
+
+            for _ in 0..samples {
+                let root = rng.gen_range(0..n);
+                self.bfs_tree(root, &removed, &mut order, &mut parent);
+
+                // every vertex's path to the root crosses the tree edge above
+                // each of its ancestors, so an edge carries as many paths as
+                // there are vertices below it
+                for &v in order.iter() {
+                    subtree[v] = 1;
+                }
+                for &v in order.iter().skip(1).rev() {
+                    let p = parent[v];
+                    subtree[p] += subtree[v];
+                    *centrality.entry(edge(v, p)).or_default() += subtree[v];
+                }
+            }
+
+            let (&busiest, _) = centrality
+                .iter()
+                .max_by_key(|(&e, &count)| (count, std::cmp::Reverse(e)))?;
+            removed.insert(busiest);
+        }
+
+        self.bfs_tree(0, &removed, &mut order, &mut parent);
+        let count = order.len();
+        (count < n).then_some(count * (n - count))
+    }
+
+    /// Finds a minimum cut by random contraction, returning the number of
+    /// edges in the cut and the product of the sizes of the two groups.
+    ///
+    /// Each trial is one run of Karger-Stein: contract random edges until
+    /// about `n / sqrt(2)` vertices are left, twice independently, and
+    /// recurse on both, keeping the smaller cut. A single trial finds the
+    /// minimum cut with probability on the order of `1 / log n`, so the
+    /// smallest of several trials is very likely to be it. The RNG is seeded
+    /// with `seed`, so the result is deterministic.
+    pub fn min_cut_contraction(&self, trials: usize, seed: u64) -> Option<(usize, usize)> {
+        let n = self.graph.len();
+        if n < 2 {
+            return None;
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut edges: Vec<(usize, usize, usize)> = Vec::with_capacity(self.graph.edge_count());
+        for u in self.graph.vertices() {
+            edges.extend(
+                self.graph
+                    .neighbours(u)
+                    .iter()
+                    .filter(|&&v| u < v)
+                    .map(|&v| (u, v, 1)),
+            );
+        }
+        let graph = Multigraph {
+            sizes: vec![1; n],
+            edges,
+        };
+
+        (0..trials)
+            .map(|_| graph.karger_stein(&mut rng))
+            .min_by_key(|(cut, _)| *cut)
+            .map(|(cut, side)| (cut, side * (n - side)))
+    }
+
+    /// Fills `order` with the vertices reachable from `root` without crossing
+    /// a removed edge, in BFS order, and `parent` with each one's parent in
+    /// the BFS tree.
+    fn bfs_tree(
+        &self,
+        root: usize,
+        removed: &FxHashSet<(usize, usize)>,
+        order: &mut Vec<usize>,
+        parent: &mut [usize],
+    ) {
+        parent.fill(usize::MAX);
+        order.clear();
+        order.push(root);
+        parent[root] = root;
+
+        let mut head = 0;
+        while head < order.len() {
+            let u = order[head];
+            head += 1;
+
+            for &v in self.graph.neighbours(u) {
+                if parent[v] == usize::MAX && !removed.contains(&edge(u, v)) {
+                    parent[v] = u;
+                    order.push(v);
+                }
+            }
+        }
+    }
+
+    /// Returns the weight of the minimum cut and the vertices on one side of
+    /// it, or `None` if there are fewer than two vertices.
+    fn stoer_wagner(&self) -> Option<(usize, Vec<usize>)> {
+        let n = self.graph.len();
+        if n < 2 {
+            return None;
+        }
+
+        let mut adj: Vec<FxHashMap<usize, usize>> = vec![FxHashMap::default(); n];
+        for v in self.graph.vertices() {
+            for &u in self.graph.neighbours(v) {
+                *adj[v].entry(u).or_default() += 1;
+            }
+        }
+
+        // the original vertices that have been merged into each vertex
+        let mut groups: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+        let mut active: Vec<usize> = (0..n).collect();
+        let mut best: Option<(usize, Vec<usize>)> = None;
+
+        let mut in_a = vec![false; n];
+        let mut weights = vec![0; n];
+        let mut heap = BinaryHeap::default();
+
+        while active.len() > 1 {
+            // maximum adjacency ordering over the active vertices
+            heap.clear();
+            for &v in &active {
+                in_a[v] = false;
+                weights[v] = 0;
+                heap.push((0, v));
+            }
+
+            let (mut prev, mut last) = (active[0], active[0]);
+            while let Some((w, v)) = heap.pop() {
+                if in_a[v] || w != weights[v] {
+                    continue;
+                }
+
+                in_a[v] = true;
+                prev = last;
+                last = v;
+
+                for (&u, &c) in &adj[v] {
+                    if !in_a[u] {
+                        weights[u] += c;
+                        heap.push((weights[u], u));
+                    }
+                }
+            }
+
+            if best.as_ref().is_none_or(|(w, _)| weights[last] < *w) {
+                best = Some((weights[last], groups[last].clone()));
+            }
+
+            // merge last into prev
+            let edges = std::mem::take(&mut adj[last]);
+            for (u, c) in edges {
+                adj[u].remove(&last);
+                if u != prev {
+                    *adj[prev].entry(u).or_default() += c;
+                    *adj[u].entry(prev).or_default() += c;
+                }
+            }
+            let merged = std::mem::take(&mut groups[last]);
+            groups[prev].extend(merged);
+            active.retain(|&v| v != last);
+        }
+
+        best
     }
 
-    fn min_cut_helper(&self, source: u16, sink: u16) -> Option<usize> {
-        let mut pred = FxHashMap::default();
+    fn min_cut_helper(&self, source: usize, sink: usize) -> Option<usize> {
+        let n = self.graph.len();
+        // the vertex and edge each vertex was reached through
+        let mut pred: Vec<Option<(usize, usize)>> = vec![None; n];
         let mut q = VecDeque::default();
-        let mut visited_edges = FxHashSet::default();
+        // net flow along each edge, from its lower to its higher vertex. every edge has a
+        // capacity of 1 in each direction, so it has residual capacity from u to v as long as
+        // the net flow from u to v is < 1
+        let mut net_flow = vec![0_i8; self.graph.edge_count()];
+        let has_capacity = |net_flow: &[i8], u: usize, v: usize, e: usize| {
+            let flow = if u < v { net_flow[e] } else { -net_flow[e] };
+            flow < 1
+        };
         let mut flow = 0;
 
         // do bfs over and over again until we can't reach the sink anymore, or if we've exceeded a
         // flow of 3
         loop {
-            pred.clear();
+            pred.fill(None);
             q.clear();
             q.push_back(source);
 
             while let Some(u) = q.pop_front() {
-                if pred.contains_key(&sink) {
+                if pred[sink].is_some() {
                     flow += 1;
                     break;
                 }
 
-                for &v in &self.graph[&u] {
-                    if !pred.contains_key(&v) && v != source && !visited_edges.contains(&(u, v)) {
-                        pred.insert(v, u);
+                let edges = self
+                    .graph
+                    .neighbours(u)
+                    .iter()
+                    .zip(self.graph.incident_edges(u));
+                for (&v, &e) in edges {
+                    if pred[v].is_none() && v != source && has_capacity(&net_flow, u, v, e) {
+                        pred[v] = Some((u, e));
                         q.push_back(v)
                     }
                 }
@@ -64,15 +333,15 @@ impl Snowverload {
             }
 
             // sink is unreachable, don't search further
-            if !pred.contains_key(&sink) {
+            if pred[sink].is_none() {
                 break;
             }
 
-            // we know the flow is always 1, so we simplify updating the residual network to just
-            // insert visited edges
+            // we know the flow is always 1, so push a single unit along the path, which also
+            // frees up capacity in the reverse direction
             let mut v = sink;
-            while let Some(&u) = pred.get(&v) {
-                visited_edges.insert((u, v));
+            while let Some((u, e)) = pred[v] {
+                net_flow[e] += if u < v { 1 } else { -1 };
                 v = u;
             }
         }
@@ -83,103 +352,342 @@ impl Snowverload {
             return None;
         }
 
-        // now we just need to do bfs from the source once while avoiding the edges that have
-        // already reached capacity (visited_edges). since we've found the max flow, all the min
-        // cut edges should be saturated, which means our bfs will only reach 1 of the 2 islands.
-        let mut visited_vertices = FxHashSet::default();
+        // now we just need to do bfs from the source once over the residual network. since we've
+        // found the max flow, all the min cut edges should be saturated, which means our bfs will
+        // only reach 1 of the 2 islands.
+        let mut visited = vec![false; n];
         let mut q = VecDeque::default();
         q.push_back(source);
-        visited_vertices.insert(source);
+        visited[source] = true;
+        let mut count = 1;
 
         while let Some(u) = q.pop_front() {
-            for &v in &self.graph[&u] {
-                if !visited_vertices.contains(&v)
-                    && !visited_edges.contains(&(u, v))
-                    && !visited_edges.contains(&(v, u))
-                {
+            let edges = self
+                .graph
+                .neighbours(u)
+                .iter()
+                .zip(self.graph.incident_edges(u));
+            for (&v, &e) in edges {
+                if !visited[v] && has_capacity(&net_flow, u, v, e) {
                     q.push_back(v);
-                    visited_vertices.insert(v);
+                    visited[v] = true;
+                    count += 1;
                 }
             }
         }
 
-        let count = visited_vertices.len();
-        Some(count * (self.graph.len() - count))
+        Some(count * (n - count))
     }
 }
 
+/// A multigraph for random contraction, with parallel edges merged into
+/// one weighted edge so that heavily contracted graphs stay small.
+#[derive(Debug, Clone)]
+struct Multigraph {
+    /// The number of original vertices merged into each vertex.
+    sizes: Vec<usize>,
+    /// Edges `(u, v, count)` with `u < v`, with no two between the same
+    /// pair of vertices.
+    edges: Vec<(usize, usize, usize)>,
+}
+
+impl Multigraph {
+    /// Contracts uniformly random edges until at most `target` vertices are
+    /// left, or there is nothing left to contract.
+    ///
+    /// Contracting the parallel edges in a random order, skipping any that
+    /// became loops, picks each contraction uniformly from the remaining
+    /// edges. The first of `w` parallel edges to come up in a random order is
+    /// exponentially distributed with rate `w`, so each merged edge is given
+    /// a random key like that and the edges are contracted in key order.
+    fn contract<R: Rng>(&self, target: usize, rng: &mut R) -> Self {
+        let mut uf = UnionFind::new(self.sizes.len());
+        let mut order: Vec<(f64, usize, usize)> = self
+            .edges
+            .iter()
+            .map(|&(u, v, w)| (-(1.0 - rng.gen::<f64>()).ln() / w as f64, u, v))
+            .collect();
+        order.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+        for (_, u, v) in order {
+            if uf.sets() <= target {
+                break;
+            }
+            uf.union(u, v);
+        }
+
+        // renumber the merged vertices densely
+        let mut label = vec![usize::MAX; self.sizes.len()];
+        let mut sizes = Vec::with_capacity(uf.sets());
+        for (v, size) in self.sizes.iter().enumerate() {
+            let root = uf.find(v);
+            if label[root] == usize::MAX {
+                label[root] = sizes.len();
+                sizes.push(0);
+            }
+            sizes[label[root]] += size;
+        }
+
+        let mut edges: Vec<(usize, usize, usize)> = self
+            .edges
+            .iter()
+            .map(|&(u, v, w)| {
+                let (u, v) = edge(label[uf.find(u)], label[uf.find(v)]);
+                (u, v, w)
+            })
+            .filter(|(u, v, _)| u != v)
+            .collect();
+        edges.sort_unstable();
+        edges.dedup_by(|b, a| {
+            let parallel = (a.0, a.1) == (b.0, b.1);
+            if parallel {
+                a.2 += b.2;
+            }
+            parallel
+        });
+
+        Self { sizes, edges }
+    }
+
+    /// One run of Karger-Stein, returning the number of edges in the cut it
+    /// found and the size of one side.
+    fn karger_stein<R: Rng>(&self, rng: &mut R) -> (usize, usize) {
+        let n = self.sizes.len();
+        if n <= 6 {
+            // small enough to try every way of splitting it in two, keeping
+            // the last vertex on the same side every time
+            return (1..1_usize << (n - 1))
+                .map(|mask| {
+                    let side = |v: usize| mask & (1 << v) != 0;
+                    let cut = self
+                        .edges
+                        .iter()
+                        .filter(|(u, v, _)| side(*u) != side(*v))
+                        .map(|(_, _, w)| w)
+                        .sum();
+                    let size = (0..n).filter(|&v| side(v)).map(|v| self.sizes[v]).sum();
+                    (cut, size)
+                })
+                .min_by_key(|(cut, _)| *cut)
+                .unwrap_or((0, self.sizes[0]));
+        }
+
+        let target = (1.0 + n as f64 / std::f64::consts::SQRT_2).ceil() as usize;
+        (0..2)
+            .map(|_| {
+                let contracted = self.contract(target, rng);
+                if contracted.edges.is_empty() {
+                    // whatever is left isn't connected to anything else, so
+                    // contracting further would never shrink it
+                    (0, contracted.sizes[0])
+                } else {
+                    contracted.karger_stein(rng)
+                }
+            })
+            .min_by_key(|(cut, _)| *cut)
+            .expect("there are two branches")
+    }
+}
+
+/// An undirected edge, with the smaller index first.
+fn edge(u: usize, v: usize) -> (usize, usize) {
+    (u.min(v), u.max(v))
+}
+
 impl FromStr for Snowverload {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut graph: Graph = FxHashMap::default();
+        let mut graph = Graph::default();
 
         for line in s.lines() {
             if let Some((left, right)) = line.split_once(": ") {
-                let v = u16::from_str_radix(left, 36)?;
-
                 for token in right.split_whitespace() {
-                    let u = u16::from_str_radix(token, 36)?;
-
-                    graph
-                        .entry(v)
-                        .and_modify(|x| {
-                            x.push(u);
-                        })
-                        .or_insert(vec![u]);
-
-                    graph
-                        .entry(u)
-                        .and_modify(|x| {
-                            x.push(v);
-                        })
-                        .or_insert(vec![v]);
+                    graph.add_edge(left.to_owned(), token.to_owned());
                 }
             }
         }
 
-        let vertices = graph.keys().copied().collect();
+        let vertices = graph.vertices().collect();
         Ok(Self { graph, vertices })
     }
 }
 
 impl Problem for Snowverload {
+    const YEAR: usize = 2023;
     const DAY: usize = 25;
     const TITLE: &'static str = "snowverload";
     const README: &'static str = include_str!("../README.md");
 
     type ProblemError = anyhow::Error;
-    type P1 = usize;
-    type P2 = i64;
+    type P1 = u64;
+    // there's no puzzle for part two
+    type P2 = OptionalAnswer<u64>;
 
     fn part_one(&mut self) -> Result<Self::P1, Self::ProblemError> {
         self.min_cut()
+            .map(|x| x as u64)
             .ok_or_else(|| anyhow!("count not find answer"))
     }
 
     fn part_two(&mut self) -> Result<Self::P2, Self::ProblemError> {
-        Ok(0)
+        Ok(OptionalAnswer(None))
+    }
+
+    fn describe(&self) -> Description {
+        Description::default()
+            .graph(self.graph.len(), self.graph.edge_count())
+            .range_of(
+                "degree",
+                self.graph
+                    .vertices()
+                    .map(|v| self.graph.neighbours(v).len()),
+            )
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use aoc_plumbing::Solution;
-
     use super::*;
+    use aoc_plumbing::Solution;
 
     #[test]
     #[ignore]
     fn full_dataset() {
         let input = std::fs::read_to_string("input.txt").expect("Unable to load input");
         let solution = Snowverload::solve(&input).unwrap();
-        assert_eq!(solution, Solution::new(554064, 0));
+        assert_eq!(solution, Solution::new(554064, OptionalAnswer(None)));
+
+        let instance = Snowverload::instance(&input).unwrap();
+        assert_eq!(instance.min_cut_exact(), Some((3, 554064)));
+        assert_eq!(instance.min_cut_betweenness(20, 25), Some(554064));
+        assert_eq!(instance.min_cut_contraction(2, 0), Some((3, 554064)));
     }
 
     #[test]
     fn example() {
         let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
         let solution = Snowverload::solve(&input).unwrap();
-        assert_eq!(solution, Solution::new(54, 0));
+        assert_eq!(solution, Solution::new(54, OptionalAnswer(None)));
+
+        let instance = Snowverload::instance(&input).unwrap();
+        assert_eq!(instance.min_cut_exact(), Some((3, 54)));
+    }
+
+    #[test]
+    fn betweenness_matches_flow() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = Snowverload::instance(&input).unwrap();
+        let expected = instance.min_cut();
+        assert_eq!(expected, Some(54));
+
+        for seed in 0..10 {
+            assert_eq!(instance.min_cut_betweenness(15, seed), expected);
+        }
+
+        // with no samples nothing stands out, so there's nothing to remove
+        assert_eq!(instance.min_cut_betweenness(0, 0), None);
+    }
+
+    #[test]
+    fn contraction_matches_exact() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = Snowverload::instance(&input).unwrap();
+        for seed in 0..5 {
+            assert_eq!(instance.min_cut_contraction(10, seed), Some((3, 54)));
+        }
+        assert_eq!(instance.min_cut_contraction(0, 0), None);
+
+        let disconnected = Snowverload::instance("a: b\nc: d").unwrap();
+        assert_eq!(disconnected.min_cut_contraction(5, 0).unwrap().0, 0);
+
+        // too many components to split by brute force once every edge has
+        // been contracted
+        let pairs = (0..8)
+            .map(|i| format!("a{}: b{}", i, i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let pairs = Snowverload::instance(&pairs).unwrap();
+        assert_eq!(pairs.min_cut_exact().unwrap().0, 0);
+        for seed in 0..5 {
+            assert_eq!(pairs.min_cut_contraction(1, seed).unwrap().0, 0);
+        }
+        assert_eq!(
+            Snowverload::instance("a: b")
+                .unwrap()
+                .min_cut_contraction(1, 0),
+            Some((1, 1))
+        );
+    }
+
+    #[test]
+    fn example_cut() {
+        let input = std::fs::read_to_string("example.txt").expect("Unable to load input");
+        let instance = Snowverload::instance(&input).unwrap();
+        let cut = instance.cut().unwrap();
+        assert_eq!(
+            cut.edges,
+            vec![
+                ("bvb".to_string(), "cmg".to_string()),
+                ("hfx".to_string(), "pzl".to_string()),
+                ("jqt".to_string(), "nvd".to_string()),
+            ]
+        );
+
+        let (a, b) = cut.components;
+        let (small, large) = if a.len() < b.len() { (a, b) } else { (b, a) };
+        assert_eq!(
+            small,
+            vec!["bvb", "hfx", "jqt", "ntq", "rhn", "xhk"]
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(large.len(), 9);
     }
+
+    #[test]
+    fn long_and_colliding_labels() {
+        // "a" and "0a" are the same number in radix 36, and the long labels
+        // would overflow a u16
+        let input = "a: 0a longlabel1 longlabel2 longlabel3
+0a: longlabel1 longlabel2 longlabel3
+longlabel1: longlabel2 longlabel3
+longlabel2: longlabel3
+b: 0b longlabel4 longlabel5 longlabel6 longlabel1
+0b: longlabel4 longlabel5 longlabel6 a
+longlabel4: longlabel5 longlabel6 0a
+longlabel5: longlabel6";
+        let instance = Snowverload::instance(input).unwrap();
+        assert_eq!(instance.graph.len(), 10);
+
+        let cut = instance.cut().unwrap();
+        assert_eq!(
+            cut.edges,
+            vec![
+                ("0a".to_string(), "longlabel4".to_string()),
+                ("0b".to_string(), "a".to_string()),
+                ("b".to_string(), "longlabel1".to_string()),
+            ]
+        );
+        assert_eq!(instance.min_cut_exact(), Some((3, 25)));
+    }
+
+    #[test]
+    fn cut_of_two() {
+        // two complete graphs of 5 vertices joined by 2 edges
+        let input = "aa: ab ac ad ae ba
+ab: ac ad ae bb
+ac: ad ae
+ad: ae
+ba: bb bc bd be
+bb: bc bd be
+bc: bd be
+bd: be";
+        let mut instance = Snowverload::instance(input).unwrap();
+        assert_eq!(instance.min_cut_exact(), Some((2, 25)));
+        assert_eq!(instance.part_one().unwrap(), 25);
+    }
+
+    aoc_plumbing::parsing_never_panics!(Snowverload);
 }
@@ -8,12 +8,13 @@ pub struct {{project-name|upper_camel_case}};
 impl FromStr for {{project-name|upper_camel_case}} {
     type Err = anyhow::Error;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(_s: &str) -> Result<Self, Self::Err> {
         Ok(Self)
     }
 }
 
 impl Problem for {{project-name|upper_camel_case}} {
+    const YEAR: usize = {{year}};
     const DAY: usize = {{day}};
     const TITLE: &'static str = "{{project-name|title_case|downcase}}";
     const README: &'static str = include_str!("../README.md");
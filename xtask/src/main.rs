@@ -0,0 +1,286 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
+
+/// Workspace maintenance tasks
+#[derive(Parser)]
+#[command(name = "xtask")]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    NewDay(NewDay),
+}
+
+/// Generate the crate for a new day and wire it into the cli and benchmarks.
+#[derive(clap::Args)]
+struct NewDay {
+    /// The day, e.g. `1`.
+    day: usize,
+
+    /// The problem title, e.g. `calorie-counting` or "Calorie Counting".
+    title: String,
+
+    /// The year of the event.
+    #[clap(short, long, default_value_t = 2023)]
+    year: usize,
+}
+
+/// The various forms of a day's name needed by the templates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Names {
+    year: usize,
+    day: usize,
+    /// `calorie-counting`
+    project: String,
+    /// `calorie_counting`
+    crate_name: String,
+    /// `CalorieCounting`
+    struct_name: String,
+    /// `Calorie Counting`
+    title: String,
+    /// `day-001-calorie-counting`
+    dir: String,
+    /// `day_2022_001`, so benchmarks from different years don't collide
+    bench_fn: String,
+}
+
+impl Names {
+    fn new(year: usize, day: usize, title: &str) -> Result<Self> {
+        let words: Vec<String> = title
+            .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_lowercase())
+            .collect();
+
+        if words.is_empty() {
+            bail!("title must not be empty");
+        }
+
+        if let Some(w) = words
+            .iter()
+            .find(|w| !w.chars().all(|c| c.is_ascii_alphanumeric()))
+        {
+            bail!("title contains invalid word: {}", w);
+        }
+
+        let capitalize = |w: &String| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        };
+
+        let project = words.join("-");
+
+        Ok(Self {
+            year,
+            day,
+            crate_name: words.join("_"),
+            struct_name: words.iter().map(capitalize).collect(),
+            title: words.iter().map(capitalize).collect::<Vec<_>>().join(" "),
+            dir: format!("day-{:03}-{}", day, project),
+            bench_fn: format!("day_{}_{:03}", year, day),
+            project,
+        })
+    }
+
+    /// Fills in the placeholders used by the cargo-generate template.
+    fn render(&self, template: &str) -> String {
+        template
+            .replace(
+                "{{project-name|title_case|downcase}}",
+                &self.title.to_lowercase(),
+            )
+            .replace("{{project-name|title_case}}", &self.title)
+            .replace("{{project-name|upper_camel_case}}", &self.struct_name)
+            .replace("{{project-name}}", &self.project)
+            .replace("{{crate_name}}", &self.crate_name)
+            .replace("{{year}}", &self.year.to_string())
+            .replace("{{day}}", &self.day.to_string())
+    }
+
+    fn dependency(&self) -> String {
+        format!("{} = {{ path = \"../{}\" }}", self.project, self.dir)
+    }
+
+    fn import(&self) -> String {
+        format!("use {}::{};", self.crate_name, self.struct_name)
+    }
+}
+
+/// Inserts `text` on the line(s) before `marker`, matching its indentation.
+fn insert_before_marker(contents: &str, marker: &str, text: &str) -> Result<String> {
+    let idx = contents
+        .find(marker)
+        .with_context(|| format!("could not find {}", marker))?;
+    let line_start = contents[..idx].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let indent = &contents[line_start..idx];
+
+    let mut ret = String::with_capacity(contents.len() + text.len());
+    ret.push_str(&contents[..line_start]);
+    for line in text.lines() {
+        if !line.is_empty() {
+            ret.push_str(indent);
+        }
+        ret.push_str(line);
+        ret.push('\n');
+    }
+    ret.push_str(&contents[line_start..]);
+
+    Ok(ret)
+}
+
+/// Adds a dependency after the last day crate in a `Cargo.toml`.
+fn add_dependency(contents: &str, dependency: &str) -> Result<String> {
+    let idx = contents
+        .rfind("path = \"../day-")
+        .context("could not find existing day dependencies")?;
+    let line_end = contents[idx..]
+        .find('\n')
+        .map(|i| idx + i + 1)
+        .unwrap_or(contents.len());
+
+    Ok(format!(
+        "{}{}\n{}",
+        &contents[..line_end],
+        dependency,
+        &contents[line_end..]
+    ))
+}
+
+fn edit(path: &Path, f: impl FnOnce(&str) -> Result<String>) -> Result<()> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("could not read {}", path.display()))?;
+    let updated = f(&contents).with_context(|| format!("could not update {}", path.display()))?;
+    fs::write(path, updated).with_context(|| format!("could not write {}", path.display()))
+}
+
+impl NewDay {
+    fn run(&self, root: &Path) -> Result<()> {
+        let names = Names::new(self.year, self.day, &self.title)?;
+        let template = root.join("template");
+        let dest = root.join(&names.dir);
+
+        if dest.exists() {
+            bail!("{} already exists", dest.display());
+        }
+
+        println!("Creating {}", names.dir);
+        fs::create_dir_all(dest.join("src"))?;
+        for file in [
+            "Cargo.toml",
+            "README.md",
+            "example.txt",
+            "input.txt",
+            "src/lib.rs",
+        ] {
+            let contents = fs::read_to_string(template.join(file))
+                .with_context(|| format!("could not read template {}", file))?;
+            fs::write(dest.join(file), names.render(&contents))?;
+        }
+
+        println!("Adding {} to the cli", names.project);
+        edit(&root.join("aoc-cli/Cargo.toml"), |c| {
+            add_dependency(c, &names.dependency())
+        })?;
+        edit(&root.join("aoc-cli/src/cli.rs"), |c| {
+            let c = insert_before_marker(c, "// import_marker", &names.import())?;
+            insert_before_marker(
+                &c,
+                "// command_marker",
+                &format!("({}, {}),", names.struct_name, names.day),
+            )
+        })?;
+
+        println!("Adding {} to the benchmarks", names.project);
+        edit(&root.join("aoc-benchmarking/Cargo.toml"), |c| {
+            add_dependency(c, &names.dependency())
+        })?;
+        edit(&root.join("aoc-benchmarking/benches/bench_main.rs"), |c| {
+            let c = insert_before_marker(c, "// import_marker", &names.import())?;
+            insert_before_marker(
+                &c,
+                "// bench_marker",
+                &format!(
+                    "(\n    {},\n    \"../{}/input.txt\",\n    {},\n    \"Part 1\",\n    \"Part 2\"\n),",
+                    names.bench_fn, names.dir, names.struct_name
+                ),
+            )
+        })?;
+
+        Ok(())
+    }
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives in the workspace")
+        .to_path_buf()
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Commands::NewDay(cmd) => cmd.run(&workspace_root()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        let names = Names::new(2022, 7, "Calorie Counting").unwrap();
+        assert_eq!(names, Names::new(2022, 7, "calorie-counting").unwrap());
+        assert_eq!(names.project, "calorie-counting");
+        assert_eq!(names.crate_name, "calorie_counting");
+        assert_eq!(names.struct_name, "CalorieCounting");
+        assert_eq!(names.title, "Calorie Counting");
+        assert_eq!(names.dir, "day-007-calorie-counting");
+        assert_eq!(names.bench_fn, "day_2022_007");
+        assert_eq!(
+            names.render(
+                "# Day {{day}}: {{project-name|title_case}} ({{project-name|title_case|downcase}})"
+            ),
+            "# Day 7: Calorie Counting (calorie counting)"
+        );
+        assert_eq!(
+            names.render("const YEAR: usize = {{year}};"),
+            "const YEAR: usize = 2022;"
+        );
+
+        assert!(Names::new(2023, 1, " - ").is_err());
+        assert!(Names::new(2023, 1, "foo/bar").is_err());
+    }
+
+    #[test]
+    fn markers() {
+        let contents = "fn main() {\n    foo();\n    // marker\n}\n";
+        let updated = insert_before_marker(contents, "// marker", "bar(\n    1,\n);").unwrap();
+        assert_eq!(
+            updated,
+            "fn main() {\n    foo();\n    bar(\n        1,\n    );\n    // marker\n}\n"
+        );
+        assert!(insert_before_marker(contents, "// missing", "bar();").is_err());
+    }
+
+    #[test]
+    fn dependencies() {
+        let contents = "[dependencies]\na = { path = \"../day-001-a\" }\n\n[features]\n";
+        let updated = add_dependency(contents, "b = { path = \"../day-002-b\" }").unwrap();
+        assert_eq!(
+            updated,
+            "[dependencies]\na = { path = \"../day-001-a\" }\nb = { path = \"../day-002-b\" }\n\n[features]\n"
+        );
+    }
+}